@@ -1,8 +1,10 @@
-use std::collections::HashMap;
 use std::ffi::{c_uchar, c_uint, c_ushort};
+use std::fmt::{Display, Formatter};
+use crate::can::CanFdTimingCfg;
 use crate::can::frame::ZCanHeaderV1;
+use crate::device::{DeviceBackend, ZCanDeviceType};
 use crate::error::ZCanError;
-use super::constant::{BRP, CANERR_FRAME_LENGTH, SJW, SMP, TSEG1, TSEG2, ZCanChlMode, ZCanChlType, ZCanFilterType};
+use super::constant::{CANERR_FRAME_LENGTH, ZCanChlMode, ZCanChlType, ZCanFilterType};
 
 /// Linux USBCAN USBCAN_4E(8_E) USBCANFD_800U and windows
 #[repr(C)]
@@ -39,6 +41,14 @@ impl ZCanChlCfg {
             mode: mode as u8,
         })
     }
+    #[inline(always)]
+    pub fn timing0(&self) -> u32 {
+        self.timing0 as u32
+    }
+    #[inline(always)]
+    pub fn timing1(&self) -> u32 {
+        self.timing1 as u32
+    }
 }
 
 /// Linux USBCAN_4E_8E USBCANFD_800U and windows
@@ -82,6 +92,14 @@ impl ZCanFdChlCfgV1 {
             reserved: Default::default(),
         })
     }
+    #[inline(always)]
+    pub fn timing0(&self) -> u32 {
+        self.timing0
+    }
+    #[inline(always)]
+    pub fn timing1(&self) -> u32 {
+        self.timing1
+    }
 }
 
 /// Linux USBCANFD
@@ -95,21 +113,10 @@ pub struct ZCanFdChlCfgSet {
     brp: c_ushort,
 }
 
-impl TryFrom<&HashMap<String, u32>> for ZCanFdChlCfgSet {
-    type Error = ZCanError;
-    fn try_from(value: &HashMap<String, u32>) -> Result<Self, Self::Error> {
-        let tseg1 = value.get(TSEG1)
-            .ok_or(ZCanError::ConfigurationError(format!("`{}` is not configured in file!", TSEG1)))?;
-        let tseg2 = value.get(TSEG2)
-            .ok_or(ZCanError::ConfigurationError(format!("ZLGCAN - `{}` is not configured in file!", TSEG2)))?;
-        let sjw = value.get(SJW)
-            .ok_or(ZCanError::ConfigurationError(format!("ZLGCAN - `{}` is not configured in file!", SJW)))?;
-        let smp = value.get(SMP)
-            .ok_or(ZCanError::ConfigurationError(format!("ZLGCAN - `{}` is not configured in file!", SMP)))?;
-        let brp = value.get(BRP)
-            .ok_or(ZCanError::ConfigurationError(format!("ZLGCAN - `{}` is not configured in file!", BRP)))?;
-
-        Ok(Self::new(*tseg1, *tseg2, *sjw, *smp, *brp))
+impl From<&CanFdTimingCfg> for ZCanFdChlCfgSet {
+    #[inline]
+    fn from(value: &CanFdTimingCfg) -> Self {
+        Self::new(value.tseg1, value.tseg2, value.sjw, value.smp, value.brp)
     }
 }
 
@@ -132,6 +139,31 @@ impl ZCanFdChlCfgSet {
             | (self.tseg2 as u32 & 0x7f) << 8
             | (self.tseg1 as u32)
     }
+    /// Unpack the timing of `get_timing`, only used for USBCANFD-800U
+    #[inline(always)]
+    pub fn from_timing(timing: u32) -> Self {
+        Self::new(timing & 0xff, (timing >> 8) & 0x7f, (timing >> 15) & 0x7f, 0, timing >> 22)
+    }
+    #[inline(always)]
+    pub fn tseg1(&self) -> u32 {
+        self.tseg1 as u32
+    }
+    #[inline(always)]
+    pub fn tseg2(&self) -> u32 {
+        self.tseg2 as u32
+    }
+    #[inline(always)]
+    pub fn sjw(&self) -> u32 {
+        self.sjw as u32
+    }
+    #[inline(always)]
+    pub fn smp(&self) -> u32 {
+        self.smp as u32
+    }
+    #[inline(always)]
+    pub fn brp(&self) -> u32 {
+        self.brp as u32
+    }
 }
 /// Linux USBCANFD
 #[repr(C)]
@@ -230,6 +262,7 @@ impl From<ZCanFdChlCfgV2> for ZCanChlCfgV2 {
 #[allow(non_snake_case)]
 #[repr(C)]
 #[derive(Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ZCanChlStatus {
     pub errInterrupt: c_uchar,  /**< not used(for backward compatibility) */
     pub regMode: c_uchar,       /**< not used */
@@ -289,3 +322,221 @@ impl From<&ZCanChlError> for ZCanChlErrorV2 {
         unsafe { value.v2 }
     }
 }
+
+// the error code bits of `ZCanChlErrorV2`
+pub(crate) const ERR_CAN_OVERFLOW: u32      = 0x0001;
+pub(crate) const ERR_CAN_ERRALARM: u32      = 0x0002;
+pub(crate) const ERR_CAN_PASSIVE: u32       = 0x0004;
+pub(crate) const ERR_CAN_LOSE: u32          = 0x0008;
+pub(crate) const ERR_CAN_BUSERR: u32        = 0x0010;
+pub(crate) const ERR_CAN_BUSOFF: u32        = 0x0020;
+pub(crate) const ERR_CAN_BUFFER_OVERFLOW: u32 = 0x0040;
+
+// the error type(`hdr.can_id`) of `ZCanChlErrorV1`
+pub(crate) const ZCAN_ERR_TYPE_BUS_ERR: u32  = 1;
+pub(crate) const ZCAN_ERR_TYPE_CTRL_ERR: u32 = 2;
+// the node state(`data[1]`) of `ZCanChlErrorV1`
+pub(crate) const ZCAN_NODE_STATE_WARNING: u8 = 2;
+pub(crate) const ZCAN_NODE_STATE_PASSIVE: u8 = 3;
+pub(crate) const ZCAN_NODE_STATE_BUSOFF: u8  = 4;
+
+/// The protocol(bus) error type.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ProtocolError {
+    Bit,
+    Stuff,
+    Crc,
+    Form,
+    Ack,
+    Overload,
+    /// The error that the type is not given by device.
+    Other,
+}
+
+/// The RX and TX error counters.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ErrorCounters {
+    pub rx: u8,
+    pub tx: u8,
+}
+
+/// The decoded channel error.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ChannelError {
+    /// The error counters reached the warning limit(96).
+    ErrorWarning(ErrorCounters),
+    /// The error counters reached 128.
+    ErrorPassive(ErrorCounters),
+    BusOff(ErrorCounters),
+    /// The arbitration is lost at the bit position.
+    ArbitrationLost { bit: u8 },
+    /// The protocol error, `rx` is `true` if it occurred while receiving.
+    Protocol { kind: ProtocolError, rx: bool, counters: ErrorCounters },
+    /// The receive buffer of controller or driver is overflowed.
+    Overflow,
+}
+
+impl ChannelError {
+    /// Decode the channel error by the device type, the empty result means no error.
+    /// USBCANFD on linux, virtual and offline device(except windows) use `ZCanChlErrorV1`, the others use `ZCanChlErrorV2`.
+    pub fn decode(dev_type: ZCanDeviceType, error: &ZCanChlError) -> Vec<Self> {
+        if Self::is_v1(dev_type) {
+            Self::from_v1(&ZCanChlErrorV1::from(error))
+        }
+        else {
+            Self::from_v2(&ZCanChlErrorV2::from(error))
+        }
+    }
+
+    #[inline]
+    fn is_v1(dev_type: ZCanDeviceType) -> bool {
+        let usbcanfd = matches!(dev_type, ZCanDeviceType::ZCAN_USBCANFD_100U
+            | ZCanDeviceType::ZCAN_USBCANFD_200U | ZCanDeviceType::ZCAN_USBCANFD_MINI);
+        (cfg!(target_os = "linux") && usbcanfd) || matches!(dev_type.capabilities().backend, DeviceBackend::Builtin)
+    }
+
+    /// Decode the `ZCAN_ERR_MSG` of USBCANFD:
+    /// `hdr.can_id` is the error type, `data` is the sub type, node state, RX and TX counters and the arbitration lost position.
+    pub fn from_v1(error: &ZCanChlErrorV1) -> Vec<Self> {
+        let data = &error.data;
+        let counters = ErrorCounters { rx: data[2], tx: data[3] };
+        let mut result = Vec::new();
+        match error.hdr.can_id {
+            ZCAN_ERR_TYPE_BUS_ERR => {
+                let kind = match data[0] {
+                    1 => Some(ProtocolError::Bit),
+                    2 => Some(ProtocolError::Ack),
+                    3 => Some(ProtocolError::Crc),
+                    4 => Some(ProtocolError::Form),
+                    5 => Some(ProtocolError::Stuff),
+                    6 => Some(ProtocolError::Overload),
+                    7 => {
+                        result.push(Self::ArbitrationLost { bit: data[4] });
+                        None
+                    },
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    // the direction is not given
+                    result.push(Self::Protocol { kind, rx: true, counters });
+                }
+            },
+            ZCAN_ERR_TYPE_CTRL_ERR if matches!(data[0], 1..=2) => result.push(Self::Overflow),
+            _ => {},
+        }
+        match data[1] {
+            ZCAN_NODE_STATE_WARNING => result.push(Self::ErrorWarning(counters)),
+            ZCAN_NODE_STATE_PASSIVE => result.push(Self::ErrorPassive(counters)),
+            ZCAN_NODE_STATE_BUSOFF => result.push(Self::BusOff(counters)),
+            _ => {},
+        }
+
+        result
+    }
+
+    /// Decode the `VCI_ERR_INFO`(`ZCAN_CHANNEL_ERR_INFO`):
+    /// `passive_ErrData` is the SJA1000 error code capture(ECC) and the RX and TX counters,
+    /// `arLost_ErrData` is the SJA1000 arbitration lost capture(ALC).
+    pub fn from_v2(error: &ZCanChlErrorV2) -> Vec<Self> {
+        let code = error.error_code;
+        let [ecc, rx, tx] = error.passive_ErrData;
+        let counters = ErrorCounters { rx, tx };
+        let mut result = Vec::new();
+        if code & (ERR_CAN_OVERFLOW | ERR_CAN_BUFFER_OVERFLOW) > 0 {
+            result.push(Self::Overflow);
+        }
+        if code & ERR_CAN_LOSE > 0 {
+            result.push(Self::ArbitrationLost { bit: error.arLost_ErrData & 0x1F });
+        }
+        if code & ERR_CAN_BUSERR > 0 {
+            let kind = match (ecc >> 6, ecc & 0x1F) {
+                (0, _) => ProtocolError::Bit,
+                (1, _) => ProtocolError::Form,
+                (2, _) => ProtocolError::Stuff,
+                // the segment of CRC sequence and delimiter
+                (_, 0x08 | 0x18) => ProtocolError::Crc,
+                // the segment of ACK slot and delimiter
+                (_, 0x19 | 0x1B) => ProtocolError::Ack,
+                _ => ProtocolError::Other,
+            };
+            result.push(Self::Protocol { kind, rx: ecc & 0x20 > 0, counters });
+        }
+        if code & ERR_CAN_BUSOFF > 0 {
+            result.push(Self::BusOff(counters));
+        }
+        else if code & ERR_CAN_PASSIVE > 0 {
+            result.push(Self::ErrorPassive(counters));
+        }
+        else if code & ERR_CAN_ERRALARM > 0 {
+            result.push(Self::ErrorWarning(counters));
+        }
+
+        result
+    }
+}
+
+impl Display for ChannelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ErrorWarning(v) => write!(f, "error warning(RX: {}, TX: {})", v.rx, v.tx),
+            Self::ErrorPassive(v) => write!(f, "error passive(RX: {}, TX: {})", v.rx, v.tx),
+            Self::BusOff(v) => write!(f, "bus off(RX: {}, TX: {})", v.rx, v.tx),
+            Self::ArbitrationLost { bit } => write!(f, "arbitration lost at bit: {}", bit),
+            Self::Protocol { kind, rx, counters } => write!(
+                f, "{:?} error while {}(RX: {}, TX: {})", kind, if *rx { "receiving" } else { "transmitting" }, counters.rx, counters.tx
+            ),
+            Self::Overflow => write!(f, "buffer overflow"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::can::{ZCanChlError, ZCanHeaderV1};
+    use crate::device::ZCanDeviceType;
+    use super::{ChannelError, ErrorCounters, ProtocolError, ZCanChlErrorV1, ZCanChlErrorV2, ERR_CAN_BUSERR, ERR_CAN_BUSOFF, ERR_CAN_ERRALARM, ERR_CAN_LOSE, ERR_CAN_PASSIVE};
+
+    #[test]
+    fn channel_error_v1() {
+        let mut error = ZCanChlErrorV1::default();
+        assert!(ChannelError::from_v1(&error).is_empty());
+
+        error.hdr = ZCanHeaderV1 { can_id: 1, ..Default::default() };
+        error.data = [3, 3, 130, 8, 0, 0, 0, 0];
+        let counters = ErrorCounters { rx: 130, tx: 8 };
+        assert_eq!(ChannelError::from_v1(&error), vec![
+            ChannelError::Protocol { kind: ProtocolError::Crc, rx: true, counters },
+            ChannelError::ErrorPassive(counters),
+        ]);
+        error.data = [7, 1, 0, 0, 5, 0, 0, 0];
+        assert_eq!(ChannelError::from_v1(&error), vec![ChannelError::ArbitrationLost { bit: 5 }]);
+
+        let error = ZCanChlError::from(error);
+        assert_eq!(ChannelError::decode(ZCanDeviceType::ZCAN_VIRTUAL_DEVICE, &error), vec![ChannelError::ArbitrationLost { bit: 5 }]);
+    }
+
+    #[test]
+    fn channel_error_v2() {
+        let error = ZCanChlErrorV2 { error_code: ERR_CAN_BUSERR | ERR_CAN_ERRALARM, passive_ErrData: [0xA8, 100, 0], arLost_ErrData: 0 };
+        let counters = ErrorCounters { rx: 100, tx: 0 };
+        assert_eq!(ChannelError::from_v2(&error), vec![
+            ChannelError::Protocol { kind: ProtocolError::Stuff, rx: true, counters },
+            ChannelError::ErrorWarning(counters),
+        ]);
+
+        // CRC sequence while receiving
+        let error = ZCanChlErrorV2 { error_code: ERR_CAN_BUSERR | ERR_CAN_PASSIVE, passive_ErrData: [0xE8, 128, 0], arLost_ErrData: 0 };
+        assert_eq!(ChannelError::from_v2(&error)[0], ChannelError::Protocol { kind: ProtocolError::Crc, rx: true, counters: ErrorCounters { rx: 128, tx: 0 } });
+        // ACK slot while transmitting
+        let error = ZCanChlErrorV2 { error_code: ERR_CAN_BUSERR | ERR_CAN_BUSOFF, passive_ErrData: [0xD9, 0, 255], arLost_ErrData: 0 };
+        let counters = ErrorCounters { rx: 0, tx: 255 };
+        assert_eq!(ChannelError::from_v2(&error), vec![
+            ChannelError::Protocol { kind: ProtocolError::Ack, rx: false, counters },
+            ChannelError::BusOff(counters),
+        ]);
+
+        let error = ZCanChlError::from(ZCanChlErrorV2 { error_code: ERR_CAN_LOSE, passive_ErrData: [0; 3], arLost_ErrData: 0x6A });
+        assert_eq!(ChannelError::decode(ZCanDeviceType::ZCAN_USBCAN2, &error), vec![ChannelError::ArbitrationLost { bit: 0x0A }]);
+        assert_eq!(ChannelError::ArbitrationLost { bit: 10 }.to_string(), "arbitration lost at bit: 10");
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+/// The synchronization of device clock to host clock.
+///
+/// Each sample is the device time of a received frame and the host time when it was received,
+/// so the offset(host - device) of sample is the real offset plus the latency of transfer.
+/// The min-filter keeps the sample with minimal offset(the minimal latency) in each interval,
+/// and the offset and skew are estimated by linear regression of the filtered samples.
+#[derive(Debug, Clone)]
+pub struct ClockSync {
+    interval: u64,
+    capacity: usize,
+    /// the first sample, the others are relative to it for the precision of `f64`.
+    origin: Option<(u64, i64)>,
+    /// the start of current interval and the minimal sample in it.
+    current: Option<(u64, f64, f64)>,
+    points: VecDeque<(f64, f64)>,
+    /// the offset at origin and the skew.
+    estimate: Option<(f64, f64)>,
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_INTERVAL, Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl ClockSync {
+    /// The default interval(µs) of min-filter.
+    pub const DEFAULT_INTERVAL: u64 = 1_000_000;
+    /// The default count of filtered samples used by regression.
+    pub const DEFAULT_CAPACITY: usize = 60;
+
+    /// Create the synchronization with the interval(µs) of min-filter and the count of filtered samples.
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            capacity: capacity.max(1),
+            origin: Default::default(),
+            current: Default::default(),
+            points: Default::default(),
+            estimate: Default::default(),
+        }
+    }
+
+    /// Drop all samples.
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::new(self.interval, self.capacity);
+    }
+
+    /// Add a sample of device time(µs) and host time(µs).
+    pub fn add_sample(&mut self, device: u64, host: u64) {
+        let offset = host as i64 - device as i64;
+        let (device0, offset0) = *self.origin.get_or_insert((device, offset));
+        let x = device.wrapping_sub(device0) as i64 as f64;
+        let y = (offset - offset0) as f64;
+
+        match self.current {
+            Some((start, _, _)) if device.saturating_sub(start) >= self.interval => {
+                self.push_current();
+                self.current = Some((device, x, y));
+            },
+            Some((start, _, min)) if y < min => self.current = Some((start, x, y)),
+            Some(_) => {},
+            None => self.current = Some((device, x, y)),
+        }
+
+        self.estimate = self.regression();
+    }
+
+    /// The estimated offset(µs) of host time to device time at the device time.
+    pub fn offset(&self, device: u64) -> Option<i64> {
+        let (device0, offset0) = self.origin?;
+        let (offset, skew) = self.estimate?;
+        let x = device.wrapping_sub(device0) as i64 as f64;
+        Some(offset0 + (offset + skew * x).round() as i64)
+    }
+
+    /// The estimated skew of device clock, e.g. `1e-5` means the host clock is 10ppm faster than device.
+    #[inline]
+    pub fn skew(&self) -> Option<f64> {
+        self.estimate.map(|(_, skew)| skew)
+    }
+
+    /// Convert the device time(µs) to host time(µs).
+    #[inline]
+    pub fn host_time(&self, device: u64) -> Option<u64> {
+        self.offset(device)
+            .map(|offset| (device as i64 + offset).max(0) as u64)
+    }
+
+    fn push_current(&mut self) {
+        if let Some((_, x, y)) = self.current.take() {
+            if self.points.len() >= self.capacity {
+                self.points.pop_front();
+            }
+            self.points.push_back((x, y));
+        }
+    }
+
+    /// The least squares fitting of filtered samples, the current interval is included.
+    fn regression(&self) -> Option<(f64, f64)> {
+        let points = self.points.iter()
+            .copied()
+            .chain(self.current.map(|(_, x, y)| (x, y)))
+            .collect::<Vec<_>>();
+        let n = points.len() as f64;
+        if points.is_empty() {
+            return None;
+        }
+
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let (sxy, sxx) = points.iter()
+            .fold((0., 0.), |(sxy, sxx), (x, y)| {
+                let dx = x - mean_x;
+                (sxy + dx * (y - mean_y), sxx + dx * dx)
+            });
+        let skew = if sxx > 0. { sxy / sxx } else { 0. };
+
+        Some((mean_y - skew * mean_x, skew))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClockSync;
+
+    #[test]
+    fn test_clock_sync() {
+        let mut sync = ClockSync::new(1_000_000, 60);
+        assert_eq!(sync.host_time(0), None);
+
+        // the host clock is 50ppm faster, and the latency is 100~900µs
+        let offset = 1_700_000_000_000_000_u64;
+        for i in 0..600_u64 {
+            let device = i * 100_000;
+            let host = offset + device + device / 20_000 + 100 + (i * 7919 % 9) * 100;
+            sync.add_sample(device, host);
+        }
+
+        let skew = sync.skew().unwrap();
+        assert!((skew - 5e-5).abs() < 1e-6, "{}", skew);
+        let device = 60_000_000;
+        let expect = offset + device + device / 20_000 + 100;
+        let host = sync.host_time(device).unwrap();
+        assert!(host.abs_diff(expect) < 50, "{} - {}", host, expect);
+
+        sync.reset();
+        assert_eq!(sync.skew(), None);
+        sync.add_sample(10, 1010);
+        assert_eq!((sync.host_time(20), sync.skew()), (Some(1020), Some(0.)));
+    }
+}
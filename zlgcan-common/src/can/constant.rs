@@ -2,6 +2,7 @@ use std::fmt::{Display, Formatter};
 use crate::error::ZCanError;
 
 pub(crate) const BITRATE_CFG_FILENAME: &str = "bitrate.cfg.yaml";
+pub(crate) const BITRATE_CFG_DEFAULT: &str = include_str!("bitrate.default.yaml");
 pub(crate) const TIMING0: &str = "timing0";
 pub(crate) const TIMING1: &str = "timing1";
 pub const TSEG1: &str = "tseg1";     // Time Segment 1
@@ -28,6 +29,7 @@ pub(crate) const TIME_FLAG_VALID: u8 = 1;
 #[repr(C)]
 #[allow(non_camel_case_types)]
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum ZCanFrameType {
     CAN = 0,
     CANFD = 1,
@@ -58,6 +60,7 @@ impl Display for ZCanFrameType {
 
 #[repr(C)]
 #[derive(Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum ZCanChlMode {
     #[default]
     Normal = 0,
@@ -77,6 +80,7 @@ impl TryFrom<u8> for ZCanChlMode {
 
 #[allow(non_camel_case_types)]
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum ZCanFdStd {
     CANFD_ISO = 0,
     CANFD_NON_ISO = 1,
@@ -96,6 +100,7 @@ impl TryFrom<u8> for ZCanFdStd {
 #[repr(C)]
 #[allow(non_camel_case_types)]
 #[derive(Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum ZCanChlType {
     #[default]
     CAN = 0,
@@ -116,6 +121,7 @@ impl TryFrom<u8> for ZCanChlType {
 }
 
 #[derive(Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum ZCanFilterType {
     #[default]
     Double = 0,
@@ -134,6 +140,7 @@ impl TryFrom<u8> for ZCanFilterType {
 }
 
 #[derive(Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum ZCanTxMode {
     #[default]
     Normal = 0,             //**< normal transmission */
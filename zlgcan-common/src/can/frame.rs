@@ -1,8 +1,9 @@
 use std::ffi::{c_uchar, c_uint, c_ushort};
-use isotp_rs::can::{CAN_FRAME_MAX_SIZE, CANFD_FRAME_MAX_SIZE, EFF_MASK, IdentifierFlags, SFF_MASK};
+use isotp_rs::can::{CAN_FRAME_MAX_SIZE, CANFD_FRAME_MAX_SIZE, DEFAULT_PADDING, EFF_MASK, IdentifierFlags, SFF_MASK};
 use crate::can::TIME_FLAG_VALID;
 use crate::error::ZCanError;
-use crate::utils::data_resize;
+use crate::utils::data_padded;
+use super::message::{can_length_error, padded_len};
 use super::constant::{ZCanHdrInfoField, CANFD_BRS, CANFD_ESI};
 
 #[repr(C)]
@@ -61,7 +62,6 @@ impl NewZCanFrame for ZCanFrameV1 {
         where
             T: AsRef<[u8]> {
         zcan_frame_new(can_id, channel, data, info, |id, _chl, data, len, info| {
-            let data: [c_uchar; CAN_FRAME_MAX_SIZE] = data.try_into().map_err(|_| ZCanError::MessageConvertFailed)?;
             Ok(Self {
                 can_id: id,
                 timestamp: timestamp as u32,
@@ -153,7 +153,6 @@ impl NewZCanFrame for ZCanFrameV2 {
         where
             T: AsRef<[u8]> {
         zcan_frame_new(can_id, channel, data, info, |id, chl, data, len, info| {
-            let data: [c_uchar; CAN_FRAME_MAX_SIZE] = data.try_into().map_err(|_| ZCanError::MessageConvertFailed)?;
             Ok(Self {
                 hdr: ZCanHeaderV1 {
                     timestamp: timestamp as u32,
@@ -211,7 +210,6 @@ impl NewZCanFrame for ZCanFrameV3 {
         where
             T: AsRef<[u8]> {
         zcan_frame_new2(can_id, channel, data, info,  |id, chl, data, len, info| {
-            let data: [c_uchar; CAN_FRAME_MAX_SIZE] = data.try_into().map_err(|_| ZCanError::MessageConvertFailed)?;
             Ok(Self {
                 hdr: ZCanHeaderV2 {
                     can_id: id,
@@ -253,7 +251,6 @@ impl NewZCanFrame for ZCanFdFrameV1 {
         where
             T: AsRef<[u8]> {
         zcanfd_frame_new(can_id, channel, data, info, |id, chl, data, len, info| {
-            let data: [c_uchar; CANFD_FRAME_MAX_SIZE] = data.try_into().map_err(|_| ZCanError::MessageConvertFailed)?;
             Ok(Self {
                 hdr: ZCanHeaderV1 {
                     timestamp: timestamp as u32,
@@ -299,7 +296,6 @@ impl NewZCanFrame for ZCanFdFrameV2 {
             if info.get_field(ZCanHdrInfoField::IsErrorStateIndicator) > 0 {
                 flag |= CANFD_ESI;
             }
-            let data: [c_uchar; CANFD_FRAME_MAX_SIZE] = data.try_into().map_err(|_| ZCanError::MessageConvertFailed)?;
 
             Ok(Self {
                 hdr: ZCanHeaderV2 {
@@ -321,22 +317,22 @@ fn zcan_frame_new<T, R>(
     channel: u8,
     data: T,
     mut info: ZCanHdrInfo,
-    callback: impl Fn(u32, u8, Vec<u8>, u8, ZCanHdrInfo) -> Result<R, ZCanError>
+    callback: impl Fn(u32, u8, [u8; CAN_FRAME_MAX_SIZE], u8, ZCanHdrInfo) -> Result<R, ZCanError>
 ) -> Result<R, ZCanError>
     where
         T: AsRef<[u8]> {
     match can_id {
         0..=EFF_MASK => {
-            let mut data = Vec::from(data.as_ref());
+            let data = data.as_ref();
             let len = data.len();
             match len {
                 0..=CAN_FRAME_MAX_SIZE => {
                     set_extended(&mut info, can_id);
-                    data_resize(&mut data, CAN_FRAME_MAX_SIZE);
+                    let data = data_padded(data, len, DEFAULT_PADDING);
 
                     callback(can_id, channel, data, len as u8, info)
                 },
-                _ => Err(ZCanError::ParamNotSupported),
+                _ => Err(can_length_error(len)),
             }
         },
         _ => Err(ZCanError::ParamNotSupported),
@@ -348,16 +344,16 @@ fn zcanfd_frame_new<T, R>(
     channel: u8,
     data: T,
     mut info: ZCanHdrInfo,
-    callback: impl Fn(u32, u8, Vec<u8>, u8, ZCanHdrInfo) -> Result<R, ZCanError>
+    callback: impl Fn(u32, u8, [u8; CANFD_FRAME_MAX_SIZE], u8, ZCanHdrInfo) -> Result<R, ZCanError>
 ) -> Result<R, ZCanError>
     where
         T: AsRef<[u8]> {
     if let 0..=EFF_MASK = can_id {
-        let mut data = Vec::from(data.as_ref());
-        let len = data.len();
-        if let ..=CANFD_FRAME_MAX_SIZE = len {
+        let data = data.as_ref();
+        // the length is padded so that the `len` is always matched with the DLC
+        if let Some(len) = padded_len(data.len()) {
             set_extended(&mut info, can_id);
-            data_resize(&mut data, CANFD_FRAME_MAX_SIZE);
+            let data = data_padded(data, len, DEFAULT_PADDING);
 
             callback(can_id, channel, data, len as u8, info)
         }
@@ -376,17 +372,17 @@ fn zcan_frame_new2<T, R>(
     channel: u8,
     data: T,
     mut info: ZCanHdrInfo,
-    callback: impl Fn(u32, u8, Vec<u8>, u8, ZCanHdrInfo) -> Result<R, ZCanError>
+    callback: impl Fn(u32, u8, [u8; CAN_FRAME_MAX_SIZE], u8, ZCanHdrInfo) -> Result<R, ZCanError>
 ) -> Result<R, ZCanError>
     where
         T: AsRef<[u8]> {
     match can_id {
         0..=EFF_MASK => {
-            let mut data = Vec::from(data.as_ref());
+            let data = data.as_ref();
             let len = data.len();
             match len {
                 0..=CAN_FRAME_MAX_SIZE => {
-                    data_resize(&mut data, CAN_FRAME_MAX_SIZE);
+                    let data = data_padded(data, len, DEFAULT_PADDING);
                     set_extended(&mut info, can_id);
 
                     let mut can_id = can_id;
@@ -401,7 +397,7 @@ fn zcan_frame_new2<T, R>(
                     }
                     callback(can_id, channel, data, len as u8, info)
                 },
-                _ => Err(ZCanError::ParamNotSupported),
+                _ => Err(can_length_error(len)),
             }
         },
         _ => Err(ZCanError::ParamNotSupported),
@@ -413,15 +409,14 @@ fn zcanfd_frame_new2<T, R>(
     channel: u8,
     data: T,
     mut info: ZCanHdrInfo,
-    callback: impl Fn(u32, u8, Vec<u8>, u8, ZCanHdrInfo) -> Result<R, ZCanError>
+    callback: impl Fn(u32, u8, [u8; CANFD_FRAME_MAX_SIZE], u8, ZCanHdrInfo) -> Result<R, ZCanError>
 ) -> Result<R, ZCanError>
     where
         T: AsRef<[u8]> {
     if let 0..=EFF_MASK = can_id {
-        let mut data = Vec::from(data.as_ref());
-        let len = data.len();
-        if let ..=CANFD_FRAME_MAX_SIZE = len {
-            data_resize(&mut data, CANFD_FRAME_MAX_SIZE);
+        let data = data.as_ref();
+        if let Some(len) = padded_len(data.len()) {
+            let data = data_padded(data, len, DEFAULT_PADDING);
             set_extended(&mut info, can_id);
 
             let mut can_id = can_id;
@@ -453,8 +448,72 @@ fn set_extended(info: &mut ZCanHdrInfo, can_id: u32) {
 
 #[cfg(test)]
 mod tests {
+    use isotp_rs::can::{DEFAULT_PADDING, frame::Frame, identifier::Id};
     use crate::can::constant::{ZCanFrameType, ZCanTxMode};
-    use super::{ZCanHdrInfo, ZCanHdrInfoField};
+    use crate::can::message::{dlc_to_len, len_to_dlc, padded_len, CanMessage};
+    use crate::{TryFrom, TryFromIterator};
+    use super::{NewZCanFrame, ZCanFdFrameV2, ZCanFrameV3, ZCanHdrInfo, ZCanHdrInfoField};
+
+    #[test]
+    fn frame_convert() -> anyhow::Result<()> {
+        // the message holds no heap memory
+        assert!(!std::mem::needs_drop::<CanMessage>());
+
+        let data = [0x02, 0x10, 0x01];
+        let frame = ZCanFrameV3::new(0x7DF, 1, data, Default::default(), 0)?;
+        let message = <CanMessage as TryFrom<ZCanFrameV3, u64>>::try_from(frame, 0)?;
+        assert_eq!((message.id(), message.channel(), message.data()), (Id::Standard(0x7DF), 1, data.as_slice()));
+        assert_eq!(message.hardware_timestamp(), Some(0));
+
+        let data = [0x55; 48];
+        let frames = vec![ZCanFdFrameV2::new(0x18DAF110, 0, data, Default::default(), 0)?; 2];
+        let messages = Vec::<CanMessage>::try_from_iter(frames, 0)?;
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].is_can_fd() && messages[0].is_extended());
+        assert_eq!(messages[1].data(), data.as_slice());
+
+        let mut message = CanMessage::new_padded(Id::Standard(0x123), &[0x01, 0x02], 12)
+            .ok_or(anyhow::anyhow!("invalid data length"))?;
+        assert_eq!(&message.data()[..2], [0x01, 0x02].as_slice());
+        assert_eq!(message.length(), 12);
+        assert!(message.try_set_can_fd(false).is_err());
+        assert!(message.is_can_fd());
+        assert!(CanMessage::new_padded(Id::Standard(0x123), &[], 65).is_none());
+        // the CAN frame can't hold more than 8 bytes, it's kept as CANFD frame
+        message.set_can_fd(false);
+        assert!(message.is_can_fd());
+        assert_eq!(message.dlc(), Some(9));
+        assert!(<ZCanFrameV3 as TryFrom<CanMessage, u64>>::try_from(message, 0).is_err());
+
+        // the data is padded to the next valid length
+        let frame = ZCanFdFrameV2::new(0x7DF, 0, [0x11; 9], Default::default(), 0)?;
+        assert_eq!(frame.hdr.can_len, 12);
+        assert_eq!(frame.data.data[9..12], [DEFAULT_PADDING; 3]);
+        let message = <CanMessage as TryFrom<ZCanFdFrameV2, u64>>::try_from(frame, 0)?;
+        assert_eq!((message.length(), message.dlc()), (12, Some(9)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn frame_dlc() {
+        for dlc in 0..=15 {
+            let len = dlc_to_len(dlc).unwrap();
+            assert_eq!(len_to_dlc(len), Some(dlc));
+            assert_eq!(padded_len(len), Some(len));
+        }
+        assert_eq!(dlc_to_len(16), None);
+        assert_eq!((len_to_dlc(9), len_to_dlc(65)), (None, None));
+        assert_eq!((padded_len(9), padded_len(33), padded_len(49), padded_len(65)), (Some(12), Some(48), Some(64), None));
+
+        let message = CanMessage::new_with_padding(Id::Standard(0x7DF), &[0x01; 21], 0x00).unwrap();
+        assert!(message.is_can_fd());
+        assert_eq!((message.length(), message.dlc()), (24, Some(12)));
+        assert_eq!(message.data()[20..], [0x01, 0x00, 0x00, 0x00]);
+        let message = CanMessage::new(Id::Standard(0x7DF), &[0x01; 8]).unwrap();
+        assert!(!message.is_can_fd());
+        assert_eq!((message.length(), message.dlc()), (8, Some(8)));
+    }
 
     #[test]
     fn frame_info() {
@@ -1,18 +1,23 @@
 use std::fmt::{Display, Formatter};
-use isotp_rs::can::{CAN_FRAME_MAX_SIZE, CANFD_FRAME_MAX_SIZE, frame::{Frame, Direct}, identifier::Id};
-use crate::utils::{system_timestamp, data_resize};
+use isotp_rs::can::{CAN_FRAME_MAX_SIZE, CANFD_FRAME_MAX_SIZE, DEFAULT_PADDING, frame::{Frame, Direct}, identifier::Id};
+use crate::error::ZCanError;
+use crate::utils::{system_timestamp, data_padded};
 
+/// The CAN(FD) message, the data is stored inline so that no heap allocation is needed.
 #[repr(C)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(into = "RawCanMessage", try_from = "RawCanMessage"))]
 pub struct CanMessage {
+    /// the host time(µs) since UNIX epoch.
     timestamp: u64,
+    hardware_timestamp: Option<u32>,
     arbitration_id: u32,
     is_extended_id: bool,
     is_remote_frame: bool,
     is_error_frame: bool,
     channel: u8,
     length: usize,
-    data: Vec<u8>,
+    data: [u8; CANFD_FRAME_MAX_SIZE],
     is_fd: bool,
     direct: Direct,
     bitrate_switch: bool,
@@ -27,56 +32,14 @@ impl Frame for CanMessage {
     type Channel = u8;
     #[inline]
     fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
-        let length = data.len();
-
-        match is_can_fd(length) {
-            Some(is_fd) => {
-                let id: Id = id.into();
-                Some(Self {
-                    timestamp: 0,
-                    arbitration_id: id.as_raw(),
-                    is_extended_id: id.is_extended(),
-                    is_remote_frame: false,
-                    is_error_frame: false,
-                    channel: Default::default(),
-                    length,
-                    data: data.to_vec(),
-                    is_fd,
-                    direct: Default::default(),
-                    bitrate_switch: false,
-                    error_state_indicator: false,
-                    tx_mode: 0,
-                })
-            },
-            None => None,
-        }
+        Self::new_padded(id, data, data.len())
     }
 
     #[inline]
     fn new_remote(id: impl Into<Id>, len: usize) -> Option<Self> {
-        match is_can_fd(len) {
-            Some(is_fd) => {
-                let id = id.into();
-                let mut data = Vec::new();
-                data_resize(&mut data, len);
-                Some(Self {
-                    timestamp: 0,
-                    arbitration_id: id.as_raw(),
-                    is_extended_id: id.is_extended(),
-                    is_remote_frame: true,
-                    is_error_frame: false,
-                    channel: Default::default(),
-                    length: len,
-                    data,
-                    is_fd,
-                    direct: Default::default(),
-                    bitrate_switch: false,
-                    error_state_indicator: false,
-                    tx_mode: 0,
-                })
-            },
-            None => None,
-        }
+        let mut message = Self::new_padded(id, &[], len)?;
+        message.is_remote_frame = true;
+        Some(message)
     }
 
     #[inline]
@@ -100,16 +63,13 @@ impl Frame for CanMessage {
         self.is_fd
     }
 
+    /// The frame that is longer than 8 bytes is kept as CANFD frame when it's set to CAN frame,
+    /// use `try_set_can_fd` to get the error.
     #[inline]
     fn set_can_fd(&mut self, value: bool) -> &mut Self where Self: Sized {
-        if !value {
-            match self.length {
-                9.. => {
-                    log::warn!("resize a fd-frame to: {}", CAN_FRAME_MAX_SIZE);
-                    self.length = CAN_FRAME_MAX_SIZE;
-                },
-                _ => {},
-            }
+        if !value && self.length > CAN_FRAME_MAX_SIZE {
+            log::warn!("CanMessage - the frame with length: {} is not a valid CAN frame, it's kept as CANFD", self.length);
+            return self;
         }
         self.is_fd = value;
         self
@@ -143,7 +103,7 @@ impl Frame for CanMessage {
 
     #[inline]
     fn set_bitrate_switch(&mut self, value: bool) -> &mut Self where Self: Sized {
-        self.is_error_frame = value;
+        self.bitrate_switch = value;
         self
     }
 
@@ -182,31 +142,16 @@ impl Frame for CanMessage {
 
     #[inline]
     fn data(&self) -> &[u8] {
-        self.data.as_slice()
+        &self.data[..self.length]
     }
 
+    /// The DLC code of frame, `None` if it is a CAN frame that is longer than 8 bytes.
     #[inline]
     fn dlc(&self) -> Option<usize> {
-        let len = self.length;
-        match len {
-            ..=CAN_FRAME_MAX_SIZE => Some(len),
-            ..=CANFD_FRAME_MAX_SIZE => {
-                if !self.is_fd {
-                    return None;
-                }
-                match len {
-                    9..=12 =>  Some(12),
-                    13..=16 => Some(16),
-                    17..=20 => Some(20),
-                    21..=24 => Some(24),
-                    25..=32 => Some(32),
-                    33..=48 => Some(48),
-                    49..=64 => Some(64),
-                    _ => None,
-                }
-            },
-            _ => None,
+        if !self.is_fd && self.length > CAN_FRAME_MAX_SIZE {
+            return None;
         }
+        len_to_dlc(self.length).map(|v| v as usize)
     }
 
     #[inline]
@@ -229,12 +174,66 @@ impl PartialEq for CanMessage {
                 (self.is_extended_id == other.is_extended_id) &&
                 (self.is_error_frame == other.is_error_frame) &&
                 (self.error_state_indicator == other.error_state_indicator) &&
-                (self.data == other.data)
+                (self.data() == other.data())
         }
     }
 }
 
 impl CanMessage {
+    /// Create a data frame of `len` bytes, the data is padded with `DEFAULT_PADDING` if it is shorter than `len`.
+    /// The `len` is rounded up to the next valid length of CANFD frame.
+    #[inline]
+    pub fn new_padded(id: impl Into<Id>, data: &[u8], len: usize) -> Option<Self> {
+        Self::create(id, data, len, DEFAULT_PADDING)
+    }
+
+    /// Create a data frame, the data is padded with `padding` to the next valid length of CANFD frame.
+    #[inline]
+    pub fn new_with_padding(id: impl Into<Id>, data: &[u8], padding: u8) -> Option<Self> {
+        Self::create(id, data, data.len(), padding)
+    }
+
+    fn create(id: impl Into<Id>, data: &[u8], len: usize, padding: u8) -> Option<Self> {
+        let len = padded_len(len)
+            .or_else(|| {
+                log::warn!("CanMessage - invalid data length: {}", len);
+                None
+            })?;
+        let id: Id = id.into();
+        Some(Self {
+            timestamp: 0,
+            hardware_timestamp: None,
+            arbitration_id: id.as_raw(),
+            is_extended_id: id.is_extended(),
+            is_remote_frame: false,
+            is_error_frame: false,
+            channel: Default::default(),
+            length: len,
+            data: data_padded(data, len, padding),
+            is_fd: len > CAN_FRAME_MAX_SIZE,
+            direct: Default::default(),
+            bitrate_switch: false,
+            error_state_indicator: false,
+            tx_mode: 0,
+        })
+    }
+
+    /// Same as `set_can_fd`, but a frame that is longer than 8 bytes can't be set to CAN frame.
+    pub fn try_set_can_fd(&mut self, value: bool) -> Result<&mut Self, ZCanError> {
+        if !value && self.length > CAN_FRAME_MAX_SIZE {
+            return Err(can_length_error(self.length));
+        }
+        self.is_fd = value;
+        Ok(self)
+    }
+    /// The raw ticks of device timestamp, `None` if the message is not received from device.
+    #[inline(always)]
+    pub const fn hardware_timestamp(&self) -> Option<u32> { self.hardware_timestamp }
+    #[inline(always)]
+    pub fn set_hardware_timestamp(&mut self, value: Option<u32>) -> &mut Self {
+        self.hardware_timestamp = value;
+        self
+    }
     #[inline(always)]
     pub const fn tx_mode(&self) -> u8 { self.tx_mode }
     #[inline(always)]
@@ -246,18 +245,280 @@ impl CanMessage {
 
 impl Display for CanMessage {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        <dyn Frame<Channel=u8> as Display>::fmt(self, f)
+        // the timestamp(µs) is formatted as seconds from milliseconds by `Frame`
+        let mut msg = self.clone();
+        msg.timestamp /= 1_000;
+        <dyn Frame<Channel=u8> as Display>::fmt(&msg, f)
+    }
+}
+
+/// Convert the DLC code to data length, e.g. `9` => `12`.
+#[inline]
+pub const fn dlc_to_len(dlc: u8) -> Option<usize> {
+    match dlc {
+        0..=8 => Some(dlc as usize),
+        9 => Some(12),
+        10 => Some(16),
+        11 => Some(20),
+        12 => Some(24),
+        13 => Some(32),
+        14 => Some(48),
+        15 => Some(64),
+        _ => None,
+    }
+}
+
+/// Convert the data length to DLC code, `None` if it is not a valid length of CAN(FD) frame.
+#[inline]
+pub const fn len_to_dlc(len: usize) -> Option<u8> {
+    match len {
+        0..=8 => Some(len as u8),
+        12 => Some(9),
+        16 => Some(10),
+        20 => Some(11),
+        24 => Some(12),
+        32 => Some(13),
+        48 => Some(14),
+        64 => Some(15),
+        _ => None,
     }
 }
 
+/// Round up the data length to the next valid length of CAN(FD) frame, e.g. `9` => `12`.
 #[inline]
-fn is_can_fd(len: usize) -> Option<bool> {
+pub const fn padded_len(len: usize) -> Option<usize> {
     match len {
-        ..=CAN_FRAME_MAX_SIZE => Some(false),
-        ..=CANFD_FRAME_MAX_SIZE => Some(true),
-        _ => {
-            log::warn!("CanMessage - invalid data length: {}", len);
-            None
-        },
+        0..=8 => Some(len),
+        9..=12 => Some(12),
+        13..=16 => Some(16),
+        17..=20 => Some(20),
+        21..=24 => Some(24),
+        25..=32 => Some(32),
+        33..=48 => Some(48),
+        49..=CANFD_FRAME_MAX_SIZE => Some(CANFD_FRAME_MAX_SIZE),
+        _ => None,
+    }
+}
+
+#[inline]
+pub(crate) fn can_length_error(len: usize) -> ZCanError {
+    ZCanError::Other(format!("the data length: {} is out of CAN frame: {}", len, CAN_FRAME_MAX_SIZE))
+}
+
+/// The textual flags of serialized message.
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MessageFlag {
+    Fd,
+    Brs,
+    Esi,
+    Remote,
+    Error,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RawDirect {
+    Transmit,
+    Receive,
+}
+
+/// The serialized form of `CanMessage`.
+///
+/// The id is hex string as candump, 3 digits for standard id and 8 digits for extended id.
+/// The data is hex string, and the length is only present for remote frame.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCanMessage {
+    #[serde(default)]
+    timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hardware_timestamp: Option<u32>,
+    #[serde(default)]
+    channel: u8,
+    id: String,
+    #[serde(default = "RawCanMessage::direct_default")]
+    direct: RawDirect,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    flags: Vec<MessageFlag>,
+    #[serde(default)]
+    data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    length: Option<usize>,
+    #[serde(default)]
+    tx_mode: super::ZCanTxMode,
+}
+
+#[cfg(feature = "serde")]
+impl RawCanMessage {
+    fn direct_default() -> RawDirect { RawDirect::Transmit }
+}
+
+#[cfg(feature = "serde")]
+impl From<CanMessage> for RawCanMessage {
+    fn from(value: CanMessage) -> Self {
+        let id = if value.is_extended_id {
+            format!("{:08X}", value.arbitration_id)
+        }
+        else {
+            format!("{:03X}", value.arbitration_id)
+        };
+        let flags = [
+            (value.is_fd, MessageFlag::Fd),
+            (value.bitrate_switch, MessageFlag::Brs),
+            (value.error_state_indicator, MessageFlag::Esi),
+            (value.is_remote_frame, MessageFlag::Remote),
+            (value.is_error_frame, MessageFlag::Error),
+        ].into_iter()
+            .filter_map(|(v, flag)| v.then_some(flag))
+            .collect();
+        let (data, length) = if value.is_remote_frame {
+            (Default::default(), Some(value.length))
+        }
+        else {
+            (crate::utils::hex_encode(value.data()), None)
+        };
+
+        Self {
+            timestamp: value.timestamp,
+            hardware_timestamp: value.hardware_timestamp,
+            channel: value.channel,
+            id,
+            direct: match value.direct {
+                Direct::Transmit => RawDirect::Transmit,
+                Direct::Receive => RawDirect::Receive,
+            },
+            flags,
+            data,
+            length,
+            tx_mode: super::ZCanTxMode::try_from(value.tx_mode).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawCanMessage> for CanMessage {
+    type Error = String;
+
+    fn try_from(value: RawCanMessage) -> Result<Self, Self::Error> {
+        let raw_id = u32::from_str_radix(&value.id, 16)
+            .map_err(|_| format!("CanMessage - invalid id: `{}`", value.id))?;
+        let extended = value.id.len() > 3;
+        if raw_id > if extended { 0x1FFF_FFFF } else { 0x7FF } {
+            return Err(format!("CanMessage - invalid id: `{}`", value.id));
+        }
+        let id = Id::from_bits(raw_id, extended);
+        let flag = |flag| value.flags.contains(&flag);
+
+        let mut message = if flag(MessageFlag::Remote) {
+            let len = value.length.ok_or("CanMessage - the length of remote frame is missing".to_string())?;
+            Self::new_remote(id, len)
+        }
+        else {
+            let data = crate::utils::hex_decode(&value.data).map_err(|e| format!("CanMessage - {}", e))?;
+            Self::new(id, &data)
+        }
+            .ok_or("CanMessage - invalid data length".to_string())?;
+        if !flag(MessageFlag::Fd) && message.length > CAN_FRAME_MAX_SIZE {
+            return Err(format!("CanMessage - data length: {} requires the flag `fd`", message.length));
+        }
+
+        message.timestamp = value.timestamp;
+        message.hardware_timestamp = value.hardware_timestamp;
+        message.channel = value.channel;
+        message.is_fd = flag(MessageFlag::Fd);
+        message.bitrate_switch = flag(MessageFlag::Brs);
+        message.error_state_indicator = flag(MessageFlag::Esi);
+        message.is_error_frame = flag(MessageFlag::Error);
+        message.direct = match value.direct {
+            RawDirect::Transmit => Direct::Transmit,
+            RawDirect::Receive => Direct::Receive,
+        };
+        message.tx_mode = value.tx_mode as u8;
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isotp_rs::can::{frame::Frame, identifier::Id};
+    use super::CanMessage;
+
+    #[test]
+    fn test_bitrate_switch() -> anyhow::Result<()> {
+        let mut message = CanMessage::new(Id::from_bits(0x7DF, false), &[0x02, 0x10, 0x01])
+            .ok_or(anyhow::anyhow!("invalid data"))?;
+        message.set_can_fd(true)
+            .set_bitrate_switch(true);
+        assert!(message.is_bitrate_switch());
+        assert!(!message.is_error_frame());
+
+        message.set_bitrate_switch(false);
+        assert!(!message.is_bitrate_switch());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> anyhow::Result<()> {
+        let mut message = CanMessage::new(Id::from_bits(0x7DF, false), &[0x01])
+            .ok_or(anyhow::anyhow!("invalid data"))?;
+        // the timestamp(µs) is displayed in milliseconds
+        message.set_timestamp(Some(1_700_000_000_123_456));
+        assert_eq!(message.to_string(), "1700000000.123 0      7df     Tx d  1 01 ");
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() -> anyhow::Result<()> {
+        use isotp_rs::can::frame::Direct;
+        use crate::can::{ZCanChlType, ZCanTxMode};
+
+
+        let mut message = CanMessage::new(Id::from_bits(0x18DAF110, true), &[0x02, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55])
+            .ok_or(anyhow::anyhow!("invalid data"))?;
+        message.set_timestamp(Some(1234))
+            .set_channel(1)
+            .set_direct(Direct::Receive)
+            .set_bitrate_switch(true)
+            .set_tx_mode(ZCanTxMode::SelfReception as u8);
+        let s = serde_yaml::to_string(&message)?;
+        assert_eq!(s, "\
+timestamp: 1234
+channel: 1
+id: 18DAF110
+direct: receive
+flags:
+- fd
+- brs
+data: 021001000000000055AAAAAA
+tx_mode: self_reception
+");
+        let other: CanMessage = serde_yaml::from_str(&s)?;
+        assert_eq!(other, message);
+        assert_eq!((other.timestamp(), other.channel(), other.direct()), (1234, 1, Direct::Receive));
+        assert!(other.is_can_fd() && other.is_bitrate_switch() && !other.is_esi());
+        assert_eq!((other.length(), other.dlc()), (12, Some(9)));
+
+        let message: CanMessage = serde_yaml::from_str("{ id: 7df, data: 02 10 01 }")?;
+        assert_eq!(message.id(), Id::from_bits(0x7DF, false));
+        assert_eq!(message.data(), [0x02, 0x10, 0x01].as_slice());
+        assert_eq!(message.direct(), Direct::Transmit);
+
+        let message: CanMessage = serde_yaml::from_str("{ id: 00000123, flags: [remote], length: 8 }")?;
+        assert!(message.is_remote() && message.is_extended());
+        assert_eq!(message.length(), 8);
+        assert!(serde_yaml::to_string(&message)?.contains("length: 8"));
+
+        for s in ["{ id: 800, data: '' }", "{ id: 7df, data: 0210011 }", "{ id: 7df, data: 000000000000000000 }", "{ id: 7df, flags: [remote] }"] {
+            assert!(serde_yaml::from_str::<CanMessage>(s).is_err(), "{}", s);
+        }
+
+        assert_eq!(serde_yaml::to_string(&ZCanChlType::CANFD_NON_ISO)?, "canfd_non_iso\n");
+        assert!(matches!(serde_yaml::from_str("self_reception_once")?, ZCanTxMode::SelfReceptionOnce));
+        Ok(())
     }
 }
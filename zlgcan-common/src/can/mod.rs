@@ -1,42 +1,170 @@
 mod channel;
+mod clock;
 mod constant;
 mod frame;
 mod message;
+mod timestamp;
+mod timing;
 mod util;
 
 pub use channel::*;
+pub use clock::*;
 pub use constant::*;
 pub use frame::*;
 pub use message::*;
+pub use timestamp::*;
+pub use timing::*;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::read_to_string;
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::{Arc, Weak};
 use serde::Deserialize;
 use crate::device::ZCanDeviceType;
 use crate::error::ZCanError;
 
+/// The SJA1000 bit timing entry of configuration file.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct CanTimingCfg {
+    pub timing0: u32,
+    pub timing1: u32,
+}
+
+/// The CANFD bit timing entry of configuration file, the values are register values of device.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct CanFdTimingCfg {
+    pub tseg1: u32,
+    pub tseg2: u32,
+    pub sjw: u32,
+    pub smp: u32,
+    pub brp: u32,
+}
+
+/// The bitrate entry of configuration file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BitrateTimingCfg {
+    Can(CanTimingCfg),
+    CanFd(CanFdTimingCfg),
+}
+
+impl TryFrom<HashMap<String, u32>> for BitrateTimingCfg {
+    type Error = String;
+
+    fn try_from(mut value: HashMap<String, u32>) -> Result<Self, Self::Error> {
+        let fields: &[&str] = if value.contains_key(TIMING0) || value.contains_key(TIMING1) {
+            &[TIMING0, TIMING1]
+        }
+        else {
+            &[TSEG1, TSEG2, SJW, SMP, BRP]
+        };
+        if let Some(k) = value.keys()
+            .filter(|k| !fields.contains(&k.as_str()))
+            .min() {
+            return Err(format!("unknown field `{}`, expected: {}", k, fields.join(", ")));
+        }
+        let mut values = Vec::with_capacity(fields.len());
+        for &field in fields {
+            values.push(value.remove(field).ok_or(format!("missing field `{}`", field))?);
+        }
+
+        match *values.as_slice() {
+            [timing0, timing1] => Ok(Self::Can(CanTimingCfg { timing0, timing1 })),
+            [tseg1, tseg2, sjw, smp, brp] => Ok(Self::CanFd(CanFdTimingCfg { tseg1, tseg2, sjw, smp, brp })),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBitrateCfg {
+    #[serde(default)]
+    bitrate: HashMap<u32, HashMap<String, u32>>,
+    clock: Option<u32>,
+    data_bitrate: Option<HashMap<u32, HashMap<String, u32>>>
+}
+
 /// The deserialize object mapped to configuration file context.
 #[derive(Debug, Deserialize)]
+#[serde(try_from = "RawBitrateCfg")]
 pub struct BitrateCfg {
-    pub(crate) bitrate: HashMap<String, HashMap<String, u32>>,
+    pub(crate) bitrate: HashMap<u32, BitrateTimingCfg>,
     pub(crate) clock: Option<u32>,
-    pub(crate) data_bitrate: Option<HashMap<String, HashMap<String, u32>>>
+    pub(crate) data_bitrate: Option<HashMap<u32, BitrateTimingCfg>>
+}
+
+impl TryFrom<RawBitrateCfg> for BitrateCfg {
+    type Error = String;
+
+    fn try_from(value: RawBitrateCfg) -> Result<Self, Self::Error> {
+        fn convert(ctx: HashMap<u32, HashMap<String, u32>>, name: &str) -> Result<HashMap<u32, BitrateTimingCfg>, String> {
+            ctx.into_iter()
+                .map(|(k, v)| BitrateTimingCfg::try_from(v)
+                    .map(|v| (k, v))
+                    .map_err(|e| format!("{}: `{}` - {}", name, k, e)))
+                .collect()
+        }
+
+        Ok(Self {
+            bitrate: convert(value.bitrate, "bitrate")?,
+            clock: value.clock,
+            data_bitrate: value.data_bitrate.map(|v| convert(v, "data bitrate")).transpose()?,
+        })
+    }
 }
 
 impl BitrateCfg {
     #[inline]
-    pub fn bitrate(&self) -> &HashMap<String, HashMap<String, u32>> {
+    pub fn bitrate(&self) -> &HashMap<u32, BitrateTimingCfg> {
         &self.bitrate
     }
     #[inline]
     pub fn clock(&self) -> Option<u32> {
-        self.clock.clone()
+        self.clock
     }
     #[inline]
-    pub fn dbitrate(&self) -> &Option<HashMap<String, HashMap<String, u32>>> {
+    pub fn dbitrate(&self) -> &Option<HashMap<u32, BitrateTimingCfg>> {
         &self.data_bitrate
     }
+
+    /// Add or override the entries by other.
+    pub fn merge(&mut self, other: Self) {
+        self.bitrate.extend(other.bitrate);
+        if other.clock.is_some() {
+            self.clock = other.clock;
+        }
+        match (&mut self.data_bitrate, other.data_bitrate) {
+            (Some(v), Some(other)) => v.extend(other),
+            (v, Some(other)) => *v = Some(other),
+            _ => {},
+        }
+    }
+
+    /// Check the entries against the register of device.
+    /// The register layout is decided by the kind of entry, because some CANFD devices are configured by `timing0` and `timing1`.
+    pub fn validate(&self, dev_type: ZCanDeviceType) -> Result<(), ZCanError> {
+        let timing_type = |entry: &BitrateTimingCfg| match entry {
+            BitrateTimingCfg::Can(_) => ZCanTimingType::SJA1000,
+            BitrateTimingCfg::CanFd(_) => match ZCanTimingType::from_device_type(dev_type) {
+                ZCanTimingType::USBCANFD_800U => ZCanTimingType::USBCANFD_800U,
+                _ => ZCanTimingType::USBCANFD,
+            },
+        };
+        let mut entries = self.bitrate.iter()
+            .map(|(k, v)| (k, v, false))
+            .chain(self.data_bitrate.iter().flatten().map(|(k, v)| (k, v, true)))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(k, _, data)| (*data, **k));
+
+        entries.into_iter()
+            .try_for_each(|(bitrate, entry, data)| {
+                timing_type(entry).validate(entry, data)
+                    .map_err(|e| ZCanError::ConfigurationError(format!(
+                        "device: `{}`, {}bitrate: `{}` - {}", dev_type as u32, if data { "data " } else { "" }, bitrate, e
+                    )))
+            })
+    }
 }
 
 /// The extra info for common CAN channel configuration.
@@ -137,7 +265,7 @@ impl CanChlCfg {
     #[inline(always)]
     pub fn clock(&self) -> Option<u32> {
         if let Some(ctx) = self.cfg_ctx.upgrade() {
-            if let Some(cfg) = ctx.get(&self.bitrate.to_string()) {
+            if let Some(cfg) = ctx.get(&self.dev_type.to_string()) {
                 return cfg.clock;
             }
         }
@@ -161,20 +289,29 @@ impl CanChlCfg {
     }
 }
 
-fn to_chl_cfg(mode: u8, bitrate: u32, cfg_ctx: &BitrateCfg, ext: &CanChlCfgExt) -> Result<ZCanChlCfg, ZCanError> {
-    match cfg_ctx.bitrate.get(&bitrate.to_string()) {
-        Some(v) => {
-            let timing0 = v.get(TIMING0)
-                .ok_or(ZCanError::ConfigurationError(format!("`{}` is not configured in file!", TIMING0)))?;
-            let timing1 = v.get(TIMING1)
-                .ok_or(ZCanError::ConfigurationError(format!("`{}` is not configured in file!", TIMING1)))?;
+fn to_chl_cfg(
+    dev_type: ZCanDeviceType,
+    mode: u8,
+    bitrate: u32,
+    cfg_ctx: &BitrateCfg,
+    ext: &CanChlCfgExt
+) -> Result<ZCanChlCfg, ZCanError> {
+    match cfg_ctx.bitrate.get(&bitrate) {
+        Some(BitrateTimingCfg::Can(v)) => ZCanChlCfg::new(
+            mode, v.timing0, v.timing1, ext.filter, ext.acc_code, ext.acc_mask
+        ),
+        Some(BitrateTimingCfg::CanFd(_)) => Err(ZCanError::ConfigurationError(
+            format!("the bitrate: `{}` of device: {:?} is not `timing0` and `timing1`", bitrate, dev_type)
+        )),
+        None => {   // solve the timing when bitrate is not configured
+            let timing_type = ZCanTimingType::from_device_type(dev_type);
+            let clock = cfg_ctx.clock.unwrap_or(timing_type.clock());
+            let timing = BitTiming::solve(clock, bitrate, None, None, &timing_type.nominal_const())?;
+            let (timing0, timing1) = timing_type.sja1000(&timing)?;
             ZCanChlCfg::new(
-                mode, *timing0, *timing1, ext.filter, ext.acc_code, ext.acc_mask
+                mode, timing0, timing1, ext.filter, ext.acc_code, ext.acc_mask
             )
         },
-        None => Err(ZCanError::ConfigurationError(
-            format!("the bitrate: `{}` is not configured", bitrate)
-        )),
     }
 }
 
@@ -222,7 +359,7 @@ impl TryFrom<&CanChlCfg> for ZCanChlCfgV1 {
             ZCanChlCfgV1::new(
                 ZCanChlType::CAN as u8,
                 ZCanChlCfgV1Union::from(
-                    to_chl_cfg(value.mode, value.bitrate, cfg, &value.extra)?
+                    to_chl_cfg(value.device_type()?, value.mode, value.bitrate, cfg, &value.extra)?
                 )
             )
         }
@@ -240,8 +377,8 @@ impl TryFrom<&CanChlCfg> for ZCanChlCfgV2 {
             .ok_or(ZCanError::ConfigurationError(format!("device: {:?} is not configured in file!", dev_type)))?;
         if value.device_type()?
             .canfd_support() {
-            let clock = cfg.clock
-                .ok_or(ZCanError::ConfigurationError("`clock` is not configured in file!".to_string()))?;
+            // the default clock of device is used as same as solving the timing
+            let clock = cfg.clock.unwrap_or(ZCanTimingType::from_device_type(value.device_type()?).clock());
             let ext = &value.extra;
             let (aset, dset) = get_fd_set(value, cfg, ext.dbitrate)?;
             Ok(Self::from(
@@ -256,7 +393,7 @@ impl TryFrom<&CanChlCfg> for ZCanChlCfgV2 {
         }
         else {
             Ok(Self::from(
-                to_chl_cfg(value.mode, value.bitrate, cfg, &value.extra)?
+                to_chl_cfg(value.device_type()?, value.mode, value.bitrate, cfg, &value.extra)?
             ))
         }
     }
@@ -268,16 +405,41 @@ pub struct CanChlCfgFactory(Arc<HashMap<String, BitrateCfg>>);
 
 
 impl CanChlCfgFactory {
+    /// Create the factory with the default timing tables,
+    /// and the entries of `bitrate.cfg.yaml`(or `ZCAN_BITRATE` in `zcan.env`) are merged into it if the file is existed.
     pub fn new() -> Result<Self, ZCanError> {
-        let libpath = match dotenvy::from_filename("zcan.env") {
-            Ok(_) => std::env::var("ZCAN_BITRATE").unwrap_or_else(|_| BITRATE_CFG_FILENAME.into()),
-            Err(_) => BITRATE_CFG_FILENAME.into(),
-        };
-        let data = read_to_string(libpath.clone())
-            .map_err(|e| ZCanError::ConfigurationError(format!("Unable to read `{}`: {:?}", libpath, e)))?;
-        let result = serde_yaml::from_str(&data)
-            .map_err(|e| ZCanError::ConfigurationError(format!("Error parsing YAML: {:?}", e)))?;
-        Ok(Self(Arc::new(result)))
+        match dotenvy::from_filename("zcan.env") {
+            Ok(_) => match std::env::var("ZCAN_BITRATE") {
+                Ok(path) => Self::from_file(path),
+                Err(_) => Self::from_default_file(),
+            },
+            Err(_) => Self::from_default_file(),
+        }
+    }
+
+    /// Create the factory with the default timing tables only.
+    pub fn builtin() -> Result<Self, ZCanError> {
+        Self::load(None)
+    }
+
+    /// Load and validate the configuration file, the entries are merged into the default timing tables.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ZCanError> {
+        let path = path.as_ref();
+        let data = read_to_string(path)
+            .map_err(|e| ZCanError::ConfigurationError(format!("Unable to read `{}`: {:?}", path.display(), e)))?;
+        data.parse()
+    }
+
+    /// The merged timing tables, the key is device type.
+    #[inline]
+    pub fn table(&self) -> &HashMap<String, BitrateCfg> {
+        &self.0
+    }
+
+    /// The merged timing table of device.
+    #[inline]
+    pub fn device_cfg(&self, dev_type: u32) -> Option<&BitrateCfg> {
+        self.0.get(&dev_type.to_string())
     }
 
     pub fn new_can_chl_cfg(
@@ -297,6 +459,155 @@ impl CanChlCfgFactory {
             ))
         }
     }
+
+    /// Create a builder of channel configuration.
+    #[inline]
+    pub fn builder(&self, dev_type: ZCanDeviceType, bitrate: u32) -> CanChlCfgBuilder<'_> {
+        CanChlCfgBuilder::new(self, dev_type, bitrate)
+    }
+
+    fn from_default_file() -> Result<Self, ZCanError> {
+        if Path::new(BITRATE_CFG_FILENAME).exists() {
+            Self::from_file(BITRATE_CFG_FILENAME)
+        }
+        else {
+            Self::builtin()
+        }
+    }
+
+    /// Merge the overlay into the default timing tables and validate the result.
+    /// The keys that are not device type(such as YAML anchors) are only checked the fields.
+    fn load(overlay: Option<&str>) -> Result<Self, ZCanError> {
+        let mut result = parse_bitrate_cfg(BITRATE_CFG_DEFAULT)?;
+        if let Some(overlay) = overlay {
+            for (key, cfg) in parse_bitrate_cfg(overlay)? {
+                match result.get_mut(&key) {
+                    Some(v) => v.merge(cfg),
+                    None => { result.insert(key, cfg); },
+                }
+            }
+        }
+
+        let mut keys = result.keys().collect::<Vec<_>>();
+        keys.sort();
+        for key in keys {
+            if let Ok(dev_type) = key.parse::<u32>() {
+                let dev_type = ZCanDeviceType::try_from(dev_type)
+                    .map_err(|_| ZCanError::ConfigurationError(format!("device: `{}` is not a device type", key)))?;
+                result[key].validate(dev_type)?;
+            }
+        }
+        Ok(Self(Arc::new(result)))
+    }
+}
+
+/// The builder of `CanChlCfg`, the combinations are checked by `build`.
+#[derive(Debug, Clone)]
+pub struct CanChlCfgBuilder<'a> {
+    factory: &'a CanChlCfgFactory,
+    dev_type: ZCanDeviceType,
+    can_type: ZCanChlType,
+    mode: ZCanChlMode,
+    bitrate: u32,
+    extra: CanChlCfgExt,
+}
+
+impl<'a> CanChlCfgBuilder<'a> {
+    pub fn new(factory: &'a CanChlCfgFactory, dev_type: ZCanDeviceType, bitrate: u32) -> Self {
+        Self {
+            factory,
+            dev_type,
+            can_type: Default::default(),
+            mode: Default::default(),
+            bitrate,
+            extra: Default::default(),
+        }
+    }
+    #[inline]
+    pub fn can_type(mut self, can_type: ZCanChlType) -> Self {
+        self.can_type = can_type;
+        self
+    }
+    #[inline]
+    pub fn mode(mut self, mode: ZCanChlMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    #[inline]
+    pub fn dbitrate(mut self, dbitrate: u32) -> Self {
+        self.extra.dbitrate = Some(dbitrate);
+        self
+    }
+    #[inline]
+    pub fn filter(mut self, filter: ZCanFilterType) -> Self {
+        self.extra.filter = filter as u8;
+        self
+    }
+    #[inline]
+    pub fn resistance(mut self, resistance: bool) -> Self {
+        self.extra.resistance = Some(resistance);
+        self
+    }
+    #[inline]
+    pub fn acc_code(mut self, acc_code: u32) -> Self {
+        self.extra.acc_code = Some(acc_code);
+        self
+    }
+    #[inline]
+    pub fn acc_mask(mut self, acc_mask: u32) -> Self {
+        self.extra.acc_mask = Some(acc_mask);
+        self
+    }
+    #[inline]
+    pub fn brp(mut self, brp: u32) -> Self {
+        self.extra.brp = Some(brp);
+        self
+    }
+
+    pub fn build(self) -> Result<CanChlCfg, ZCanError> {
+        let dev_type = self.dev_type;
+        let canfd = dev_type.canfd_support();
+        if !canfd && !matches!(self.can_type, ZCanChlType::CAN) {
+            return Err(ZCanError::ConfigurationError(format!("device: {:?} is not supported {:?}", dev_type, self.can_type)));
+        }
+        if !canfd && self.extra.dbitrate.is_some() {
+            return Err(ZCanError::ConfigurationError(format!("device: {:?} is not supported data bitrate", dev_type)));
+        }
+        if !dev_type.has_resistance() && self.extra.resistance.is_some() {
+            return Err(ZCanError::ConfigurationError(format!("device: {:?} is not supported resistance", dev_type)));
+        }
+
+        self.factory.new_can_chl_cfg(dev_type as u32, self.can_type as u8, self.mode as u8, self.bitrate, self.extra)
+    }
+}
+
+impl FromStr for CanChlCfgFactory {
+    type Err = ZCanError;
+
+    /// Parse the configuration context and merge it into the default timing tables.
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::load(Some(s))
+    }
+}
+
+fn parse_bitrate_cfg(s: &str) -> Result<HashMap<String, BitrateCfg>, ZCanError> {
+    let raw: BTreeMap<String, RawBitrateCfg> = serde_yaml::from_str(s)
+        .map_err(|e| ZCanError::ConfigurationError(format!("Error parsing YAML: {}", e)))?;
+    raw.into_iter()
+        .map(|(key, value)| BitrateCfg::try_from(value)
+            .map(|cfg| (key.clone(), cfg))
+            .map_err(|e| ZCanError::ConfigurationError(format!("device: `{}`, {}", key, e))))
+        .collect()
+}
+
+fn fd_set(bitrate: u32, entry: &BitrateTimingCfg) -> Result<ZCanFdChlCfgSet, ZCanError> {
+    match entry {
+        BitrateTimingCfg::CanFd(v) => Ok(ZCanFdChlCfgSet::from(v)),
+        BitrateTimingCfg::Can(_) => Err(ZCanError::ConfigurationError(
+            format!("the bitrate: `{}` is not `tseg1`, `tseg2`, `sjw`, `smp` and `brp`", bitrate)
+        )),
+    }
 }
 
 fn get_fd_set(
@@ -307,40 +618,103 @@ fn get_fd_set(
     let bitrate = value.bitrate;
     let bitrate_ctx = &cfg.bitrate;
     let dbitrate_ctx = &cfg.data_bitrate;
-    let aset = bitrate_ctx
-        .get(&bitrate.to_string())
-        .ok_or(ZCanError::ConfigurationError(format!("bitrate `{}` is not configured in file!", bitrate)))?;
-    let dset=
-        match dbitrate {
-            Some(v) => {    // dbitrate is not None
-                match dbitrate_ctx {
-                    Some(ctx) => {  // dbitrate context is not None
-                        match ctx.get(&v.to_string()) {
-                            Some(value) => Ok(value),
-                            None => Err(ZCanError::ConfigurationError(format!("data bitrate `{}` is not configured in file!", v))),
-                        }
-                    },
-                    None => {   // dbitrate context is None
-                        match bitrate_ctx.get(&v.to_string()) {
-                            Some(value) => Ok(value),
-                            None => Err(ZCanError::ConfigurationError(format!("data bitrate `{}` is not configured in file!", v))),
-                        }
-                    }
-                }
-            },
-            None => {   // dbitrate is None
-                match dbitrate_ctx {
-                    Some(ctx) => {
-                        match ctx.get(&bitrate.to_string()) {
-                            Some(value) => Ok(value),
-                            None => Ok(aset),
-                        }
-                    },
-                    None => Ok(aset),
-                }
+    let timing_type = ZCanTimingType::from_device_type(value.device_type()?);
+    let clock = cfg.clock.unwrap_or(timing_type.clock());
+    // solve the timing when bitrate is not configured
+    let solve = |bitrate: u32, constraint: BitTimingConst| -> Result<ZCanFdChlCfgSet, ZCanError> {
+        let timing = BitTiming::solve(clock, bitrate, None, None, &constraint)?;
+        timing_type.fd_set(&timing)
+    };
+
+    let aset = match bitrate_ctx.get(&bitrate) {
+        Some(v) => fd_set(bitrate, v)?,
+        None => solve(bitrate, timing_type.nominal_const())?,
+    };
+    let dset = match dbitrate {
+        Some(v) => {    // dbitrate is not None
+            let ctx = dbitrate_ctx.as_ref().unwrap_or(bitrate_ctx);
+            match ctx.get(&v) {
+                Some(value) => fd_set(v, value)?,
+                None => solve(v, timing_type.data_const())?,
+            }
+        },
+        None => {   // dbitrate is None
+            match dbitrate_ctx.as_ref().and_then(|ctx| ctx.get(&bitrate)) {
+                Some(value) => fd_set(bitrate, value)?,
+                None => aset,
             }
-        }?;
+        }
+    };
 
-    Ok((ZCanFdChlCfgSet::try_from(aset)?, ZCanFdChlCfgSet::try_from(dset)?))
+    Ok((aset, dset))
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::device::ZCanDeviceType;
+    use super::{BitrateTimingCfg, CanChlCfgFactory, CanFdTimingCfg, CanTimingCfg, ZCanChlCfgV2, ZCanChlMode, ZCanChlType, ZCanFilterType};
+
+    #[test]
+    fn bitrate_cfg_overlay() -> anyhow::Result<()> {
+        let factory = CanChlCfgFactory::builtin()?;
+        for dev_type in [3, 4, 31, 33, 41, 42, 43, 59, 98, 99] {
+            let cfg = factory.device_cfg(dev_type).unwrap();
+            assert!([10_000, 125_000, 250_000, 500_000, 1_000_000].iter().all(|v| cfg.bitrate().contains_key(v)));
+        }
+        let cfg = factory.device_cfg(41).unwrap();
+        assert_eq!(cfg.clock(), Some(60_000_000));
+        assert!([1_000_000, 2_000_000, 5_000_000].iter().all(|v| cfg.dbitrate().as_ref().unwrap().contains_key(v)));
+
+        let factory: CanChlCfgFactory = "\
+            4: { bitrate: { 33333: { timing0: 0x09, timing1: 0x6f } } }\n\
+            41: { data_bitrate: { 2000000: { tseg1: 10, tseg2: 2, sjw: 2, smp: 80, brp: 1 } } }\n\
+            21: { clock: 16000000, bitrate: { 500000: { timing0: 0x00, timing1: 0x1c } } }\n".parse()?;
+        let cfg = factory.device_cfg(4).unwrap();
+        assert_eq!(cfg.bitrate().get(&33_333), Some(&BitrateTimingCfg::Can(CanTimingCfg { timing0: 0x09, timing1: 0x6f })));
+        assert_eq!(cfg.bitrate().get(&500_000), Some(&BitrateTimingCfg::Can(CanTimingCfg { timing0: 0x00, timing1: 0x1c })));
+        assert!(!factory.device_cfg(3).unwrap().bitrate().contains_key(&33_333));
+        let cfg = factory.device_cfg(41).unwrap().dbitrate().as_ref().unwrap();
+        assert_eq!(cfg.get(&2_000_000), Some(&BitrateTimingCfg::CanFd(CanFdTimingCfg { tseg1: 10, tseg2: 2, sjw: 2, smp: 80, brp: 1 })));
+        assert!(cfg.contains_key(&5_000_000));
+        assert!(factory.table().contains_key("21"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn chl_cfg_builder() -> anyhow::Result<()> {
+        let factory = CanChlCfgFactory::builtin()?;
+        let cfg = factory.builder(ZCanDeviceType::ZCAN_USBCANFD_200U, 500_000)
+            .can_type(ZCanChlType::CANFD_ISO)
+            .mode(ZCanChlMode::ListenOnly)
+            .dbitrate(2_000_000)
+            .filter(ZCanFilterType::Single)
+            .resistance(false)
+            .acc_code(0x100)
+            .acc_mask(0x7FF)
+            .build()?;
+        assert_eq!(cfg.mode(), ZCanChlMode::ListenOnly as u8);
+        assert_eq!(cfg.extra().dbitrate(), Some(2_000_000));
+        assert!(matches!(cfg.extra().filter()?, ZCanFilterType::Single));
+        assert!(!cfg.extra().resistance());
+        assert_eq!((cfg.extra().acc_code(), cfg.extra().acc_mask()), (0x100, 0x7FF));
+        assert!(ZCanChlCfgV2::try_from(&cfg).is_ok());
+
+        let builder = factory.builder(ZCanDeviceType::ZCAN_USBCAN1, 500_000);
+        assert!(builder.clone().build().is_ok());
+        assert!(builder.clone().can_type(ZCanChlType::CANFD_ISO).build().is_err());
+        assert!(builder.clone().dbitrate(2_000_000).build().is_err());
+        assert!(builder.resistance(true).build().is_err());
+        assert!(factory.builder(ZCanDeviceType::ZCAN_PCI9820, 500_000).build().is_err());
+
+        // the default clock of device is used when `clock` is not configured
+        let factory: CanChlCfgFactory = "40: { bitrate: {} }\n".parse()?;
+        assert_eq!(factory.device_cfg(40).unwrap().clock(), None);
+        let cfg = factory.builder(ZCanDeviceType::ZCAN_PCIE_CANFD_400U, 500_000)
+            .dbitrate(2_000_000)
+            .build()?;
+        assert!(ZCanChlCfgV2::try_from(&cfg).is_ok());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use isotp_rs::can::frame::Frame;
+use crate::data::DataObject;
+use crate::utils::system_timestamp;
+use super::{CanMessage, ClockSync};
+
+/// The tracker of device timestamp.
+///
+/// The timestamp of device frame is a 32-bit tick counter, the tracker extends it to 64-bit across rollover
+/// and maps it to host time by the clock synchronization, so the timestamps of channels and devices are comparable.
+#[derive(Debug, Default, Clone)]
+pub struct TimestampTracker {
+    unit: u32,
+    base: u64,
+    epoch: u64,
+    last: Option<u32>,
+    sync: ClockSync,
+}
+
+impl TimestampTracker {
+    const HALF_RANGE: u32 = u32::MAX / 2;
+
+    /// Create a tracker with the microseconds of one tick and the system time(µs) when channel opened.
+    #[inline]
+    pub fn new(unit: u32, base: u64) -> Self {
+        Self { unit: unit.max(1), base, epoch: Default::default(), last: Default::default(), sync: Default::default() }
+    }
+    /// The microseconds of one tick.
+    #[inline]
+    pub fn unit(&self) -> u32 {
+        self.unit
+    }
+    /// The system time(µs) when channel opened.
+    #[inline]
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+    /// The clock synchronization of device.
+    #[inline]
+    pub fn clock_sync(&self) -> &ClockSync {
+        &self.sync
+    }
+    /// Reset the tracker when the channel is reopened.
+    #[inline]
+    pub fn reset(&mut self, base: u64) {
+        self.epoch = Default::default();
+        self.last = Default::default();
+        self.base = base;
+        self.sync.reset();
+    }
+
+    /// Extend the raw ticks of device to 64-bit.
+    /// A frame that is a little earlier than the last one is treated as out of order, not as a rollover.
+    pub fn extend(&mut self, raw: u32) -> u64 {
+        match self.last {
+            Some(last) if raw < last && last - raw > Self::HALF_RANGE => {
+                self.epoch += 1;
+                self.last = Some(raw);
+            },
+            // the frame was stamped before the last rollover
+            Some(last) if raw > last && raw - last > Self::HALF_RANGE && self.epoch > 0 => {
+                return ((self.epoch - 1) << 32) | raw as u64;
+            },
+            Some(last) if raw <= last => {},
+            _ => self.last = Some(raw),
+        }
+
+        (self.epoch << 32) | raw as u64
+    }
+
+    /// Get the device time(µs) of the raw ticks.
+    #[inline]
+    pub fn device_time(&mut self, raw: u32) -> u64 {
+        self.extend(raw) * self.unit as u64
+    }
+
+    /// Get the absolute time(µs) of the raw ticks without clock synchronization.
+    #[inline]
+    pub fn absolute_time(&mut self, raw: u32) -> u64 {
+        self.base + self.device_time(raw)
+    }
+
+    /// Update the timestamp of messages that have hardware timestamp, the messages must be in received order.
+    #[inline]
+    pub fn update(&mut self, messages: &mut [CanMessage]) {
+        self.update_at(messages, system_timestamp())
+    }
+
+    /// Same as `update`, the `host` is the time(µs) when the messages were received.
+    pub fn update_at(&mut self, messages: &mut [CanMessage], host: u64) {
+        let times = messages.iter()
+            .map(|v| v.hardware_timestamp().map(|raw| self.device_time(raw)))
+            .collect::<Vec<_>>();
+        for (message, timestamp) in messages.iter_mut().zip(self.host_times(times, None, host)) {
+            if let Some(timestamp) = timestamp {
+                message.set_timestamp(Some(timestamp));
+            }
+        }
+    }
+
+    /// Update the timestamp of received data objects,
+    /// the hardware timestamp of LIN frame is the device time(µs) that is not rolled over.
+    #[inline]
+    pub fn update_objects(&mut self, objects: &mut [DataObject]) {
+        self.update_objects_at(objects, system_timestamp())
+    }
+
+    /// Same as `update_objects`, the `host` is the time(µs) when the objects were received.
+    #[inline]
+    pub fn update_objects_at(&mut self, objects: &mut [DataObject], host: u64) {
+        self.update_objects_with(objects, None, host)
+    }
+
+    /// Same as `update_objects_at`, but the clock is sampled with the `latest` raw ticks of device.
+    /// It's used when the frames of all channels are received by one call, the channels share the clock of device
+    /// and the `latest` is the last frame of all channels, so the timestamps of channels are in the same order as device.
+    #[inline]
+    pub fn update_merged_at(&mut self, objects: &mut [DataObject], latest: u32, host: u64) {
+        self.update_objects_with(objects, Some(latest), host)
+    }
+
+    fn update_objects_with(&mut self, objects: &mut [DataObject], latest: Option<u32>, host: u64) {
+        let times = objects.iter()
+            .map(|v| match v {
+                DataObject::Can(v) | DataObject::Error { frame: v, .. } => v.hardware_timestamp().map(|raw| self.device_time(raw)),
+                DataObject::Lin(v) | DataObject::LinError { message: v, .. } => v.hardware_timestamp(),
+            })
+            .collect::<Vec<_>>();
+        let latest = latest.map(|raw| self.device_time(raw));
+        for (object, timestamp) in objects.iter_mut().zip(self.host_times(times, latest, host)) {
+            if let Some(timestamp) = timestamp {
+                object.set_timestamp(Some(timestamp));
+            }
+        }
+    }
+
+    /// Add the sample of the latest device time and convert the device times to host time.
+    fn host_times(&mut self, times: Vec<Option<u64>>, latest: Option<u64>, host: u64) -> Vec<Option<u64>> {
+        // the last one is the closest to the receive time
+        if let Some(device) = latest.or_else(|| times.iter().flatten().max().copied()) {
+            self.sync.add_sample(device, host);
+        }
+
+        times.into_iter()
+            .map(|v| v.map(|device| self.sync.host_time(device).unwrap_or(self.base + device)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isotp_rs::can::{frame::Frame, identifier::Id};
+    use crate::can::CanMessage;
+    use super::TimestampTracker;
+
+    #[test]
+    fn test_rollover() {
+        let mut tracker = TimestampTracker::new(1, 0);
+        assert_eq!(tracker.extend(u32::MAX - 10), u32::MAX as u64 - 10);
+        // out of order
+        assert_eq!(tracker.extend(u32::MAX - 20), u32::MAX as u64 - 20);
+        assert_eq!(tracker.extend(5), (1 << 32) + 5);
+        // stamped before rollover
+        assert_eq!(tracker.extend(u32::MAX - 1), u32::MAX as u64 - 1);
+        assert_eq!(tracker.extend(100), (1 << 32) + 100);
+        assert_eq!(tracker.extend(u32::MAX / 2 + 50), (1 << 32) + u32::MAX as u64 / 2 + 50);
+        assert_eq!(tracker.extend(u32::MAX), (1 << 32) + u32::MAX as u64);
+        assert_eq!(tracker.extend(0), 2 << 32);
+
+        tracker.reset(1000);
+        assert_eq!(tracker.extend(10), 10);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut tracker = TimestampTracker::new(10, 1_700_000_000_000_000);
+        let message = CanMessage::new(Id::Standard(0x7DF), &[0x01]).unwrap();
+        let mut messages = vec![message.clone(); 3];
+        messages[0].set_hardware_timestamp(Some(u32::MAX));
+        messages[1].set_hardware_timestamp(Some(99));
+        messages[2].set_timestamp(Some(1));
+        let device = ((1u64 << 32) + 99) * 10;
+        tracker.update_at(&mut messages, 1_700_000_000_000_000 + device + 500);
+        assert_eq!(messages[0].timestamp(), 1_700_000_000_000_000 + u32::MAX as u64 * 10 + 500);
+        assert_eq!(messages[1].timestamp(), 1_700_000_000_000_000 + device + 500);
+        // the frames 1ms apart are distinguishable
+        assert_eq!(messages[1].timestamp() - messages[0].timestamp(), 100 * 10);
+        assert_eq!(messages[1].hardware_timestamp(), Some(99));
+        // the message without hardware timestamp is not changed
+        assert_eq!(messages[2].timestamp(), 1);
+        assert_eq!(tracker.device_time(1000), ((1u64 << 32) + 1000) * 10);
+
+        // the channels are comparable on host timeline
+        let mut other = TimestampTracker::new(1, 0);
+        let mut messages = vec![message; 1];
+        messages[0].set_hardware_timestamp(Some(3_000));
+        other.update_at(&mut messages, 1_700_000_000_000_000 + device + 500);
+        assert_eq!(messages[0].timestamp(), 1_700_000_000_000_000 + device + 500);
+        assert_eq!(messages[0].hardware_timestamp(), Some(3_000));
+    }
+}
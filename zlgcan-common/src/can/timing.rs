@@ -0,0 +1,529 @@
+//! The bit timing solver.
+//!
+//! A bit is divided into time quanta(tq) of `brp / clock` seconds:
+//! one tq of sync segment, `tseg1`(propagation and phase segment 1) and `tseg2`(phase segment 2),
+//! and it is sampled at the end of `tseg1`.
+//! The values of `BitTiming` are the actual values, the register encoding and decoding are done by `ZCanTimingType`.
+use std::fmt::{Display, Formatter};
+use crate::can::{BitrateTimingCfg, ZCanFdChlCfgSet, BRP, SJW, SMP, TIMING0, TIMING1, TSEG1, TSEG2};
+use crate::device::ZCanDeviceType;
+use crate::error::ZCanError;
+
+/// The max bitrate error of solution in permille.
+pub const BIT_TIMING_MAX_ERROR: u32 = 50;
+/// The max sample point difference in permille of compatible nodes.
+pub const BIT_TIMING_MAX_SAMPLE_POINT_DIFF: u32 = 50;
+
+/// The range of bit timing values, the `brp` is a multiple of `brp_inc`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BitTimingConst {
+    pub tseg1_min: u32,
+    pub tseg1_max: u32,
+    pub tseg2_min: u32,
+    pub tseg2_max: u32,
+    pub sjw_max: u32,
+    pub brp_min: u32,
+    pub brp_max: u32,
+    pub brp_inc: u32,
+}
+
+/// The bit timing register layout of device family.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ZCanTimingType {
+    /// The SJA1000 `timing0`(BTR0) and `timing1`(BTR1) of USBCAN, the clock is 16MHz.
+    SJA1000,
+    /// The `ZCanFdChlCfgSet` of USBCANFD that each value is minus 1, the clock is 60MHz.
+    USBCANFD,
+    /// The `ZCanFdChlCfgSet` of USBCANFD-800U that packed by `get_timing`, the clock is 40MHz.
+    USBCANFD_800U,
+}
+
+impl ZCanTimingType {
+    pub fn from_device_type(dev_type: ZCanDeviceType) -> Self {
+        match dev_type {
+            ZCanDeviceType::ZCAN_USBCANFD_800U => Self::USBCANFD_800U,
+            v if v.canfd_support() => Self::USBCANFD,
+            _ => Self::SJA1000,
+        }
+    }
+
+    /// The default clock(Hz) of device.
+    pub const fn clock(&self) -> u32 {
+        match self {
+            Self::SJA1000 => 16_000_000,
+            Self::USBCANFD => 60_000_000,
+            Self::USBCANFD_800U => 40_000_000,
+        }
+    }
+
+    /// The range of nominal(arbitration) bit timing.
+    pub const fn nominal_const(&self) -> BitTimingConst {
+        match self {
+            // the tq of SJA1000 is 2 * (BRP + 1) / clock
+            Self::SJA1000 => BitTimingConst {
+                tseg1_min: 1, tseg1_max: 16, tseg2_min: 1, tseg2_max: 8, sjw_max: 4, brp_min: 2, brp_max: 128, brp_inc: 2,
+            },
+            Self::USBCANFD => BitTimingConst {
+                tseg1_min: 1, tseg1_max: 64, tseg2_min: 1, tseg2_max: 16, sjw_max: 16, brp_min: 1, brp_max: 1024, brp_inc: 1,
+            },
+            Self::USBCANFD_800U => BitTimingConst {
+                tseg1_min: 1, tseg1_max: 255, tseg2_min: 1, tseg2_max: 127, sjw_max: 127, brp_min: 1, brp_max: 1023, brp_inc: 1,
+            },
+        }
+    }
+
+    /// The range of data bit timing, it is same as nominal for SJA1000.
+    pub const fn data_const(&self) -> BitTimingConst {
+        match self {
+            Self::SJA1000 => self.nominal_const(),
+            Self::USBCANFD => BitTimingConst {
+                tseg1_min: 1, tseg1_max: 16, tseg2_min: 1, tseg2_max: 8, sjw_max: 8, brp_min: 1, brp_max: 32, brp_inc: 1,
+            },
+            Self::USBCANFD_800U => BitTimingConst {
+                tseg1_min: 1, tseg1_max: 31, tseg2_min: 1, tseg2_max: 15, sjw_max: 15, brp_min: 1, brp_max: 31, brp_inc: 1,
+            },
+        }
+    }
+
+    /// Check the register values of configuration entry.
+    pub fn validate(&self, entry: &BitrateTimingCfg, data: bool) -> Result<(), String> {
+        fn check(name: &str, value: u32, min: u32, max: u32) -> Result<(), String> {
+            if (min..=max).contains(&value) {
+                Ok(())
+            }
+            else {
+                Err(format!("`{}`: {} is out of range {}..={}", name, value, min, max))
+            }
+        }
+
+        let constraint = if data { self.data_const() } else { self.nominal_const() };
+        match (self, entry) {
+            (Self::SJA1000, BitrateTimingCfg::Can(v)) => {
+                check(TIMING0, v.timing0, 0, 0xFF)?;
+                check(TIMING1, v.timing1, 0, 0xFF)
+            },
+            (Self::SJA1000, _) => Err(format!("expected `{}` and `{}`", TIMING0, TIMING1)),
+            (_, BitrateTimingCfg::Can(_)) => Err(format!("expected `{}`, `{}`, `{}`, `{}` and `{}`", TSEG1, TSEG2, SJW, SMP, BRP)),
+            (Self::USBCANFD, BitrateTimingCfg::CanFd(v)) => {
+                check(TSEG1, v.tseg1, constraint.tseg1_min - 1, constraint.tseg1_max - 1)?;
+                check(TSEG2, v.tseg2, constraint.tseg2_min - 1, constraint.tseg2_max - 1)?;
+                check(SJW, v.sjw, 0, constraint.sjw_max - 1)?;
+                check(SMP, v.smp, 0, 100)?;
+                check(BRP, v.brp, constraint.brp_min - 1, constraint.brp_max - 1)
+            },
+            (Self::USBCANFD_800U, BitrateTimingCfg::CanFd(v)) => {
+                check(TSEG1, v.tseg1, constraint.tseg1_min, constraint.tseg1_max)?;
+                check(TSEG2, v.tseg2, constraint.tseg2_min, constraint.tseg2_max)?;
+                check(SJW, v.sjw, 0, constraint.sjw_max)?;
+                check(SMP, v.smp, 0, 0xFF)?;
+                check(BRP, v.brp, constraint.brp_min, constraint.brp_max)
+            },
+        }
+    }
+
+    /// Encode the timing to SJA1000 `(timing0, timing1)`.
+    pub fn sja1000(&self, timing: &BitTiming) -> Result<(u32, u32), ZCanError> {
+        match self {
+            Self::SJA1000 => {
+                let timing0 = ((timing.sjw - 1) & 0x03) << 6 | ((timing.brp / 2 - 1) & 0x3F);
+                let timing1 = ((timing.tseg2 - 1) & 0x07) << 4 | ((timing.tseg1 - 1) & 0x0F);
+                Ok((timing0, timing1))
+            },
+            _ => Err(ZCanError::ParamNotSupported),
+        }
+    }
+
+    /// Encode the timing to `ZCanFdChlCfgSet`.
+    pub fn fd_set(&self, timing: &BitTiming) -> Result<ZCanFdChlCfgSet, ZCanError> {
+        match self {
+            Self::SJA1000 => Err(ZCanError::ParamNotSupported),
+            Self::USBCANFD => Ok(ZCanFdChlCfgSet::new(
+                timing.tseg1 - 1, timing.tseg2 - 1, timing.sjw - 1, timing.sample_point() / 10, timing.brp - 1
+            )),
+            Self::USBCANFD_800U => Ok(ZCanFdChlCfgSet::new(
+                timing.tseg1, timing.tseg2, timing.sjw, 0, timing.brp
+            )),
+        }
+    }
+
+    /// Decode the SJA1000 `(timing0, timing1)`, the sampling mode bit of `timing1` is ignored.
+    pub fn decode_sja1000(&self, timing0: u32, timing1: u32, clock: u32) -> Result<BitTiming, ZCanError> {
+        match self {
+            Self::SJA1000 => BitTiming::checked(
+                clock,
+                ((timing0 & 0x3F) + 1) * 2,
+                (timing1 & 0x0F) + 1,
+                ((timing1 >> 4) & 0x07) + 1,
+                ((timing0 >> 6) & 0x03) + 1,
+            ),
+            _ => Err(ZCanError::ParamNotSupported),
+        }
+    }
+
+    /// Decode the `ZCanFdChlCfgSet`.
+    pub fn decode_fd_set(&self, set: &ZCanFdChlCfgSet, clock: u32) -> Result<BitTiming, ZCanError> {
+        match self {
+            Self::SJA1000 => Err(ZCanError::ParamNotSupported),
+            Self::USBCANFD => BitTiming::checked(clock, set.brp() + 1, set.tseg1() + 1, set.tseg2() + 1, set.sjw() + 1),
+            Self::USBCANFD_800U => BitTiming::checked(clock, set.brp(), set.tseg1(), set.tseg2(), set.sjw()),
+        }
+    }
+
+    /// Decode the timing word that packed by `ZCanFdChlCfgSet::get_timing`, only used for USBCANFD-800U.
+    pub fn decode_timing(&self, timing: u32, clock: u32) -> Result<BitTiming, ZCanError> {
+        match self {
+            Self::USBCANFD_800U => self.decode_fd_set(&ZCanFdChlCfgSet::from_timing(timing), clock),
+            _ => Err(ZCanError::ParamNotSupported),
+        }
+    }
+
+    /// Decode the entry of bitrate configuration, the bitrate of entry is the target.
+    pub fn decode(&self, bitrate: u32, entry: &BitrateTimingCfg, clock: u32) -> Result<BitTiming, ZCanError> {
+        let timing = match entry {
+            BitrateTimingCfg::Can(v) => self.decode_sja1000(v.timing0, v.timing1, clock),
+            BitrateTimingCfg::CanFd(v) => self.decode_fd_set(&ZCanFdChlCfgSet::from(v), clock),
+        }?;
+        Ok(timing.with_target(bitrate))
+    }
+}
+
+/// The solved bit timing.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BitTiming {
+    clock: u32,
+    brp: u32,
+    tseg1: u32,
+    tseg2: u32,
+    sjw: u32,
+    /// The bitrate requested.
+    target: u32,
+}
+
+impl BitTiming {
+    pub fn new(clock: u32, brp: u32, tseg1: u32, tseg2: u32, sjw: u32) -> Self {
+        let mut timing = Self { clock, brp, tseg1, tseg2, sjw, target: 0 };
+        timing.target = timing.bitrate().round() as u32;
+        timing
+    }
+
+    fn checked(clock: u32, brp: u32, tseg1: u32, tseg2: u32, sjw: u32) -> Result<Self, ZCanError> {
+        if clock == 0 || brp == 0 || tseg1 == 0 || tseg2 == 0 || sjw == 0 {
+            return Err(ZCanError::ConfigurationError(format!(
+                "invalid bit timing - clock: {}, brp: {}, tseg1: {}, tseg2: {}, sjw: {}", clock, brp, tseg1, tseg2, sjw
+            )));
+        }
+        Ok(Self::new(clock, brp, tseg1, tseg2, sjw))
+    }
+
+    /// Set the requested bitrate that `bitrate_error` compared to.
+    #[inline]
+    pub fn with_target(self, bitrate: u32) -> Self {
+        Self { target: bitrate, ..self }
+    }
+
+    /// Solve the bit timing of bitrate with the sample point(permille) and SJW.
+    /// The sample point is 87.5% when bitrate <= 500k, 80% when bitrate <= 800k, or else 75% if it is `None`.
+    /// The SJW is `tseg2` that limited by `sjw_max` if it is `None`.
+    pub fn solve(
+        clock: u32,
+        bitrate: u32,
+        sample_point: Option<u32>,
+        sjw: Option<u32>,
+        constraint: &BitTimingConst,
+    ) -> Result<Self, ZCanError> {
+        if clock == 0 || bitrate == 0 {
+            return Err(ZCanError::ConfigurationError(format!("invalid clock: {} or bitrate: {}", clock, bitrate)));
+        }
+        let sample_point = sample_point.unwrap_or(match bitrate {
+            0..=500_000 => 875,
+            500_001..=800_000 => 800,
+            _ => 750,
+        });
+        if !(1..1000).contains(&sample_point) {
+            return Err(ZCanError::ConfigurationError(format!("invalid sample point: {}‰", sample_point)));
+        }
+        if let Some(sjw) = sjw {
+            if sjw == 0 || sjw > constraint.sjw_max {
+                return Err(ZCanError::ConfigurationError(format!("invalid SJW: {}, the max is: {}", sjw, constraint.sjw_max)));
+            }
+        }
+        let tseg2_min = constraint.tseg2_min.max(sjw.unwrap_or_default());
+        let brp_inc = constraint.brp_inc.max(1);
+
+        let mut best: Option<(Self, u64, u32)> = None;
+        let tq_min = 1 + constraint.tseg1_min + tseg2_min;
+        let tq_max = 1 + constraint.tseg1_max + constraint.tseg2_max;
+        for tq in (tq_min..=tq_max).rev() {
+            // the brp that nearest to the bitrate
+            let brp = (clock as f64 / (tq as f64 * bitrate as f64) / brp_inc as f64).round() as u32 * brp_inc;
+            if brp < constraint.brp_min || brp > constraint.brp_max {
+                continue;
+            }
+            let rate = clock as f64 / (brp as f64 * tq as f64);
+            // bitrate error in ppm
+            let error = ((rate - bitrate as f64).abs() * 1_000_000. / bitrate as f64).round() as u64;
+
+            let tseg2 = (tq - (tq * sample_point + 500) / 1000)
+                .clamp(tseg2_min, constraint.tseg2_max);
+            // the tseg2 of low sample point may be out of time quanta
+            let Some(tseg1) = (tq - 1).checked_sub(tseg2) else { continue };
+            let (tseg1, tseg2) = if tseg1 > constraint.tseg1_max {
+                (constraint.tseg1_max, tq - 1 - constraint.tseg1_max)
+            }
+            else if tseg1 < constraint.tseg1_min {
+                (constraint.tseg1_min, tq - 1 - constraint.tseg1_min)
+            }
+            else {
+                (tseg1, tseg2)
+            };
+            if tseg2 < tseg2_min || tseg2 > constraint.tseg2_max {
+                continue;
+            }
+
+            let sjw = sjw.unwrap_or(tseg2.min(constraint.sjw_max));
+            let timing = Self { clock, brp, tseg1, tseg2, sjw, target: bitrate };
+            let sp_error = timing.sample_point().abs_diff(sample_point);
+            match &best {
+                Some((_, e, s)) if (*e, *s) <= (error, sp_error) => {},
+                _ => best = Some((timing, error, sp_error)),
+            }
+            if error == 0 && sp_error == 0 {
+                break;
+            }
+        }
+
+        match best {
+            Some((timing, error, _)) if error <= BIT_TIMING_MAX_ERROR as u64 * 1000 => {
+                log::debug!("ZLGCAN - solved bit timing: {}", timing);
+                Ok(timing)
+            },
+            Some((timing, _, _)) => Err(ZCanError::ConfigurationError(format!(
+                "the bitrate: {} can't be reached with clock: {}, the nearest is: {:.0}", bitrate, clock, timing.bitrate()
+            ))),
+            None => Err(ZCanError::ConfigurationError(format!(
+                "the bitrate: {} can't be reached with clock: {}", bitrate, clock
+            ))),
+        }
+    }
+
+    #[inline]
+    pub fn clock(&self) -> u32 {
+        self.clock
+    }
+    #[inline]
+    pub fn brp(&self) -> u32 {
+        self.brp
+    }
+    #[inline]
+    pub fn tseg1(&self) -> u32 {
+        self.tseg1
+    }
+    #[inline]
+    pub fn tseg2(&self) -> u32 {
+        self.tseg2
+    }
+    #[inline]
+    pub fn sjw(&self) -> u32 {
+        self.sjw
+    }
+    /// The time quanta count of a bit.
+    #[inline]
+    pub fn tq_count(&self) -> u32 {
+        1 + self.tseg1 + self.tseg2
+    }
+    /// The achieved bitrate.
+    #[inline]
+    pub fn bitrate(&self) -> f64 {
+        self.clock as f64 / (self.brp as f64 * self.tq_count() as f64)
+    }
+    /// The error of achieved bitrate to requested in percent.
+    #[inline]
+    pub fn bitrate_error(&self) -> f64 {
+        (self.bitrate() - self.target as f64) * 100. / self.target as f64
+    }
+    /// The achieved sample point in permille.
+    #[inline]
+    pub fn sample_point(&self) -> u32 {
+        (1 + self.tseg1) * 1000 / self.tq_count()
+    }
+    /// The max bitrate deviation in percent that resynchronization can compensate,
+    /// the phase error of 10 bits must not be greater than SJW.
+    #[inline]
+    pub fn tolerance(&self) -> f64 {
+        self.sjw.min(self.tseg2) as f64 * 100. / (20. * self.tq_count() as f64)
+    }
+
+    /// Check the timing of two nodes on the same bus.
+    /// The bitrate deviation must be in the `tolerance` of both,
+    /// and the sample point difference must not be greater than `BIT_TIMING_MAX_SAMPLE_POINT_DIFF`.
+    pub fn check_compatible(&self, other: &Self) -> Result<(), ZCanError> {
+        let (rate, other_rate) = (self.bitrate(), other.bitrate());
+        let deviation = (rate - other_rate).abs() * 100. / rate.min(other_rate);
+        let tolerance = self.tolerance().min(other.tolerance());
+        if deviation > tolerance {
+            return Err(ZCanError::ConfigurationError(format!(
+                "the bitrate: {:.0} and {:.0} deviate {:.3}%, the tolerance is {:.3}%", rate, other_rate, deviation, tolerance
+            )));
+        }
+        let diff = self.sample_point().abs_diff(other.sample_point());
+        if diff > BIT_TIMING_MAX_SAMPLE_POINT_DIFF {
+            return Err(ZCanError::ConfigurationError(format!(
+                "the sample point: {}‰ and {}‰ differ more than {}‰",
+                self.sample_point(), other.sample_point(), BIT_TIMING_MAX_SAMPLE_POINT_DIFF
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Display for BitTiming {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bitrate: {:.0}({:+.3}%), sample point: {}.{}%, clock: {}, brp: {}, tseg1: {}, tseg2: {}, sjw: {}",
+               self.bitrate(), self.bitrate_error(), self.sample_point() / 10, self.sample_point() % 10,
+               self.clock, self.brp, self.tseg1, self.tseg2, self.sjw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::can::{BitrateTimingCfg, CanChlCfgExt, CanChlCfgFactory, ZCanChlCfgV2, ZCanChlMode, ZCanChlType, ZCanFdChlCfgSet};
+    use crate::device::ZCanDeviceType;
+    use super::{BitTiming, ZCanTimingType};
+
+    #[test]
+    fn test_solve() -> anyhow::Result<()> {
+        let timing_type = ZCanTimingType::SJA1000;
+        let timing = BitTiming::solve(timing_type.clock(), 500_000, Some(875), Some(1), &timing_type.nominal_const())?;
+        assert_eq!(timing.bitrate(), 500_000.);
+        assert_eq!(timing.sample_point(), 875);
+        assert_eq!(timing_type.sja1000(&timing)?, (0x00, 0x1C));
+        let timing = BitTiming::solve(timing_type.clock(), 250_000, Some(875), Some(1), &timing_type.nominal_const())?;
+        assert_eq!(timing_type.sja1000(&timing)?, (0x01, 0x1C));
+
+        let timing_type = ZCanTimingType::USBCANFD;
+        let timing = BitTiming::solve(timing_type.clock(), 2_000_000, Some(800), None, &timing_type.data_const())?;
+        assert_eq!(timing.bitrate(), 2_000_000.);
+        assert_eq!(timing.sample_point(), 800);
+        assert!(timing.sjw() <= timing.tseg2());
+        assert!(timing_type.sja1000(&timing).is_err());
+
+        let timing_type = ZCanTimingType::USBCANFD_800U;
+        let timing = BitTiming::solve(timing_type.clock(), 500_000, Some(800), None, &timing_type.nominal_const())?;
+        assert_eq!(timing.bitrate(), 500_000.);
+        assert_eq!(timing.sample_point(), 800);
+
+        let timing = BitTiming::solve(16_000_000, 33_333, None, None, &ZCanTimingType::SJA1000.nominal_const())?;
+        assert!(timing.bitrate_error().abs() < 0.5);
+        assert!(BitTiming::solve(16_000_000, 5_000_000, None, None, &ZCanTimingType::SJA1000.nominal_const()).is_err());
+        assert!(BitTiming::solve(16_000_000, 500_000, None, Some(5), &ZCanTimingType::SJA1000.nominal_const()).is_err());
+        // the low sample point is solved to the nearest one
+        let timing = BitTiming::solve(16_000_000, 500_000, Some(100), None, &ZCanTimingType::SJA1000.nominal_const())?;
+        assert_eq!(timing.bitrate(), 500_000.);
+        assert!(timing.sample_point() < 500);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_unconfigured() -> anyhow::Result<()> {
+        let factory: CanChlCfgFactory = "4: { bitrate: { 500000: { timing0: 0, timing1: 28 } } }\n\
+             41: { bitrate: { 500000: { tseg1: 2, tseg2: 0, sjw: 0, smp: 80, brp: 23 } }, clock: 60000000 }\n".parse()?;
+
+        let dev_type = ZCanDeviceType::ZCAN_USBCAN2 as u32;
+        let cfg = factory.new_can_chl_cfg(dev_type, ZCanChlType::CAN as u8, ZCanChlMode::Normal as u8, 33_333, Default::default())?;
+        assert!(ZCanChlCfgV2::try_from(&cfg).is_ok());
+
+        let dev_type = ZCanDeviceType::ZCAN_USBCANFD_200U as u32;
+        let cfg = factory.new_can_chl_cfg(dev_type, ZCanChlType::CANFD_ISO as u8, ZCanChlMode::Normal as u8, 500_000,
+                                          CanChlCfgExt::new(None, Some(5_000_000), None, None, None, None))?;
+        assert!(ZCanChlCfgV2::try_from(&cfg).is_ok());
+        let cfg = factory.new_can_chl_cfg(dev_type, ZCanChlType::CANFD_ISO as u8, ZCanChlMode::Normal as u8, 50_000_000,
+                                          Default::default())?;
+        assert!(ZCanChlCfgV2::try_from(&cfg).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        let error = |s: &str| match s.parse::<CanChlCfgFactory>() {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("`{}` is valid", s),
+        };
+
+        let e = error("41: { bitrate: { 500000: { tseg: 2, tseg2: 0, sjw: 0, smp: 80, brp: 23 } } }");
+        assert!(e.contains("device: `41`, bitrate: `500000` - unknown field `tseg`"), "{}", e);
+        let e = error("41: { bitrate: { 500000: { tseg1: 2, tseg2: 0, smp: 80, brp: 23 } } }");
+        assert!(e.contains("missing field `sjw`"), "{}", e);
+        let e = error("41: { bitrate: { 500000: { tseg1: 64, tseg2: 0, sjw: 0, smp: 80, brp: 23 } } }");
+        assert!(e.contains("device: `41`, bitrate: `500000` - `tseg1`: 64 is out of range 0..=63"), "{}", e);
+        let e = error("59: { bitrate: { 500000: { tseg1: 31, tseg2: 8, sjw: 8, smp: 0, brp: 2 } }, \
+                       data_bitrate: { 2000000: { tseg1: 31, tseg2: 16, sjw: 8, smp: 0, brp: 1 } } }");
+        assert!(e.contains("device: `59`, data bitrate: `2000000` - `tseg2`: 16 is out of range 1..=15"), "{}", e);
+        let e = error("4: { bitrate: { 500000: { timing0: 0, timing1: 256 } } }");
+        assert!(e.contains("device: `4`, bitrate: `500000` - `timing1`: 256 is out of range 0..=255"), "{}", e);
+
+        assert!("USBCANFD: { bitrate: { 500000: { tseg1: 200, tseg2: 0, sjw: 0, smp: 80, brp: 23 } } }"
+            .parse::<CanChlCfgFactory>().is_ok());
+    }
+
+    #[test]
+    fn test_decode() -> anyhow::Result<()> {
+        let timing_type = ZCanTimingType::SJA1000;
+        let timing = timing_type.decode_sja1000(0x00, 0x1C, timing_type.clock())?;
+        assert_eq!((timing.bitrate(), timing.sample_point(), timing.tq_count(), timing.sjw()), (500_000., 875, 16, 1));
+        assert_eq!(timing_type.sja1000(&timing)?, (0x00, 0x1C));
+        assert!(timing_type.decode_timing(0x44081F, 16_000_000).is_err());
+
+        let timing_type = ZCanTimingType::USBCANFD;
+        let set = ZCanFdChlCfgSet::new(2, 0, 0, 80, 23);
+        let timing = timing_type.decode_fd_set(&set, timing_type.clock())?;
+        assert_eq!((timing.bitrate(), timing.sample_point(), timing.brp()), (500_000., 800, 24));
+
+        let timing_type = ZCanTimingType::USBCANFD_800U;
+        let timing = timing_type.decode_timing(0x44081F, timing_type.clock())?;
+        assert_eq!((timing.brp(), timing.tseg1(), timing.tseg2(), timing.sjw()), (1, 31, 8, 8));
+        assert_eq!((timing.bitrate(), timing.sample_point()), (1_000_000., 800));
+        assert_eq!(timing_type.fd_set(&timing)?.get_timing(), 0x44081F);
+        assert!(timing_type.decode_timing(0, timing_type.clock()).is_err());
+
+        // audit the built-in tables
+        let factory = CanChlCfgFactory::builtin()?;
+        for dev_type in ZCanDeviceType::ALL {
+            let Some(cfg) = factory.device_cfg(*dev_type as u32) else { continue };
+            let entries = cfg.bitrate().iter()
+                .chain(cfg.dbitrate().iter().flat_map(|v| v.iter()));
+            for (bitrate, entry) in entries {
+                let timing_type = match entry {
+                    BitrateTimingCfg::Can(_) => ZCanTimingType::SJA1000,
+                    BitrateTimingCfg::CanFd(_) => match ZCanTimingType::from_device_type(*dev_type) {
+                        ZCanTimingType::SJA1000 => ZCanTimingType::USBCANFD,
+                        v => v,
+                    },
+                };
+                let timing = timing_type.decode(*bitrate, entry, cfg.clock().unwrap_or(timing_type.clock()))?;
+                assert!(timing.bitrate_error().abs() < 1., "device: {:?}, {}", dev_type, timing);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compatible() -> anyhow::Result<()> {
+        let sja1000 = ZCanTimingType::SJA1000.decode_sja1000(0x00, 0x1C, 16_000_000)?;
+        let usbcanfd = ZCanTimingType::USBCANFD.decode_fd_set(&ZCanFdChlCfgSet::new(2, 0, 0, 80, 23), 60_000_000)?;
+        let timing_type = ZCanTimingType::USBCANFD_800U;
+        let usbcanfd_800u = BitTiming::solve(timing_type.clock(), 500_000, Some(800), None, &timing_type.nominal_const())?;
+
+        assert!(usbcanfd.check_compatible(&usbcanfd_800u).is_ok());
+        let e = sja1000.check_compatible(&usbcanfd).unwrap_err().to_string();
+        assert!(e.contains("sample point: 875‰ and 800‰"), "{}", e);
+        let slow = ZCanTimingType::SJA1000.decode_sja1000(0x00, 0x1D, 16_000_000)?;
+        let e = sja1000.check_compatible(&slow).unwrap_err().to_string();
+        assert!(e.contains("deviate"), "{}", e);
+
+        Ok(())
+    }
+}
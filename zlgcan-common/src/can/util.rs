@@ -1,9 +1,9 @@
 use isotp_rs::can::{IdentifierFlags, SFF_MASK, EFF_MASK, frame::{Frame, Direct}, identifier::Id};
-use crate::can::constant::{CANFD_BRS, CANFD_ESI, ZCanFrameType};
+use crate::can::constant::{CANERR_FRAME_LENGTH, CANFD_BRS, CANFD_ESI, ZCanFrameType};
 use crate::can::frame::NewZCanFrame;
 use crate::{TryFrom, TryFromIterator};
 use crate::error::ZCanError;
-use crate::utils::{fix_device_time, fix_system_time, data_resize};
+use crate::utils::fix_device_time;
 use super::{
     channel::{ZCanChlErrorV1, ZCanChlErrorV2},
     constant::ZCanHdrInfoField,
@@ -58,9 +58,11 @@ impl TryFrom<CanMessage, u64> for ZCanFrameV1 {
     }
 }
 
+/// The received message is stamped with the host time of conversion,
+/// it's replaced by the `TimestampTracker` of channel that converts the hardware ticks by the timestamp unit of device.
 impl TryFrom<ZCanFrameV1, u64> for CanMessage {
     type Error = ZCanError;
-    fn try_from(value: ZCanFrameV1, timestamp: u64) -> Result<Self, Self::Error> {
+    fn try_from(value: ZCanFrameV1, _: u64) -> Result<Self, Self::Error> {
         let id = if value.ext_flag > 0 {
             Id::Extended(value.can_id)
         }
@@ -72,14 +74,13 @@ impl TryFrom<ZCanFrameV1, u64> for CanMessage {
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }
         else {
-            let mut data = value.data.to_vec();
-            data_resize(&mut data, value.len as usize);
-            CanMessage::new(id, data.as_slice())
+            CanMessage::new_padded(id, &value.data, value.len as usize)
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }?;
 
         message.set_direct(Direct::Receive)
-            .set_timestamp(Some(fix_system_time(value.timestamp as u64, timestamp)))
+            .set_timestamp(None)
+            .set_hardware_timestamp(Some(value.timestamp))
             .set_channel(value.channel);
 
         Ok(message)
@@ -113,7 +114,7 @@ impl TryFrom<CanMessage, u64> for ZCanFrameV2 {
 
 impl TryFrom<ZCanFrameV2, u64> for CanMessage {
     type Error = ZCanError;
-    fn try_from(value: ZCanFrameV2, timestamp: u64) -> Result<Self, Self::Error> {
+    fn try_from(value: ZCanFrameV2, _: u64) -> Result<Self, Self::Error> {
         let hdr = value.hdr;
         let info = hdr.info;
 
@@ -128,14 +129,13 @@ impl TryFrom<ZCanFrameV2, u64> for CanMessage {
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }
         else {
-            let mut data = value.data.to_vec();
-            data_resize(&mut data, hdr.len as usize);
-            CanMessage::new(id, data.as_slice())
+            CanMessage::new_padded(id, &value.data, hdr.len as usize)
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }?;
 
         message.set_direct(Direct::Receive)
-            .set_timestamp(Some(fix_system_time(value.hdr.timestamp as u64, timestamp)))
+            .set_timestamp(None)
+            .set_hardware_timestamp(Some(value.hdr.timestamp))
             .set_channel(hdr.channel)
             .set_error_frame(info.get_field(ZCanHdrInfoField::IsErrorFrame) > 0);
 
@@ -170,7 +170,7 @@ impl TryFrom<CanMessage, u64> for ZCanFrameV3 {
 
 impl TryFrom<ZCanFrameV3, u64> for CanMessage {
     type Error = ZCanError;
-    fn try_from(value: ZCanFrameV3, timestamp: u64) -> Result<Self, Self::Error> {
+    fn try_from(value: ZCanFrameV3, _: u64) -> Result<Self, Self::Error> {
         let hdr = value.hdr;
         let can_id = hdr.can_id;
 
@@ -185,14 +185,13 @@ impl TryFrom<ZCanFrameV3, u64> for CanMessage {
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }
         else {
-            let mut data = value.data.to_vec();
-            data_resize(&mut data, hdr.can_len as usize);
-            CanMessage::new(id, data.as_slice())
+            CanMessage::new_padded(id, &value.data, hdr.can_len as usize)
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }?;
 
         message.set_direct(Direct::Receive)
-            .set_timestamp(Some(fix_system_time(value.ts_or_mode as u64, timestamp)))
+            .set_timestamp(None)
+            .set_hardware_timestamp(Some(value.ts_or_mode))
             .set_channel(hdr.__res0)
             .set_error_frame((can_id & IdentifierFlags::ERROR.bits()) > 0);
 
@@ -228,7 +227,7 @@ impl TryFrom<CanMessage, u64> for ZCanFdFrameV1 {
 
 impl TryFrom<ZCanFdFrameV1, u64> for CanMessage {
     type Error = ZCanError;
-    fn try_from(value: ZCanFdFrameV1, timestamp: u64) -> Result<Self, Self::Error> {
+    fn try_from(value: ZCanFdFrameV1, _: u64) -> Result<Self, Self::Error> {
         let hdr = value.hdr;
         let info = hdr.info;
         let can_id = hdr.can_id;
@@ -244,15 +243,14 @@ impl TryFrom<ZCanFdFrameV1, u64> for CanMessage {
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }
         else {
-            let mut data = value.data.data.to_vec();
-            data_resize(&mut data, hdr.len as usize);
-            CanMessage::new(id, data.as_slice())
+            CanMessage::new_padded(id, &value.data.data, hdr.len as usize)
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }?;
 
         message.set_direct(Direct::Receive)
             .set_can_fd(true)
-            .set_timestamp(Some(fix_system_time(hdr.timestamp as u64, timestamp)))
+            .set_timestamp(None)
+            .set_hardware_timestamp(Some(hdr.timestamp))
             .set_channel(hdr.channel)
             .set_error_frame((can_id & IdentifierFlags::ERROR.bits()) > 0)
             .set_bitrate_switch(info.get_field(ZCanHdrInfoField::IsBitrateSwitch) > 0)
@@ -289,7 +287,7 @@ impl TryFrom<CanMessage, u64> for ZCanFdFrameV2 {
 
 impl TryFrom<ZCanFdFrameV2, u64> for CanMessage {
     type Error = ZCanError;
-    fn try_from(value: ZCanFdFrameV2, timestamp: u64) -> Result<Self, Self::Error> {
+    fn try_from(value: ZCanFdFrameV2, _: u64) -> Result<Self, Self::Error> {
         let hdr = value.hdr;
         let can_id = hdr.can_id;
         let flag = hdr.flag;
@@ -305,15 +303,14 @@ impl TryFrom<ZCanFdFrameV2, u64> for CanMessage {
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }
         else {
-            let mut data = value.data.data.to_vec();
-            data_resize(&mut data, hdr.can_len as usize);
-            CanMessage::new(id, data.as_slice())
+            CanMessage::new_padded(id, &value.data.data, hdr.can_len as usize)
                 .ok_or(ZCanError::Other("invalid data length".to_string()))
         }?;
 
         message.set_direct(Direct::Receive)
             .set_can_fd(true)
-            .set_timestamp(Some(fix_system_time(value.ts_or_mode as u64, timestamp)))
+            .set_timestamp(None)
+            .set_hardware_timestamp(Some(value.ts_or_mode))
             .set_channel(hdr.__res0)
             .set_error_frame(can_id & IdentifierFlags::ERROR.bits() > 0)
             .set_bitrate_switch(flag & CANFD_BRS > 0)
@@ -343,7 +340,7 @@ impl TryFromIterator<ZCanFdFrameV2, u64> for Vec<CanMessage> {
 
 impl TryFrom<ZCanChlErrorV1, u64> for CanMessage {
     type Error = ZCanError;
-    fn try_from(value: ZCanChlErrorV1, timestamp: u64) -> Result<Self, Self::Error> {
+    fn try_from(value: ZCanChlErrorV1, _: u64) -> Result<Self, Self::Error> {
         let hdr = value.hdr;
         let info = hdr.info;
 
@@ -353,13 +350,12 @@ impl TryFrom<ZCanChlErrorV1, u64> for CanMessage {
         else {
             Id::Standard(hdr.can_id  as u16)
         };
-        let mut data = value.data.to_vec();
-        data_resize(&mut data, hdr.len as usize);
-        let mut message = CanMessage::new(id, data.as_slice())
+        let mut message = CanMessage::new_padded(id, &value.data, hdr.len as usize)
             .ok_or(ZCanError::Other("invalid data length".to_string()))?;
 
         message.set_direct(Direct::Receive)
-            .set_timestamp(Some(fix_system_time(hdr.timestamp as u64, timestamp)))
+            .set_timestamp(None)
+            .set_hardware_timestamp(Some(hdr.timestamp))
             .set_channel(hdr.channel)
             .set_error_frame(true);
 
@@ -367,11 +363,22 @@ impl TryFrom<ZCanChlErrorV1, u64> for CanMessage {
     }
 }
 
-#[allow(unused_variables)]
+/// The data of error frame is the error code(little endian), `passive_ErrData` and `arLost_ErrData`.
 impl TryFrom<ZCanChlErrorV2, ()> for CanMessage {
     type Error = ZCanError;
     fn try_from(value: ZCanChlErrorV2, _: ()) -> Result<Self, Self::Error> {
-        todo!()
+        let mut data = [0u8; CANERR_FRAME_LENGTH];
+        data[..4].copy_from_slice(&value.error_code.to_le_bytes());
+        data[4..7].copy_from_slice(&value.passive_ErrData);
+        data[7] = value.arLost_ErrData;
+        let mut message = CanMessage::new(Id::Standard(0), &data)
+            .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+
+        message.set_direct(Direct::Receive)
+            .set_timestamp(None)
+            .set_error_frame(true);
+
+        Ok(message)
     }
 }
 
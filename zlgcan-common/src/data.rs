@@ -0,0 +1,540 @@
+use std::ffi::{c_uchar, c_ushort};
+use isotp_rs::can::frame::{Direct, Frame};
+use crate::can::{CanMessage, ChannelError, ZCanChlErrorV1, ZCanFdFrameV1, ZCanFdFrameV2, ZCanFrameV2, ZCanFrameV3, CANERR_FRAME_LENGTH};
+use crate::error::ZCanError;
+use crate::lin::LinMessage;
+use crate::{TryFrom, TryFromIterator};
+
+const DATA_OBJECT_SIZE: usize = 92;
+
+/// The data type of `ZCANDataObj`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ZCanDataType {
+    CAN = 1,
+    CANFD = 2,
+    ERROR = 3,
+    LIN = 4,
+    LIN_ERROR = 5,
+}
+
+impl std::convert::TryFrom<u8> for ZCanDataType {
+    type Error = ZCanError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ZCanDataType::CAN),
+            2 => Ok(ZCanDataType::CANFD),
+            3 => Ok(ZCanDataType::ERROR),
+            4 => Ok(ZCanDataType::LIN),
+            5 => Ok(ZCanDataType::LIN_ERROR),
+            _ => Err(ZCanError::ParamNotSupported),
+        }
+    }
+}
+
+/// The data object of `VCI_TransmitData` and `VCI_ReceiveData`, used by USBCANFD on linux.
+/// The `data` is the union of `ZCAN_20_MSG`, `ZCAN_FD_MSG`, `ZCAN_ERR_MSG`, `ZCANLINData` and `ZCANLINErrData`.
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct ZCanDataObj {
+    pub(crate) data_type: c_uchar,
+    pub(crate) channel: c_uchar,
+    #[allow(dead_code)]
+    pub(crate) flag: c_ushort,
+    #[allow(dead_code)]
+    pub(crate) extra: [c_uchar; 4],
+    pub(crate) data: [c_uchar; DATA_OBJECT_SIZE],
+}
+
+impl Default for ZCanDataObj {
+    fn default() -> Self {
+        Self {
+            data_type: Default::default(),
+            channel: Default::default(),
+            flag: Default::default(),
+            extra: Default::default(),
+            data: [Default::default(); DATA_OBJECT_SIZE],
+        }
+    }
+}
+
+impl ZCanDataObj {
+    #[inline]
+    fn read<T: Copy>(&self) -> T {
+        let data = self.data;
+        debug_assert!(size_of::<T>() <= DATA_OBJECT_SIZE);
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const T) }
+    }
+
+    #[inline]
+    fn write<T: Copy>(&mut self, value: T) {
+        let mut data = [0; DATA_OBJECT_SIZE];
+        debug_assert!(size_of::<T>() <= DATA_OBJECT_SIZE);
+        unsafe { std::ptr::write_unaligned(data.as_mut_ptr() as *mut T, value) };
+        self.data = data;
+    }
+}
+
+impl From<ZCanFrameV2> for ZCanDataObj {
+    fn from(value: ZCanFrameV2) -> Self {
+        let mut result = Self { data_type: ZCanDataType::CAN as u8, channel: value.hdr.channel, ..Default::default() };
+        result.write(value);
+        result
+    }
+}
+
+impl From<ZCanFdFrameV1> for ZCanDataObj {
+    fn from(value: ZCanFdFrameV1) -> Self {
+        let mut result = Self { data_type: ZCanDataType::CANFD as u8, channel: value.hdr.channel, ..Default::default() };
+        result.write(value);
+        result
+    }
+}
+
+/// The data type of `ZCANDataObj` in `zlgcan.h`, the CAN and CANFD frames share one type.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ZCanDataTypeV2 {
+    CAN_CANFD = 1,
+    ERROR = 2,
+    GPS = 3,
+    LIN = 4,
+    BUS_USAGE = 5,
+    LIN_ERROR = 6,
+}
+
+impl std::convert::TryFrom<u8> for ZCanDataTypeV2 {
+    type Error = ZCanError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ZCanDataTypeV2::CAN_CANFD),
+            2 => Ok(ZCanDataTypeV2::ERROR),
+            3 => Ok(ZCanDataTypeV2::GPS),
+            4 => Ok(ZCanDataTypeV2::LIN),
+            5 => Ok(ZCanDataTypeV2::BUS_USAGE),
+            6 => Ok(ZCanDataTypeV2::LIN_ERROR),
+            _ => Err(ZCanError::ParamNotSupported),
+        }
+    }
+}
+
+/// The data object of `ZCAN_ReceiveData`, used by USBCANFD-800U on linux and the devices on windows.
+/// The `data` is the union of `ZCANCANFDData`, `ZCANErrorData`, `ZCANGPSData`, `ZCANLINData`, `ZCANLINErrData` and `BusUsage`.
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct ZCanDataObjV2 {
+    pub(crate) data_type: c_uchar,
+    pub(crate) channel: c_uchar,
+    #[allow(dead_code)]
+    pub(crate) flag: c_ushort,
+    #[allow(dead_code)]
+    pub(crate) extra: [c_uchar; 4],
+    pub(crate) data: [c_uchar; DATA_OBJECT_SIZE],
+}
+
+impl Default for ZCanDataObjV2 {
+    fn default() -> Self {
+        Self {
+            data_type: Default::default(),
+            channel: Default::default(),
+            flag: Default::default(),
+            extra: Default::default(),
+            data: [Default::default(); DATA_OBJECT_SIZE],
+        }
+    }
+}
+
+impl ZCanDataObjV2 {
+    /// The offset of `canfd_frame` in `ZCANCANFDData`, after the timestamp, flag and extra data.
+    const FRAME_OFFSET: usize = 16;
+
+    #[inline]
+    fn timestamp(&self) -> Result<u64, ZCanError> {
+        let data = self.data;
+        Ok(u64::from_le_bytes(data[..8].try_into().map_err(|_| ZCanError::MessageConvertFailed)?))
+    }
+
+    /// The `ZCANCANFDData`: the `canfd_frame` is same as `ZCanFdFrameV2` without the timestamp,
+    /// the frame type is bit 0-1 and the `txEchoed` is bit 9 of flag.
+    fn can_data(&self, timestamp: u64) -> Result<CanMessage, ZCanError> {
+        let data = self.data;
+        let hardware = self.timestamp()?;
+        let flag = u32::from_le_bytes(data[8..12].try_into().map_err(|_| ZCanError::MessageConvertFailed)?);
+        debug_assert!(Self::FRAME_OFFSET + size_of::<ZCanFdFrameV2>() <= DATA_OBJECT_SIZE);
+        let mut frame = unsafe { std::ptr::read_unaligned(data[Self::FRAME_OFFSET..].as_ptr() as *const ZCanFdFrameV2) };
+        // the low 32 bits are kept as the ticks of other frames
+        frame.ts_or_mode = hardware as u32;
+        let mut message = <CanMessage as TryFrom<ZCanFdFrameV2, u64>>::try_from(frame, timestamp)?;
+        message.set_can_fd(flag & 0x03 == 1)
+            .set_direct(if flag & (1 << 9) > 0 { Direct::Transmit } else { Direct::Receive });
+        Ok(message)
+    }
+
+    /// The `ZCANErrorData`: timestamp, error type, sub type, node state, RX and TX counters and error data,
+    /// it's same as `ZCAN_ERR_MSG` except the layout.
+    fn error_data(&self, timestamp: u64) -> Result<(CanMessage, Vec<ChannelError>), ZCanError> {
+        let data = self.data;
+        let mut error = ZCanChlErrorV1::default();
+        error.hdr.timestamp = self.timestamp()? as u32;
+        error.hdr.can_id = data[8] as u32;
+        error.hdr.len = CANERR_FRAME_LENGTH as u8;
+        error.data[..5].copy_from_slice(&data[9..14]);
+        let errors = ChannelError::from_v1(&error);
+        Ok((<CanMessage as TryFrom<ZCanChlErrorV1, u64>>::try_from(error, timestamp)?, errors))
+    }
+}
+
+impl ZCanDataObjV2 {
+    fn from_frame(frame: ZCanFdFrameV2, canfd: bool) -> Self {
+        let mut data = [0; DATA_OBJECT_SIZE];
+        data[..8].copy_from_slice(&(frame.ts_or_mode as u64).to_le_bytes());
+        data[8..12].copy_from_slice(&(canfd as u32).to_le_bytes());
+        unsafe { std::ptr::write_unaligned(data[Self::FRAME_OFFSET..].as_mut_ptr() as *mut ZCanFdFrameV2, frame) };
+        Self { data_type: ZCanDataTypeV2::CAN_CANFD as u8, channel: frame.hdr.__res0, data, ..Default::default() }
+    }
+}
+
+impl From<ZCanFrameV3> for ZCanDataObjV2 {
+    fn from(value: ZCanFrameV3) -> Self {
+        let mut frame = ZCanFdFrameV2 { hdr: value.hdr, ts_or_mode: value.ts_or_mode, ..Default::default() };
+        frame.data.data[..value.data.len()].copy_from_slice(&value.data);
+        Self::from_frame(frame, false)
+    }
+}
+
+impl From<ZCanFdFrameV2> for ZCanDataObjV2 {
+    #[inline]
+    fn from(value: ZCanFdFrameV2) -> Self {
+        Self::from_frame(value, true)
+    }
+}
+
+/// The object transmitted or received by one call, the CAN(FD), error and LIN frames are mixed.
+#[derive(Debug, Clone)]
+pub enum DataObject {
+    /// The CAN or CAN FD frame.
+    Can(CanMessage),
+    /// The error frame of CAN channel and the decoded errors.
+    Error { frame: CanMessage, errors: Vec<ChannelError> },
+    Lin(LinMessage),
+    /// The LIN error frame, `stage` and `reason` are given by device.
+    LinError { message: LinMessage, stage: u8, reason: u8 },
+}
+
+impl DataObject {
+    #[inline]
+    pub fn channel(&self) -> u8 {
+        match self {
+            Self::Can(v) | Self::Error { frame: v, .. } => v.channel(),
+            Self::Lin(v) | Self::LinError { message: v, .. } => v.channel(),
+        }
+    }
+
+    /// The host time(µs) of the object.
+    #[inline]
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Self::Can(v) | Self::Error { frame: v, .. } => v.timestamp(),
+            Self::Lin(v) | Self::LinError { message: v, .. } => v.timestamp(),
+        }
+    }
+
+    #[inline]
+    pub fn set_timestamp(&mut self, value: Option<u64>) -> &mut Self {
+        match self {
+            Self::Can(v) | Self::Error { frame: v, .. } => { v.set_timestamp(value); },
+            Self::Lin(v) | Self::LinError { message: v, .. } => { v.set_timestamp(value); },
+        }
+        self
+    }
+}
+
+impl From<CanMessage> for DataObject {
+    #[inline]
+    fn from(value: CanMessage) -> Self {
+        Self::Can(value)
+    }
+}
+
+impl From<LinMessage> for DataObject {
+    #[inline]
+    fn from(value: LinMessage) -> Self {
+        Self::Lin(value)
+    }
+}
+
+/// `ZCANLINData`: PID, timestamp, length, direction, checksum, reserved(13) and data.
+fn lin_data(data: &[u8]) -> Result<LinMessage, ZCanError> {
+    let hardware = u64::from_le_bytes(data[1..9].try_into().map_err(|_| ZCanError::MessageConvertFailed)?);
+    let (len, dir, checksum) = (data[9] as usize, data[10], data[11]);
+    lin_message(data[0], &data[25..33], len, dir, checksum, hardware)
+}
+
+/// `ZCANLINErrData`: timestamp, PID, length, data, error data, direction and checksum.
+fn lin_error(data: &[u8]) -> Result<(LinMessage, u8, u8), ZCanError> {
+    let hardware = u64::from_le_bytes(data[0..8].try_into().map_err(|_| ZCanError::MessageConvertFailed)?);
+    let (len, dir, checksum) = (data[9] as usize, data[20], data[21]);
+    let message = lin_message(data[8], &data[10..18], len, dir, checksum, hardware)?;
+    let error = u16::from_le_bytes([data[18], data[19]]);
+    Ok((message, (error & 0x0F) as u8, ((error >> 4) & 0x0F) as u8))
+}
+
+/// The message is stamped with the host time of conversion, it's replaced by the `TimestampTracker` of channel.
+fn lin_message(pid: u8, data: &[u8], len: usize, dir: u8, checksum: u8, hardware: u64) -> Result<LinMessage, ZCanError> {
+    let mut message = LinMessage::new(pid, data.get(..len).unwrap_or(data))
+        .ok_or(ZCanError::MessageConvertFailed)?;
+    message.set_timestamp(None)
+        .set_hardware_timestamp(Some(hardware))
+        .set_direct(if dir > 0 { Direct::Transmit } else { Direct::Receive })
+        .set_checksum(checksum);
+    Ok(message)
+}
+
+impl TryFrom<ZCanDataObj, u64> for DataObject {
+    type Error = ZCanError;
+    fn try_from(value: ZCanDataObj, timestamp: u64) -> Result<Self, ZCanError> {
+        let channel = value.channel;
+        let data = value.data;
+        let mut result = match <ZCanDataType as std::convert::TryFrom<u8>>::try_from(value.data_type)? {
+            ZCanDataType::CAN => {
+                let frame = value.read::<ZCanFrameV2>();
+                Self::Can(<CanMessage as TryFrom<ZCanFrameV2, u64>>::try_from(frame, timestamp)?)
+            },
+            ZCanDataType::CANFD => {
+                let frame = value.read::<ZCanFdFrameV1>();
+                Self::Can(<CanMessage as TryFrom<ZCanFdFrameV1, u64>>::try_from(frame, timestamp)?)
+            },
+            ZCanDataType::ERROR => {
+                let error = value.read::<ZCanChlErrorV1>();
+                let errors = ChannelError::from_v1(&error);
+                Self::Error { frame: <CanMessage as TryFrom<ZCanChlErrorV1, u64>>::try_from(error, timestamp)?, errors }
+            },
+            ZCanDataType::LIN => Self::Lin(lin_data(&data)?),
+            ZCanDataType::LIN_ERROR => {
+                let (message, stage, reason) = lin_error(&data)?;
+                Self::LinError { message, stage, reason }
+            },
+        };
+
+        match &mut result {
+            Self::Can(v) | Self::Error { frame: v, .. } => { v.set_channel(channel); },
+            Self::Lin(v) | Self::LinError { message: v, .. } => { v.set_channel(channel); },
+        }
+
+        Ok(result)
+    }
+}
+
+/// The GPS and bus usage data are not supported.
+impl TryFrom<ZCanDataObjV2, u64> for DataObject {
+    type Error = ZCanError;
+    fn try_from(value: ZCanDataObjV2, timestamp: u64) -> Result<Self, ZCanError> {
+        let channel = value.channel;
+        let data = value.data;
+        let mut result = match <ZCanDataTypeV2 as std::convert::TryFrom<u8>>::try_from(value.data_type)? {
+            ZCanDataTypeV2::CAN_CANFD => Self::Can(value.can_data(timestamp)?),
+            ZCanDataTypeV2::ERROR => {
+                let (frame, errors) = value.error_data(timestamp)?;
+                Self::Error { frame, errors }
+            },
+            ZCanDataTypeV2::LIN => Self::Lin(lin_data(&data)?),
+            ZCanDataTypeV2::LIN_ERROR => {
+                let (message, stage, reason) = lin_error(&data)?;
+                Self::LinError { message, stage, reason }
+            },
+            ZCanDataTypeV2::GPS | ZCanDataTypeV2::BUS_USAGE => return Err(ZCanError::ParamNotSupported),
+        };
+
+        match &mut result {
+            Self::Can(v) | Self::Error { frame: v, .. } => { v.set_channel(channel); },
+            Self::Lin(v) | Self::LinError { message: v, .. } => { v.set_channel(channel); },
+        }
+
+        Ok(result)
+    }
+}
+
+/// Only the CAN(FD) and LIN frames can be transmitted.
+impl TryFrom<DataObject, u64> for ZCanDataObj {
+    type Error = ZCanError;
+    fn try_from(value: DataObject, timestamp: u64) -> Result<Self, Self::Error> {
+        let channel = value.channel();
+        let mut result = match value {
+            DataObject::Can(message) if message.is_can_fd() => {
+                Self::from(<ZCanFdFrameV1 as TryFrom<CanMessage, u64>>::try_from(message, timestamp)?)
+            },
+            DataObject::Can(message) => {
+                Self::from(<ZCanFrameV2 as TryFrom<CanMessage, u64>>::try_from(message, timestamp)?)
+            },
+            DataObject::Lin(message) => {
+                let mut data = [0; DATA_OBJECT_SIZE];
+                let len = message.length();
+                data[0] = message.pid();
+                data[9] = len as u8;
+                data[10] = matches!(message.direct(), Direct::Transmit) as u8;
+                data[11] = message.checksum();
+                data[25..25 + len].copy_from_slice(message.data());
+                Self { data_type: ZCanDataType::LIN as u8, data, ..Default::default() }
+            },
+            _ => return Err(ZCanError::ParamNotSupported),
+        };
+        result.channel = channel;
+
+        Ok(result)
+    }
+}
+
+impl TryFromIterator<ZCanDataObj, u64> for Vec<DataObject> {
+    type Error = ZCanError;
+    fn try_from_iter<T: IntoIterator<Item=ZCanDataObj>>(iter: T, timestamp: u64) -> Result<Self, Self::Error> {
+        iter.into_iter()
+            .map(|v| <DataObject as TryFrom<ZCanDataObj, u64>>::try_from(v, timestamp))
+            .collect()
+    }
+}
+
+/// The GPS and bus usage data are skipped, they are received with the frames when the merged receive is enabled.
+impl TryFromIterator<ZCanDataObjV2, u64> for Vec<DataObject> {
+    type Error = ZCanError;
+    fn try_from_iter<T: IntoIterator<Item=ZCanDataObjV2>>(iter: T, timestamp: u64) -> Result<Self, Self::Error> {
+        iter.into_iter()
+            .filter(|v| !matches!(
+                <ZCanDataTypeV2 as std::convert::TryFrom<u8>>::try_from(v.data_type),
+                Ok(ZCanDataTypeV2::GPS | ZCanDataTypeV2::BUS_USAGE)
+            ))
+            .map(|v| <DataObject as TryFrom<ZCanDataObjV2, u64>>::try_from(v, timestamp))
+            .collect()
+    }
+}
+
+impl TryFromIterator<DataObject, u64> for Vec<ZCanDataObj> {
+    type Error = ZCanError;
+    fn try_from_iter<T: IntoIterator<Item=DataObject>>(iter: T, timestamp: u64) -> Result<Self, Self::Error> {
+        iter.into_iter()
+            .map(|v| <ZCanDataObj as TryFrom<DataObject, u64>>::try_from(v, timestamp))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+    use crate::can::{CanMessage, ChannelError, ErrorCounters, ZCanFdFrameV2, ZCanFrameV3};
+    use crate::lin::LinMessage;
+    use crate::{TryFrom, TryFromIterator};
+    use super::{DataObject, ZCanDataObj, ZCanDataObjV2, ZCanDataType, ZCanDataTypeV2};
+
+    #[test]
+    fn test_data_object() -> anyhow::Result<()> {
+        assert_eq!(size_of::<ZCanDataObj>(), 100);
+
+        let mut message = CanMessage::new(Id::Extended(0x18DAF110), &[0x11; 20])
+            .ok_or(anyhow::anyhow!("invalid data"))?;
+        message.set_channel(1)
+            .set_bitrate_switch(true);
+        let mut lin = LinMessage::new(0xC1, &[0x01, 0x02]).ok_or(anyhow::anyhow!("invalid data"))?;
+        lin.set_channel(1)
+            .set_checksum(0x5A);
+        let objects = vec![
+            DataObject::from(CanMessage::new(Id::Standard(0x7DF), &[0x02, 0x10, 0x01]).unwrap()),
+            DataObject::from(message),
+            DataObject::from(lin),
+        ];
+        let raw = Vec::<ZCanDataObj>::try_from_iter(objects, 0)?;
+        assert_eq!((raw[0].data_type, raw[1].data_type, raw[2].data_type), (1, 2, 4));
+        assert_eq!(raw[1].channel, 1);
+
+        // the host time is stamped when converted, the hardware timestamp is kept
+        let host = crate::utils::system_timestamp();
+        let objects = Vec::<DataObject>::try_from_iter(raw, 1_000_000)?;
+        let DataObject::Can(message) = &objects[1] else { anyhow::bail!("not a CAN frame") };
+        assert!(message.is_can_fd() && message.is_bitrate_switch() && message.is_extended());
+        assert_eq!((message.channel(), message.length()), (1, 20));
+        let DataObject::Lin(lin) = &objects[2] else { anyhow::bail!("not a LIN frame") };
+        assert_eq!((lin.id(), lin.data(), lin.checksum()), (0x01, [0x01, 0x02].as_slice(), 0x5A));
+        assert_eq!((lin.channel(), lin.direct(), lin.hardware_timestamp()), (1, Direct::Transmit, Some(0)));
+        assert!(objects[2].timestamp() >= host);
+
+        // ZCAN_ERR_MSG: bus error(CRC), error passive, RX counter 130
+        let mut raw = ZCanDataObj { data_type: ZCanDataType::ERROR as u8, channel: 0, ..Default::default() };
+        let mut data = raw.data;
+        data[..4].copy_from_slice(&100u32.to_le_bytes());
+        data[4..8].copy_from_slice(&1u32.to_le_bytes());
+        data[15] = 8;
+        data[16..21].copy_from_slice(&[3, 3, 130, 0, 0]);
+        raw.data = data;
+        let object = <DataObject as TryFrom<ZCanDataObj, u64>>::try_from(raw, 0)?;
+        let DataObject::Error { frame, errors } = &object else { anyhow::bail!("not an error frame") };
+        assert!(frame.is_error_frame());
+        assert_eq!(frame.hardware_timestamp(), Some(100));
+        let counters = ErrorCounters { rx: 130, tx: 0 };
+        assert_eq!(errors, &vec![ChannelError::Protocol { kind: crate::can::ProtocolError::Crc, rx: true, counters }, ChannelError::ErrorPassive(counters)]);
+        assert!(<ZCanDataObj as TryFrom<DataObject, u64>>::try_from(object, 0).is_err());
+
+        // ZCANLINErrData
+        let mut raw = ZCanDataObj { data_type: ZCanDataType::LIN_ERROR as u8, channel: 2, ..Default::default() };
+        let mut data = raw.data;
+        data[..8].copy_from_slice(&500u64.to_le_bytes());
+        data[8] = 0x3C;
+        data[9] = 1;
+        data[10] = 0xFF;
+        data[18] = 0x21;
+        raw.data = data;
+        let object = <DataObject as TryFrom<ZCanDataObj, u64>>::try_from(raw, 1_000)?;
+        let DataObject::LinError { message, stage, reason } = &object else { anyhow::bail!("not a LIN error frame") };
+        assert_eq!((message.pid(), message.data(), message.direct()), (0x3C, [0xFF].as_slice(), Direct::Receive));
+        assert_eq!((*stage, *reason, object.channel(), message.hardware_timestamp()), (1, 2, 2, Some(500)));
+        assert!(object.timestamp() >= host);
+
+        raw.data_type = 6;
+        assert!(<DataObject as TryFrom<ZCanDataObj, u64>>::try_from(raw, 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_object_v2() -> anyhow::Result<()> {
+        assert_eq!(size_of::<ZCanDataObjV2>(), 100);
+
+        let mut message = CanMessage::new(Id::Extended(0x18DAF110), &[0x11; 20])
+            .ok_or(anyhow::anyhow!("invalid data"))?;
+        message.set_channel(1)
+            .set_bitrate_switch(true);
+        let mut frame = <ZCanFdFrameV2 as TryFrom<CanMessage, u64>>::try_from(message, 0)?;
+        frame.ts_or_mode = 300;
+        let mut raw = vec![
+            ZCanDataObjV2::from(<ZCanFrameV3 as TryFrom<CanMessage, u64>>::try_from(
+                CanMessage::new(Id::Standard(0x7DF), &[0x02, 0x10, 0x01]).unwrap(), 0
+            )?),
+            ZCanDataObjV2::from(frame),
+            // bus usage
+            ZCanDataObjV2 { data_type: ZCanDataTypeV2::BUS_USAGE as u8, ..Default::default() },
+        ];
+
+        // ZCANErrorData: bus error(CRC), error passive, RX counter 130
+        let mut error = ZCanDataObjV2 { data_type: ZCanDataTypeV2::ERROR as u8, channel: 2, ..Default::default() };
+        let mut data = error.data;
+        data[..8].copy_from_slice(&100u64.to_le_bytes());
+        data[8..14].copy_from_slice(&[1, 3, 3, 130, 0, 0]);
+        error.data = data;
+        raw.push(error);
+
+        let objects = Vec::<DataObject>::try_from_iter(raw, 0)?;
+        assert_eq!(objects.len(), 3);
+        let DataObject::Can(message) = &objects[0] else { anyhow::bail!("not a CAN frame") };
+        assert!(!message.is_can_fd());
+        assert_eq!((message.id(), message.data()), (Id::Standard(0x7DF), [0x02, 0x10, 0x01].as_slice()));
+        let DataObject::Can(message) = &objects[1] else { anyhow::bail!("not a CAN frame") };
+        assert!(message.is_can_fd() && message.is_bitrate_switch() && message.is_extended());
+        assert_eq!((message.channel(), message.length(), message.hardware_timestamp()), (1, 20, Some(300)));
+        let DataObject::Error { frame, errors } = &objects[2] else { anyhow::bail!("not an error frame") };
+        assert!(frame.is_error_frame());
+        assert_eq!((frame.channel(), frame.hardware_timestamp()), (2, Some(100)));
+        let counters = ErrorCounters { rx: 130, tx: 0 };
+        assert_eq!(errors, &vec![ChannelError::Protocol { kind: crate::can::ProtocolError::Crc, rx: true, counters }, ChannelError::ErrorPassive(counters)]);
+
+        Ok(())
+    }
+}
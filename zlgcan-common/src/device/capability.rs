@@ -0,0 +1,249 @@
+//! `capability.rs` defined the capabilities of each device type.
+use super::ZCanDeviceType;
+
+/// The standard bitrates of SJA1000 devices.
+pub const CAN_BITRATES: &[u32] = &[
+    5_000, 10_000, 20_000, 40_000, 50_000, 80_000, 100_000, 125_000, 200_000, 250_000, 400_000, 500_000, 800_000, 1_000_000
+];
+/// The standard nominal bitrates of CANFD devices.
+pub const CANFD_BITRATES: &[u32] = &[
+    10_000, 20_000, 50_000, 100_000, 125_000, 200_000, 250_000, 400_000, 500_000, 800_000, 1_000_000
+];
+/// The standard data bitrates of CANFD devices.
+pub const CANFD_DATA_BITRATES: &[u32] = &[500_000, 1_000_000, 2_000_000, 4_000_000, 5_000_000];
+
+/// The backend that serves the device on current platform.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DeviceBackend {
+    /// The vendor library with the file name.
+    Library(&'static str),
+    /// The backend implemented in crate, it doesn't need vendor library.
+    Builtin,
+    /// The device is not supported on current platform.
+    Unsupported,
+}
+
+/// The capabilities of device type.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DeviceCapabilities {
+    /// The max CAN channels.
+    pub can_channels: u8,
+    /// The max LIN channels.
+    pub lin_channels: u8,
+    /// The device supports CANFD.
+    pub canfd: bool,
+    /// The clock(Hz) of CAN controller, `None` if it is unknown.
+    pub clock: Option<u32>,
+    /// The standard nominal bitrates.
+    pub bitrates: &'static [u32],
+    /// The standard data bitrates, it is empty if CANFD is not supported.
+    pub data_bitrates: &'static [u32],
+    /// The terminal resistance can be controlled.
+    pub resistance: bool,
+    /// The hardware filter(filter record) is supported.
+    pub filter_record: bool,
+    /// The auto send(periodic transmit) is supported.
+    pub auto_send: bool,
+    /// The bus usage is supported.
+    pub bus_usage: bool,
+    /// The merged receive of all channels is supported.
+    pub merged_receive: bool,
+    /// The cloud is supported.
+    pub cloud: bool,
+    /// The microseconds of one tick of device timestamp.
+    pub timestamp_unit: u32,
+    pub backend: DeviceBackend,
+}
+
+impl DeviceCapabilities {
+    const fn can(can_channels: u8) -> Self {
+        Self {
+            can_channels,
+            lin_channels: 0,
+            canfd: false,
+            clock: Some(16_000_000),
+            bitrates: CAN_BITRATES,
+            data_bitrates: &[],
+            resistance: true,
+            filter_record: false,
+            auto_send: false,
+            bus_usage: false,
+            merged_receive: false,
+            cloud: false,
+            timestamp_unit: 1,
+            backend: Self::zlgcan(),
+        }
+    }
+
+    const fn canfd(can_channels: u8) -> Self {
+        Self {
+            can_channels,
+            lin_channels: 0,
+            canfd: true,
+            clock: Some(60_000_000),
+            bitrates: CANFD_BITRATES,
+            data_bitrates: CANFD_DATA_BITRATES,
+            resistance: true,
+            filter_record: false,
+            auto_send: false,
+            bus_usage: true,
+            merged_receive: false,
+            cloud: false,
+            timestamp_unit: 1,
+            backend: Self::zlgcan(),
+        }
+    }
+
+    /// The devices are served by `zlgcan.dll` on windows, and not supported on linux by default.
+    const fn zlgcan() -> DeviceBackend {
+        if cfg!(target_os = "windows") {
+            DeviceBackend::Library("zlgcan.dll")
+        }
+        else {
+            DeviceBackend::Unsupported
+        }
+    }
+
+    /// The devices are implemented in crate on linux, and served by `zlgcan.dll` on windows.
+    const fn builtin() -> DeviceBackend {
+        if cfg!(target_os = "linux") {
+            DeviceBackend::Builtin
+        }
+        else {
+            Self::zlgcan()
+        }
+    }
+
+    /// The library on linux, and `zlgcan.dll` on windows.
+    const fn library(self, linux: &'static str) -> Self {
+        let backend = if cfg!(target_os = "linux") { DeviceBackend::Library(linux) } else { self.backend };
+        Self { backend, ..self }
+    }
+
+    const fn unsupported() -> Self {
+        Self {
+            can_channels: 0,
+            lin_channels: 0,
+            canfd: false,
+            clock: None,
+            bitrates: &[],
+            data_bitrates: &[],
+            resistance: false,
+            filter_record: false,
+            auto_send: false,
+            bus_usage: false,
+            merged_receive: false,
+            cloud: false,
+            timestamp_unit: 1,
+            backend: DeviceBackend::Unsupported,
+        }
+    }
+}
+
+impl ZCanDeviceType {
+    /// Get the capabilities of device type.
+    pub const fn capabilities(&self) -> DeviceCapabilities {
+        use DeviceCapabilities as Cap;
+        match self {
+            Self::Undefined => Cap::unsupported(),
+            Self::ZCAN_USBCAN1 => Cap { resistance: false, timestamp_unit: 10, ..Cap::can(1).library("libusbcan.so") },
+            Self::ZCAN_USBCAN2 => Cap { resistance: false, timestamp_unit: 10, ..Cap::can(2).library("libusbcan.so") },
+            Self::ZCAN_USBCAN_E_U => Cap::can(1),
+            Self::ZCAN_USBCAN_2E_U => Cap { filter_record: true, auto_send: true, ..Cap::can(2) },
+            Self::ZCAN_USBCAN_4E_U => Cap { filter_record: true, auto_send: true, ..Cap::can(4).library("libusbcan-4e.so") },
+            Self::ZCAN_USBCAN_8E_U => Cap { auto_send: true, ..Cap::can(8).library("libusbcan-8e.so") },
+            Self::ZCAN_PCI5010U => Cap { filter_record: true, ..Cap::can(1) },
+            Self::ZCAN_PCI5020U => Cap { filter_record: true, ..Cap::can(2) },
+            Self::ZCAN_PCI9810 | Self::ZCAN_PCI5110 | Self::ZCAN_CAN232 | Self::ZCAN_CANLITE | Self::ZCAN_PC104CAN |
+            Self::ZCAN_CANETUDP | Self::ZCAN_CANETTCP | Self::ZCAN_DNP9810 | Self::ZCAN_EG20T_CAN | Self::ZCAN_PCIe9110 |
+            Self::ZCAN_CANDTU_100UR | Self::ZCAN_CANSCOPE | Self::ZCAN_CANREPLAY => Cap::can(1),
+            Self::ZCAN_PCI5121 | Self::ZCAN_PCI9820 | Self::ZCAN_ISA9620 | Self::ZCAN_ISA5420 | Self::ZCAN_PC104CAN2 |
+            Self::ZCAN_PCI9820I | Self::ZCAN_PCIE_9220 | Self::ZCAN_PCIE9221 | Self::ZCAN_WIFICAN_TCP | Self::ZCAN_WIFICAN_UDP |
+            Self::ZCAN_PCIe9120 | Self::ZCAN_CANDTU_200UR | Self::ZCAN_CANDTU_MINI | Self::ZCAN_CANDTU_NET | Self::ZCAN_CLOUD => Cap::can(2),
+            Self::ZCAN_PCI9840 | Self::ZCAN_PCIe9140 | Self::ZCAN_CANDTU_NET_400 => Cap::can(4),
+            Self::ZCAN_USBCANFD_MINI | Self::ZCAN_USBCANFD_100U => Cap { merged_receive: true, ..Cap::canfd(1).library("libusbcanfd.so") },
+            Self::ZCAN_USBCANFD_200U => Cap { lin_channels: 2, merged_receive: true, ..Cap::canfd(2).library("libusbcanfd.so") },
+            Self::ZCAN_USBCANFD_800U => Cap {
+                lin_channels: 2,
+                clock: Some(40_000_000),
+                merged_receive: true,
+                cloud: true,
+                ..Cap::canfd(8).library("libusbcanfd800u.so")
+            },
+            Self::ZCAN_PCIE_CANFD_100U | Self::ZCAN_PCIE_CANFD_100U_EX | Self::ZCAN_CANFDCOM_100IE |
+            Self::ZCAN_CANFDNET_100U_TCP | Self::ZCAN_CANFDNET_100U_UDP |
+            Self::ZCAN_CANFDWIFI_100U_TCP | Self::ZCAN_CANFDWIFI_100U_UDP => Cap::canfd(1),
+            Self::ZCAN_PCIE_CANFD_200U | Self::ZCAN_PCIE_CANFD_200U_MINI | Self::ZCAN_PCIE_CANFD_200U_M2 |
+            Self::ZCAN_CANFDNET_200U_TCP | Self::ZCAN_CANFDNET_200U_UDP |
+            Self::ZCAN_CANFDWIFI_200U_TCP | Self::ZCAN_CANFDWIFI_200U_UDP | Self::ZCAN_CANFDBLUE_200U => Cap::canfd(2),
+            Self::ZCAN_PCIE_CANFD_400U | Self::ZCAN_PCIE_CANFD_400U_EX |
+            Self::ZCAN_CANFDNET_400U_TCP | Self::ZCAN_CANFDNET_400U_UDP |
+            Self::ZCAN_CANFDDTU_400_TCP | Self::ZCAN_CANFDDTU_400_UDP => Cap::canfd(4),
+            Self::ZCAN_CANFDDTU_600EWGR_TCP | Self::ZCAN_CANFDDTU_600EWGR_UDP => Cap::canfd(6),
+            Self::ZCAN_CANFDNET_800U_TCP | Self::ZCAN_CANFDNET_800U_UDP |
+            Self::ZCAN_CANFDDTU_800ER_TCP | Self::ZCAN_CANFDDTU_800ER_UDP |
+            Self::ZCAN_CANFDDTU_800EWGR_TCP | Self::ZCAN_CANFDDTU_800EWGR_UDP => Cap::canfd(8),
+            // the channels is configurable by derive info, the default is 2.
+            Self::ZCAN_OFFLINE_DEVICE => Cap {
+                bus_usage: false,
+                backend: Cap::builtin(),
+                ..Cap::canfd(2)
+            },
+            Self::ZCAN_VIRTUAL_DEVICE => Cap {
+                bus_usage: false, backend: Cap::builtin(), ..Cap::canfd(2)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ZCanError;
+    use super::{DeviceBackend, ZCanDeviceType};
+
+    #[test]
+    fn test_try_from() {
+        for dev_type in ZCanDeviceType::ALL {
+            assert_eq!(ZCanDeviceType::try_from(*dev_type as u32).unwrap(), *dev_type);
+        }
+        for value in [30, 74, 97, 100, u32::MAX] {
+            assert!(matches!(ZCanDeviceType::try_from(value), Err(ZCanError::InvalidDeviceType)));
+        }
+    }
+
+    #[test]
+    fn test_capabilities() {
+        for dev_type in ZCanDeviceType::ALL {
+            let cap = dev_type.capabilities();
+            assert_eq!(cap.canfd, !cap.data_bitrates.is_empty(), "{:?}", dev_type);
+            if !matches!(dev_type, ZCanDeviceType::Undefined) {
+                assert!(cap.can_channels > 0, "{:?}", dev_type);
+                assert!(cap.clock.is_some() && !cap.bitrates.is_empty(), "{:?}", dev_type);
+            }
+        }
+
+        let cap = ZCanDeviceType::ZCAN_USBCANFD_800U.capabilities();
+        assert_eq!((cap.can_channels, cap.lin_channels, cap.clock), (8, 2, Some(40_000_000)));
+        assert!(cap.cloud && cap.canfd);
+        assert!(!ZCanDeviceType::ZCAN_USBCAN2.has_resistance());
+        assert!(ZCanDeviceType::ZCAN_USBCANFD_200U.lin_support());
+        assert_eq!(ZCanDeviceType::ZCAN_USBCAN2.capabilities().timestamp_unit, 10);
+        assert_eq!(ZCanDeviceType::ZCAN_USBCANFD_200U.capabilities().timestamp_unit, 1);
+        assert!(!ZCanDeviceType::ZCAN_USBCANFD_100U.lin_support());
+        // only the USBCAN-2E-U, USBCAN-4E-U and USBCAN-8E-U support auto send
+        let auto_send = ZCanDeviceType::ALL.iter()
+            .filter(|v| v.auto_send_support())
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(auto_send, [ZCanDeviceType::ZCAN_USBCAN_2E_U, ZCanDeviceType::ZCAN_USBCAN_4E_U, ZCanDeviceType::ZCAN_USBCAN_8E_U]);
+        assert!(ZCanDeviceType::ZCAN_USBCANFD_200U.capabilities().merged_receive);
+        assert!(!ZCanDeviceType::ZCAN_PCIE_CANFD_400U.capabilities().merged_receive);
+        #[cfg(target_os = "linux")]
+        assert_eq!(ZCanDeviceType::ZCAN_VIRTUAL_DEVICE.capabilities().backend, DeviceBackend::Builtin);
+        // the windows driver loads `zlgcan.dll` for all devices
+        #[cfg(target_os = "windows")]
+        assert_eq!(ZCanDeviceType::ZCAN_OFFLINE_DEVICE.capabilities().backend, DeviceBackend::Library("zlgcan.dll"));
+        #[cfg(target_os = "linux")]
+        assert_eq!(ZCanDeviceType::ZCAN_USBCAN_4E_U.capabilities().backend, DeviceBackend::Library("libusbcan-4e.so"));
+    }
+}
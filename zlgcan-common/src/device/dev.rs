@@ -1,12 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{c_uchar, c_ushort, CString};
 use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use crate::can::{CanMessage, TimestampTracker};
+use crate::data::DataObject;
 use crate::device::{DeriveInfo, ZCanDeviceType};
 use crate::error::ZCanError;
 use crate::utils::system_timestamp;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(into = "RawDeviceInfo", try_from = "RawDeviceInfo"))]
 pub struct ZDeviceInfo {
     hwv: c_ushort,          //**< hardware version */
     fwv: c_ushort,          //**< firmware version */
@@ -122,6 +126,66 @@ impl Display for ZDeviceInfo {
     }
 }
 
+/// The serialized form of `ZDeviceInfo`, the versions are raw values.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDeviceInfo {
+    hardware_version: u16,
+    firmware_version: u16,
+    driver_version: u16,
+    api_version: u16,
+    irq: u16,
+    can_channels: u8,
+    sn: String,
+    id: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<ZDeviceInfo> for RawDeviceInfo {
+    fn from(value: ZDeviceInfo) -> Self {
+        Self {
+            hardware_version: value.hwv,
+            firmware_version: value.fwv,
+            driver_version: value.drv,
+            api_version: value.api,
+            irq: value.irq,
+            can_channels: value.chn,
+            sn: value.sn(),
+            id: value.id(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawDeviceInfo> for ZDeviceInfo {
+    type Error = String;
+
+    fn try_from(value: RawDeviceInfo) -> Result<Self, Self::Error> {
+        fn c_chars<const N: usize>(s: &str, name: &str) -> Result<[c_uchar; N], String> {
+            let mut result = [0; N];
+            // keep the last one as nul
+            if s.len() >= N {
+                return Err(format!("ZDeviceInfo - {}: `{}` is longer than {}", name, s, N - 1));
+            }
+            result[..s.len()].copy_from_slice(s.as_bytes());
+            Ok(result)
+        }
+
+        Ok(Self {
+            hwv: value.hardware_version,
+            fwv: value.firmware_version,
+            drv: value.driver_version,
+            api: value.api_version,
+            irq: value.irq,
+            chn: value.can_channels,
+            sn: c_chars(&value.sn, "sn")?,
+            id: c_chars(&value.id, "id")?,
+            pad: Default::default(),
+        })
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ZDeviceContext {
     pub(crate) dev_type: ZCanDeviceType,
@@ -152,18 +216,27 @@ impl ZDeviceContext {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct ZChannelContext {
     device: ZDeviceContext,
     channel: u8,
     chl_hdl: Option<u32>,
     timestamp: u64,
+    /// shared by the clones of context, so the rollover is tracked once per channel.
+    tracker: Arc<Mutex<TimestampTracker>>,
 }
 
 impl ZChannelContext {
     #[inline]
     pub fn new(device: ZDeviceContext, channel: u8, chl_hdl: Option<u32>) -> Self {
-        Self { device, channel, chl_hdl, timestamp: Default::default() }
+        let unit = device.dev_type.capabilities().timestamp_unit;
+        Self {
+            device,
+            channel,
+            chl_hdl,
+            timestamp: Default::default(),
+            tracker: Arc::new(Mutex::new(TimestampTracker::new(unit, Default::default()))),
+        }
     }
     #[inline]
     pub fn device_context(&self) -> &ZDeviceContext {
@@ -192,12 +265,44 @@ impl ZChannelContext {
     #[inline]
     pub fn set_channel_handler(&mut self, handler: Option<u32>) {
         self.timestamp = system_timestamp();
+        self.tracker.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .reset(self.timestamp);
         self.chl_hdl = handler;
     }
+    /// The system time(µs) when the channel is opened.
     #[inline]
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
+    /// Get the timestamp tracker of channel.
+    #[inline]
+    pub fn timestamp_tracker(&self) -> TimestampTracker {
+        self.tracker.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+    /// Extend the hardware timestamp of received messages across rollover and convert it to absolute time.
+    #[inline]
+    pub fn update_timestamp(&self, messages: &mut [CanMessage]) {
+        self.tracker.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update(messages)
+    }
+    /// Same as `update_timestamp`, for the received data objects.
+    #[inline]
+    pub fn update_data_timestamp(&self, objects: &mut [DataObject]) {
+        self.tracker.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update_objects(objects)
+    }
+    /// Same as `update_data_timestamp`, the clock is sampled with the `latest` raw ticks of device at `host` time.
+    #[inline]
+    pub fn update_merged_timestamp(&self, objects: &mut [DataObject], latest: u32, host: u64) {
+        self.tracker.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update_merged_at(objects, latest, host)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -259,6 +364,42 @@ impl Handler {
     pub fn remove_lin(&mut self, channel: u8) {
         self.lins.remove(&channel);
     }
+    /// Update the timestamp of data objects received from all channels by the channel of each object,
+    /// the order of objects is kept.
+    ///
+    /// The CAN channels share the clock of device, so they are synchronized with the latest CAN frame of all channels.
+    pub fn update_data_timestamp(&self, objects: &mut Vec<DataObject>) {
+        let host = system_timestamp();
+        // the frames may be out of order, the rollover is ignored because it only shifts the sample by a few ticks
+        let latest = objects.iter()
+            .filter_map(|v| match v {
+                DataObject::Can(v) | DataObject::Error { frame: v, .. } => v.hardware_timestamp(),
+                _ => None,
+            })
+            .max();
+        let mut groups: BTreeMap<(bool, u8), Vec<(usize, DataObject)>> = BTreeMap::new();
+        for (index, object) in objects.drain(..).enumerate() {
+            let lin = matches!(object, DataObject::Lin(_) | DataObject::LinError { .. });
+            groups.entry((lin, object.channel()))
+                .or_default()
+                .push((index, object));
+        }
+
+        let mut results = Vec::new();
+        for ((lin, channel), group) in groups {
+            let (indexes, mut group): (Vec<_>, Vec<_>) = group.into_iter().unzip();
+            let context = if lin { self.find_lin(channel) } else { self.find_can(channel) };
+            match (context, latest) {
+                (Some(context), Some(latest)) if !lin => context.update_merged_timestamp(&mut group, latest, host),
+                (Some(context), _) => context.update_data_timestamp(&mut group),
+                (None, _) => {},
+            }
+            results.extend(indexes.into_iter().zip(group));
+        }
+
+        results.sort_by_key(|(index, _)| *index);
+        objects.extend(results.into_iter().map(|(_, v)| v));
+    }
 }
 
 /// use for batch setting parameters for device.
@@ -335,4 +476,23 @@ mod tests {
         assert_eq!(dev_info.driver_version(), "V10.01");
         assert_eq!(dev_info.api_version(), "V2.37");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn device_info_serde() -> anyhow::Result<()> {
+        let mut dev_info = ZDeviceInfo::try_from(&DeriveInfo { canfd: true, channels: 2 })?;
+        dev_info.hwv = 0x0101;
+        dev_info.sn[..4].copy_from_slice(b"SN01");
+        let s = serde_yaml::to_string(&dev_info)?;
+        assert!(s.contains("hardware_version: 257\n") && s.contains("sn: SN01\n"), "{}", s);
+
+        let other: ZDeviceInfo = serde_yaml::from_str(&s)?;
+        assert_eq!((other.hardware_version(), other.sn(), other.id()), ("V1.01".to_string(), "SN01".to_string(), dev_info.id()));
+        assert_eq!(other.can_channels(), 2);
+        assert!(other.canfd());
+
+        let s = s.replace("SN01", "SN0123456789012345678");
+        assert!(serde_yaml::from_str::<ZDeviceInfo>(&s).is_err());
+        Ok(())
+    }
 }
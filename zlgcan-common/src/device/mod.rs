@@ -1,7 +1,9 @@
+mod capability;
 mod dev;
 mod property;
 mod typedef;
 
+pub use capability::*;
 pub use dev::*;
 pub use property::*;
 pub use typedef::*;
@@ -20,6 +22,14 @@ impl DeriveInfo {
     pub fn new(canfd: bool, channels: u8) -> Self {
         Self { canfd, channels }
     }
+    #[inline]
+    pub fn canfd(&self) -> bool {
+        self.canfd
+    }
+    #[inline]
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
 }
 
 
@@ -3,7 +3,7 @@ use crate::error::ZCanError;
 
 #[allow(non_camel_case_types, dead_code)]
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum ZCanDeviceType {
     Undefined                          = 0,
     ZCAN_PCI5121                       = 1,
@@ -89,63 +89,55 @@ pub enum ZCanDeviceType {
 }
 
 impl ZCanDeviceType {
+    /// All the device types.
+    pub const ALL: &'static [Self] = &[
+        Self::Undefined, Self::ZCAN_PCI5121, Self::ZCAN_PCI9810, Self::ZCAN_USBCAN1, Self::ZCAN_USBCAN2,
+        Self::ZCAN_PCI9820, Self::ZCAN_CAN232, Self::ZCAN_PCI5110, Self::ZCAN_CANLITE, Self::ZCAN_ISA9620,
+        Self::ZCAN_ISA5420, Self::ZCAN_PC104CAN, Self::ZCAN_CANETUDP, Self::ZCAN_DNP9810, Self::ZCAN_PCI9840,
+        Self::ZCAN_PC104CAN2, Self::ZCAN_PCI9820I, Self::ZCAN_CANETTCP, Self::ZCAN_PCIE_9220, Self::ZCAN_PCI5010U,
+        Self::ZCAN_USBCAN_E_U, Self::ZCAN_USBCAN_2E_U, Self::ZCAN_PCI5020U, Self::ZCAN_EG20T_CAN, Self::ZCAN_PCIE9221,
+        Self::ZCAN_WIFICAN_TCP, Self::ZCAN_WIFICAN_UDP, Self::ZCAN_PCIe9120, Self::ZCAN_PCIe9110, Self::ZCAN_PCIe9140,
+        Self::ZCAN_USBCAN_4E_U, Self::ZCAN_CANDTU_200UR, Self::ZCAN_CANDTU_MINI, Self::ZCAN_USBCAN_8E_U,
+        Self::ZCAN_CANREPLAY, Self::ZCAN_CANDTU_NET, Self::ZCAN_CANDTU_100UR, Self::ZCAN_PCIE_CANFD_100U,
+        Self::ZCAN_PCIE_CANFD_200U, Self::ZCAN_PCIE_CANFD_400U, Self::ZCAN_USBCANFD_200U, Self::ZCAN_USBCANFD_100U,
+        Self::ZCAN_USBCANFD_MINI, Self::ZCAN_CANFDCOM_100IE, Self::ZCAN_CANSCOPE, Self::ZCAN_CLOUD,
+        Self::ZCAN_CANDTU_NET_400, Self::ZCAN_CANFDNET_200U_TCP, Self::ZCAN_CANFDNET_200U_UDP,
+        Self::ZCAN_CANFDWIFI_100U_TCP, Self::ZCAN_CANFDWIFI_100U_UDP, Self::ZCAN_CANFDNET_400U_TCP,
+        Self::ZCAN_CANFDNET_400U_UDP, Self::ZCAN_CANFDBLUE_200U, Self::ZCAN_CANFDNET_100U_TCP,
+        Self::ZCAN_CANFDNET_100U_UDP, Self::ZCAN_CANFDNET_800U_TCP, Self::ZCAN_CANFDNET_800U_UDP,
+        Self::ZCAN_USBCANFD_800U, Self::ZCAN_PCIE_CANFD_100U_EX, Self::ZCAN_PCIE_CANFD_400U_EX,
+        Self::ZCAN_PCIE_CANFD_200U_MINI, Self::ZCAN_PCIE_CANFD_200U_M2, Self::ZCAN_CANFDDTU_400_TCP,
+        Self::ZCAN_CANFDDTU_400_UDP, Self::ZCAN_CANFDWIFI_200U_TCP, Self::ZCAN_CANFDWIFI_200U_UDP,
+        Self::ZCAN_CANFDDTU_800ER_TCP, Self::ZCAN_CANFDDTU_800ER_UDP, Self::ZCAN_CANFDDTU_800EWGR_TCP,
+        Self::ZCAN_CANFDDTU_800EWGR_UDP, Self::ZCAN_CANFDDTU_600EWGR_TCP, Self::ZCAN_CANFDDTU_600EWGR_UDP,
+        Self::ZCAN_OFFLINE_DEVICE, Self::ZCAN_VIRTUAL_DEVICE,
+    ];
+
     /// Check the device can use fd frame
+    #[inline]
     pub fn canfd_support(&self) -> bool {
-        matches!(
-            self,
-            ZCanDeviceType::ZCAN_CANFDBLUE_200U |
-            ZCanDeviceType::ZCAN_CANFDCOM_100IE |
-            ZCanDeviceType::ZCAN_CANFDDTU_400_TCP | ZCanDeviceType::ZCAN_CANFDDTU_400_UDP |
-            ZCanDeviceType::ZCAN_CANFDDTU_600EWGR_TCP | ZCanDeviceType::ZCAN_CANFDDTU_600EWGR_UDP |
-            ZCanDeviceType::ZCAN_CANFDDTU_800ER_TCP | ZCanDeviceType::ZCAN_CANFDDTU_800ER_UDP |
-            ZCanDeviceType::ZCAN_CANFDDTU_800EWGR_TCP | ZCanDeviceType::ZCAN_CANFDDTU_800EWGR_UDP |
-            ZCanDeviceType::ZCAN_CANFDNET_100U_TCP | ZCanDeviceType::ZCAN_CANFDNET_100U_UDP |
-            ZCanDeviceType::ZCAN_CANFDNET_200U_TCP | ZCanDeviceType::ZCAN_CANFDNET_200U_UDP |
-            ZCanDeviceType::ZCAN_CANFDNET_400U_TCP | ZCanDeviceType::ZCAN_CANFDNET_400U_UDP |
-            ZCanDeviceType::ZCAN_CANFDNET_800U_TCP | ZCanDeviceType::ZCAN_CANFDNET_800U_UDP |
-            ZCanDeviceType::ZCAN_CANFDWIFI_100U_TCP | ZCanDeviceType::ZCAN_CANFDWIFI_100U_UDP |
-            ZCanDeviceType::ZCAN_CANFDWIFI_200U_TCP | ZCanDeviceType::ZCAN_CANFDWIFI_200U_UDP |
-            ZCanDeviceType::ZCAN_PCIE_CANFD_100U | ZCanDeviceType::ZCAN_PCIE_CANFD_100U_EX |
-            ZCanDeviceType::ZCAN_PCIE_CANFD_200U | ZCanDeviceType::ZCAN_PCIE_CANFD_200U_MINI | ZCanDeviceType::ZCAN_PCIE_CANFD_200U_M2 |
-            ZCanDeviceType::ZCAN_PCIE_CANFD_400U | ZCanDeviceType::ZCAN_PCIE_CANFD_400U_EX |
-            ZCanDeviceType::ZCAN_USBCANFD_MINI | ZCanDeviceType::ZCAN_USBCANFD_100U | ZCanDeviceType::ZCAN_USBCANFD_200U | ZCanDeviceType::ZCAN_USBCANFD_800U
-        )
+        self.capabilities().canfd
     }
     /// Check the device is supported LIN
+    #[inline]
     pub const fn lin_support(&self) -> bool{
-        matches!(
-            self,
-            ZCanDeviceType::ZCAN_USBCANFD_200U | ZCanDeviceType::ZCAN_USBCANFD_800U
-        )
+        self.capabilities().lin_channels > 0
     }
-
+    #[inline]
     pub const fn has_resistance(&self) -> bool {
-        !matches!{
-            self,
-            ZCanDeviceType::ZCAN_USBCAN1 | ZCanDeviceType::ZCAN_USBCAN2
-        }
+        self.capabilities().resistance
     }
-
+    #[inline]
     pub const fn cloud_support(&self) -> bool {
-        matches!(
-            self,
-            ZCanDeviceType::ZCAN_USBCANFD_800U
-        )
+        self.capabilities().cloud
     }
-
+    #[inline]
     pub const fn filter_record_support(&self) -> bool {
-        matches!(
-            self,
-            ZCanDeviceType::ZCAN_PCI5010U | ZCanDeviceType::ZCAN_PCI5020U |
-            ZCanDeviceType::ZCAN_USBCAN_2E_U | ZCanDeviceType::ZCAN_USBCAN_4E_U
-        )
+        self.capabilities().filter_record
     }
-
+    #[inline]
     pub const fn auto_send_support(&self) -> bool {
-        matches!(
-            self,
-            ZCanDeviceType::ZCAN_USBCAN_2E_U | ZCanDeviceType::ZCAN_USBCAN_4E_U | ZCanDeviceType::ZCAN_USBCAN_8E_U
-        )
+        self.capabilities().auto_send
     }
     /// set value then read and check the value if true
     /// TODO
@@ -161,23 +153,12 @@ impl From<ZCanDeviceType> for u32 {
 }
 
 impl TryFrom<u32> for ZCanDeviceType {
-    /// Attention!!!
-    /// This method is unsafe if the value is too large
     type Error = ZCanError;
     fn try_from(value: u32) -> Result<Self, Self::Error> {
-        let layout = std::alloc::Layout::new::<ZCanDeviceType>();
-        unsafe {
-            let vk: *mut ZCanDeviceType = std::alloc::alloc(layout).cast();
-            if vk.is_null() {
-                Err(ZCanError::ParamNotSupported)
-            }
-            else {
-                let ptr = vk as *mut u32;
-                *ptr = value;
-
-                Ok(*vk)
-            }
-        }
+        Self::ALL.iter()
+            .find(|v| **v as u32 == value)
+            .copied()
+            .ok_or(ZCanError::InvalidDeviceType)
     }
 }
 
@@ -6,12 +6,14 @@
 //! `can` module defined "CAN channel", "CAN frame" and "CAN constant" that include constants and enums.
 //! And for define a common frame, we define the file `frame.rs` and `utils.rs` for avoiding file to long.
 //! `cloud`module defined the struct for cloud device.
+//! `data` module defined the merged data object of CAN, CANFD, error and LIN frames.
 //! `device` module defined the struct for device.
 //! `lin` module defined the LIN struct.
 //! The `error.rs` defined the only error struct.
 //! The `util.rs` defined utility functions.
 pub mod can;
 pub mod cloud;
+pub mod data;
 pub mod device;
 pub mod error;
 pub mod lin;
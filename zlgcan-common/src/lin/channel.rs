@@ -5,6 +5,7 @@ use super::constant::{ZLinCheckSumMode, ZLinMode};
 #[allow(non_snake_case)]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(try_from = "RawLinChlCfg"))]
 pub struct ZLinChlCfg {
     linMode: c_uchar,   // 是否作为主机，0-从机，1-主机
     chkSumMode: c_uchar,// 校验方式，1-经典校验 2-增强校验 3-自动(对应eZLINChkSumMode的模式)
@@ -57,4 +58,56 @@ impl ZLinChlCfg {
     }
 }
 
+/// The serialized form of `ZLinChlCfg`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawLinChlCfg {
+    mode: ZLinMode,
+    checksum: ZLinCheckSumMode,
+    bitrate: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_len: Option<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawLinChlCfg> for ZLinChlCfg {
+    type Error = ZCanError;
+
+    fn try_from(value: RawLinChlCfg) -> Result<Self, Self::Error> {
+        Self::new(value.mode as u8, value.checksum as u8, value.bitrate, value.max_len)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ZLinChlCfg {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        RawLinChlCfg {
+            mode: ZLinMode::try_from(self.linMode).map_err(S::Error::custom)?,
+            checksum: ZLinCheckSumMode::try_from(self.chkSumMode).map_err(S::Error::custom)?,
+            bitrate: self.linBaud,
+            max_len: (self.maxLength > 0).then_some(self.maxLength),
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::lin::{ZLinCheckSumMode, ZLinMode};
+    use super::ZLinChlCfg;
 
+    #[test]
+    fn test_serde() -> anyhow::Result<()> {
+        let cfg = ZLinChlCfg::new(ZLinMode::Master as u8, ZLinCheckSumMode::Classic as u8, 19200, Some(8))?;
+        let s = serde_yaml::to_string(&cfg)?;
+        assert_eq!(s, "mode: master\nchecksum: classic\nbitrate: 19200\nmax_len: 8\n");
+        let other: ZLinChlCfg = serde_yaml::from_str(&s)?;
+        assert_eq!(serde_yaml::to_string(&other)?, s);
+
+        let cfg: ZLinChlCfg = serde_yaml::from_str("{ mode: slave, checksum: auto, bitrate: 9600 }")?;
+        assert_eq!((cfg.linMode, cfg.chkSumMode, cfg.maxLength), (0, 3, 0));
+        assert!(serde_yaml::from_str::<ZLinChlCfg>("{ mode: slave, checksum: auto, bitrate: 9600, max_len: 70 }").is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use crate::error::ZCanError;
 
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum ZLinMode {
     Slave = 0,
     Master = 1,
@@ -52,6 +54,8 @@ impl TryFrom<u8> for ZLinEventType {
     }
 }
 
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum ZLinCheckSumMode {
     Classic = 1,
     Enhance = 2,
@@ -62,9 +66,9 @@ impl TryFrom<u8> for ZLinCheckSumMode {
     type Error = ZCanError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            0 => Ok(ZLinCheckSumMode::Classic),
-            1 => Ok(ZLinCheckSumMode::Enhance),
-            2 => Ok(ZLinCheckSumMode::Auto),
+            1 => Ok(ZLinCheckSumMode::Classic),
+            2 => Ok(ZLinCheckSumMode::Enhance),
+            3 => Ok(ZLinCheckSumMode::Auto),
             _ => Err(ZCanError::ParamNotSupported),
         }
     }
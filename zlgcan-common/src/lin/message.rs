@@ -0,0 +1,146 @@
+use isotp_rs::can::frame::Direct;
+use crate::utils::system_timestamp;
+
+/// The max data length of LIN frame.
+pub const LIN_FRAME_MAX_SIZE: usize = 8;
+
+/// Calculate the protected ID of the frame ID(0~63).
+pub const fn protected_id(id: u8) -> u8 {
+    let id = id & 0x3F;
+    let p0 = (id ^ (id >> 1) ^ (id >> 2) ^ (id >> 4)) & 0x01;
+    let p1 = !((id >> 1) ^ (id >> 3) ^ (id >> 4) ^ (id >> 5)) & 0x01;
+    id | (p0 << 6) | (p1 << 7)
+}
+
+/// The LIN message, the timestamp is the host time(µs) since UNIX epoch.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LinMessage {
+    timestamp: u64,
+    hardware_timestamp: Option<u64>,
+    channel: u8,
+    pid: u8,
+    length: usize,
+    data: [u8; LIN_FRAME_MAX_SIZE],
+    direct: Direct,
+    checksum: u8,
+}
+
+impl LinMessage {
+    /// Create a LIN message with the protected ID(or the frame ID) and data, return `None` if the data is too long.
+    pub fn new(pid: u8, data: &[u8]) -> Option<Self> {
+        let length = data.len();
+        if length > LIN_FRAME_MAX_SIZE {
+            return None;
+        }
+
+        let mut buffer = [0; LIN_FRAME_MAX_SIZE];
+        buffer[..length].copy_from_slice(data);
+        Some(Self {
+            timestamp: system_timestamp(),
+            hardware_timestamp: Default::default(),
+            channel: Default::default(),
+            pid,
+            length,
+            data: buffer,
+            direct: Direct::Transmit,
+            checksum: Default::default(),
+        })
+    }
+
+    #[inline]
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn set_timestamp(&mut self, value: Option<u64>) -> &mut Self {
+        self.timestamp = value.unwrap_or_else(system_timestamp);
+        self
+    }
+
+    /// The device timestamp(µs) of the received frame.
+    #[inline]
+    pub fn hardware_timestamp(&self) -> Option<u64> {
+        self.hardware_timestamp
+    }
+
+    #[inline]
+    pub fn set_hardware_timestamp(&mut self, value: Option<u64>) -> &mut Self {
+        self.hardware_timestamp = value;
+        self
+    }
+
+    #[inline]
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    #[inline]
+    pub fn set_channel(&mut self, value: u8) -> &mut Self {
+        self.channel = value;
+        self
+    }
+
+    /// The protected ID, the bit 6 and 7 are parity bits.
+    #[inline]
+    pub fn pid(&self) -> u8 {
+        self.pid
+    }
+
+    /// The frame ID without parity bits.
+    #[inline]
+    pub fn id(&self) -> u8 {
+        self.pid & 0x3F
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.length]
+    }
+
+    #[inline]
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    #[inline]
+    pub fn direct(&self) -> Direct {
+        self.direct
+    }
+
+    #[inline]
+    pub fn set_direct(&mut self, value: Direct) -> &mut Self {
+        self.direct = value;
+        self
+    }
+
+    /// The checksum of frame, some devices are not supported to get it.
+    #[inline]
+    pub fn checksum(&self) -> u8 {
+        self.checksum
+    }
+
+    #[inline]
+    pub fn set_checksum(&mut self, value: u8) -> &mut Self {
+        self.checksum = value;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{protected_id, LinMessage};
+
+    #[test]
+    fn test_protected_id() {
+        assert_eq!(protected_id(0x00), 0x80);
+        assert_eq!(protected_id(0x01), 0xC1);
+        assert_eq!(protected_id(0x3C), 0x3C);
+        assert_eq!(protected_id(0x3D), 0x7D);
+        assert_eq!(protected_id(0x7D), 0x7D);
+
+        let message = LinMessage::new(protected_id(0x3D), &[0x01, 0x02]).unwrap();
+        assert_eq!((message.pid(), message.id(), message.length()), (0x7D, 0x3D, 2));
+        assert!(LinMessage::new(0x3D, &[0; 9]).is_none());
+    }
+}
@@ -6,4 +6,4 @@ mod message;
 pub use channel::*;
 pub use constant::*;
 pub use frame::*;
-// pub use message::*;
\ No newline at end of file
+pub use message::*;
\ No newline at end of file
@@ -1,6 +1,5 @@
 use std::ffi::{c_char, CStr};
 use std::time::{SystemTime, UNIX_EPOCH};
-use isotp_rs::can::DEFAULT_PADDING;
 use crate::error::ZCanError;
 
 #[inline]
@@ -16,11 +15,12 @@ pub fn c_str_to_string(src: *const c_char) -> Result<String, ZCanError> {
     }
 }
 
+/// The system time in microseconds since UNIX epoch.
 #[inline]
 pub fn system_timestamp() -> u64 {
     match SystemTime::now()
         .duration_since(UNIX_EPOCH) {
-        Ok(v) => v.as_millis() as u64,
+        Ok(v) => v.as_micros() as u64,
         Err(e) => {
             log::warn!("ZLGCAN - SystemTimeError: {0} when conversion failed!", e);
             0
@@ -28,18 +28,41 @@ pub fn system_timestamp() -> u64 {
     }
 }
 
+/// Add the device timestamp(µs) of frame to the system time(µs) when channel opened.
 #[inline]
 pub fn fix_system_time(frame_timestamp: u64, fix_timestamp: u64) -> u64 {
     frame_timestamp + fix_timestamp
 }
 
+/// The elapsed time(µs) since the system time(µs) when channel opened.
 #[inline]
 pub fn fix_device_time(fix_timestamp: u64) -> u64 {
     system_timestamp() - fix_timestamp
 }
 
+/// Copy `len` bytes of data into an array, the rest is filled with `padding`.
 #[inline]
-pub(crate) fn data_resize(data: &mut Vec<u8>, size: usize) {
-    data.resize(size, DEFAULT_PADDING);
+pub(crate) fn data_padded<const N: usize>(data: &[u8], len: usize, padding: u8) -> [u8; N] {
+    let mut result = [padding; N];
+    let len = len.min(data.len()).min(N);
+    result[..len].copy_from_slice(&data[..len]);
+    result
 }
 
+
+/// Encode the data to hex string, e.g. `[0x02, 0x10, 0x01]` => `"021001"`.
+#[cfg(feature = "serde")]
+pub(crate) fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|v| format!("{:02X}", v)).collect()
+}
+
+/// Decode the hex string, the whitespaces between bytes are ignored.
+pub fn hex_decode(s: &str) -> Result<Vec<u8>, String> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if s.len() & 1 != 0 || !s.is_ascii() {
+        return Err(format!("invalid hex string: `{}`", s));
+    }
+    (0..s.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| format!("invalid hex string: `{}`", s)))
+        .collect()
+}
@@ -14,7 +14,7 @@ struct DeriveInfo {
 };
 
 struct CanMessage {
-    uint64_t timestamp;
+    uint64_t timestamp;     // microseconds since UNIX epoch
     uint32_t arbitration_id;
     bool is_extended_id;
     bool is_remote_frame;
@@ -174,6 +174,7 @@ impl ZCanApi for USBCANApi<'_> {
         else {
             log::debug!("ZLGCAN - receive CAN frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 
@@ -52,7 +52,7 @@ impl USBCANEApi<'_> {
         &self,
         dev_hdl: &mut Handler,
         channels: u8,
-        cfg: &Vec<CanChlCfg>,
+        cfg: &[CanChlCfg],
     ) -> Result<(), ZCanError> {
         let p = self.self_get_property(dev_hdl.device_context())?;
         let set_value_func = p.SetValue;
@@ -64,19 +64,9 @@ impl USBCANEApi<'_> {
                 break;
             }
 
-            if let Some(chl_hdl) = dev_hdl.find_can(idx) {
-                self.reset_can_chl(chl_hdl).unwrap_or_else(|e| log::warn!("{}", e));
-                dev_hdl.remove_can(idx);
-            }
-
-            match self.start_channel(dev_hdl, idx, set_value_func, cfg) {
-                Ok(context) => {
-                    dev_hdl.add_can(idx, context);
-                },
-                Err(e) => {
-                    error = Some(e);
-                    break;
-                }
+            if let Err(e) = self.restart_channel(dev_hdl, idx, set_value_func, cfg) {
+                error = Some(e);
+                break;
             }
         }
         self.release_property(&p)?;
@@ -86,6 +76,36 @@ impl USBCANEApi<'_> {
             None => Ok(()),
         }
     }
+    /// Initialize a channel, the other channels are not touched.
+    pub(crate) fn init_can_channel_ex(
+        &self,
+        dev_hdl: &mut Handler,
+        channel: u8,
+        cfg: &CanChlCfg,
+    ) -> Result<(), ZCanError> {
+        let p = self.self_get_property(dev_hdl.device_context())?;
+        let result = self.restart_channel(dev_hdl, channel, p.SetValue, cfg);
+        self.release_property(&p)?;
+
+        result
+    }
+    #[inline]
+    fn restart_channel(
+        &self,
+        dev_hdl: &mut Handler,
+        channel: u8,
+        set_value_func: SetValueFunc,
+        cfg: &CanChlCfg
+    ) -> Result<(), ZCanError> {
+        if let Some(chl_hdl) = dev_hdl.find_can(channel) {
+            self.reset_can_chl(chl_hdl).unwrap_or_else(|e| log::warn!("{}", e));
+            dev_hdl.remove_can(channel);
+        }
+
+        let context = self.start_channel(dev_hdl, channel, set_value_func, cfg)?;
+        dev_hdl.add_can(channel, context);
+        Ok(())
+    }
     #[inline]
     fn start_channel(
         &self,
@@ -268,6 +288,7 @@ impl ZCanApi for USBCANEApi<'_> {
         else {
             log::debug!("ZLGCAN - receive CAN frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 
@@ -1,6 +1,7 @@
 use dlopen2::symbor::{Symbol, SymBorApi};
 use std::ffi::{c_uint, c_void, CString};
 use zlgcan_common::can::{CanChlCfg, Reference, ZCanChlErrorV2, ZCanFrameType, ZCanChlError, ZCanChlStatus, ZCanFrameV2, ZCanFdFrameV1, ZCanChlCfgV2};
+use zlgcan_common::data::ZCanDataObj;
 use zlgcan_common::device::{CmdPath, ZChannelContext, ZDeviceContext, ZDeviceInfo};
 use zlgcan_common::error::ZCanError;
 use zlgcan_common::lin::{ZLinChlCfg, ZLinFrame, ZLinPublish, ZLinSubscribe};
@@ -63,10 +64,10 @@ pub(crate) struct USBCANFDApi<'a> {
     /// UINT  VCI_SetLINPublish(U32 Type, U32 Card, U32 LinChn, PZCAN_LIN_PUBLISH_CFG pSend, U32 nPublishCount);
     VCI_SetLINPublish: Symbol<'a, unsafe extern "C" fn(dev_type: c_uint, dev_idx: c_uint, channel: c_uint, cfg: *const ZLinPublish, len: c_uint) -> c_uint>,
 
-    // EXTERN_C U32 VCI_TransmitData(unsigned Type, unsigned Card, unsigned Port, ZCANDataObj *pData, unsigned Count);
-    // VCI_TransmitData: Symbol<'a, unsafe extern "C" fn(dev_type: c_uint, dev_idx: c_uint, channel: c_uint, data: *const ZCANDataObj, len: c_uint) -> c_uint>,
-    // EXTERN_C U32 VCI_ReceiveData(unsigned Type, unsigned Card, unsigned Port, ZCANDataObj *pData, unsigned Count, unsigned Time);
-    // VCI_ReceiveData: Symbol<'a, unsafe extern "C" fn(dev_type: c_uint, dev_idx: c_uint, channel: c_uint, data: *mut ZCANDataObj, size: c_uint, timeout: c_uint) -> c_uint>,
+    /// EXTERN_C U32 VCI_TransmitData(unsigned Type, unsigned Card, unsigned Port, ZCANDataObj *pData, unsigned Count);
+    VCI_TransmitData: Symbol<'a, unsafe extern "C" fn(dev_type: c_uint, dev_idx: c_uint, channel: c_uint, data: *const ZCanDataObj, len: c_uint) -> c_uint>,
+    /// EXTERN_C U32 VCI_ReceiveData(unsigned Type, unsigned Card, unsigned Port, ZCANDataObj *pData, unsigned Count, unsigned Time);
+    VCI_ReceiveData: Symbol<'a, unsafe extern "C" fn(dev_type: c_uint, dev_idx: c_uint, channel: c_uint, data: *mut ZCanDataObj, size: c_uint, timeout: c_uint) -> c_uint>,
 
     // EXTERN_C U32 VCI_UDS_Request(unsigned Type, unsigned Card, const ZCAN_UDS_REQUEST *req, ZCAN_UDS_RESPONSE *resp, U8 *dataBuf, U32 dataBufSize);
     // VCI_UDS_Request: Symbol<'a, unsafe extern "C" fn(dev_type: c_uint, dev_idx: c_uint, req: *const ZCAN_UDS_REQUEST, resp: *mut ZCAN_UDS_RESPONSE, buff: *mut c_uchar, buff_size: c_uint) -> c_uint>,
@@ -78,6 +79,7 @@ impl USBCANFDApi<'_> {
     // const INVALID_DEVICE_HANDLE: u32 = 0;
     // const INVALID_CHANNEL_HANDLE: u32 = 0;
     const STATUS_OK: u32 = 1;
+    pub(crate) const CMD_SET_CHNL_RECV_MERGE: u32 = 0x32;    // 设置合并接收 0:不合并接收;1:合并接收
 }
 
 impl ZDeviceApi for USBCANFDApi<'_> {
@@ -240,6 +242,7 @@ impl ZCanApi for USBCANFDApi<'_> {
         else {
             log::debug!("ZLGCAN - receive CAN frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 
@@ -269,6 +272,7 @@ impl ZCanApi for USBCANFDApi<'_> {
         else {
             log::debug!("ZLGCAN - receive CAN-FD frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 
@@ -284,6 +288,35 @@ impl ZCanApi for USBCANFDApi<'_> {
         }
         Ok(ret)
     }
+
+    fn receive_data(&self, context: &ZChannelContext, size: u32, timeout: u32, resize: impl Fn(&mut Vec<ZCanDataObj>, usize)) -> Result<Vec<ZCanDataObj>, ZCanError> {
+        let (dev_type, dev_idx, channel) = (context.device_type(), context.device_index(), context.channel());
+        let mut objects = Vec::new();
+        resize(&mut objects, size as usize);
+
+        let ret = unsafe { (self.VCI_ReceiveData)(dev_type as u32, dev_idx, channel as u32, objects.as_mut_ptr(), size, timeout) };
+        if ret < size {
+            log::warn!("ZLGCAN - receive data object expect: {}, actual: {}!", size, ret);
+        }
+        else {
+            log::debug!("ZLGCAN - receive data object: {}", ret);
+        }
+        objects.truncate(ret as usize);
+        Ok(objects)
+    }
+
+    fn transmit_data(&self, context: &ZChannelContext, objects: Vec<ZCanDataObj>) -> Result<u32, ZCanError> {
+        let (dev_type, dev_idx, channel) = (context.device_type(), context.device_index(), context.channel());
+        let len = objects.len() as u32;
+        let ret = unsafe { (self.VCI_TransmitData)(dev_type as u32, dev_idx, channel as u32, objects.as_ptr(), len) };
+        if ret < len {
+            log::warn!("ZLGCAN - transmit data object expect: {}, actual: {}!", len, ret);
+        }
+        else {
+            log::debug!("ZLGCAN - transmit data object: {}", ret);
+        }
+        Ok(ret)
+    }
 }
 
 impl ZLinApi for USBCANFDApi<'_> {
@@ -331,6 +364,7 @@ impl ZLinApi for USBCANFDApi<'_> {
         else {
             log::debug!("ZLGCAN - receive LIN frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
     fn transmit_lin(&self, context: &ZChannelContext, frames: Vec<ZLinFrame>) -> Result<u32, ZCanError> {
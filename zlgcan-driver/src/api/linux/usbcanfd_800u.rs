@@ -2,6 +2,7 @@ use dlopen2::symbor::{Symbol, SymBorApi};
 use std::ffi::{c_char, c_uchar, c_uint, c_void, CString};
 
 use zlgcan_common::can::{CanChlCfg, ZCanChlCfgV1, ZCanChlError, ZCanChlErrorV2, ZCanChlStatus, ZCanFdFrameV2, ZCanFrameV3, ZCanFrameType};
+use zlgcan_common::data::ZCanDataObjV2;
 use zlgcan_common::device::{CmdPath, IProperty, ZCanDeviceType, ZChannelContext, ZDeviceContext, ZDeviceInfo};
 use zlgcan_common::error::ZCanError;
 use zlgcan_common::utils::c_str_to_string;
@@ -46,7 +47,7 @@ pub(crate) struct USBCANFD800UApi<'a> {
     /// UINT FUNC_CALL ZCAN_TransmitData(DEVICE_HANDLE device_handle, ZCANDataObj* pTransmit, UINT len);
     // ZCAN_TransmitData: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, data: *const ZCANDataObj, len: c_uint) -> c_uint>,
     /// UINT FUNC_CALL ZCAN_ReceiveData(DEVICE_HANDLE device_handle, ZCANDataObj* pReceive, UINT len, int wait_time DEF(-1));
-    // ZCAN_ReceiveData: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, frames: *mut ZCANDataObj, size: c_uint, timeout: c_uint) -> c_uint>,
+    ZCAN_ReceiveData: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, objects: *mut ZCanDataObjV2, size: c_uint, timeout: c_uint) -> c_uint>,
 
     /// UINT FUNC_CALL ZCAN_SetValue(DEVICE_HANDLE device_handle, const char* path, const void* value);
     // ZCAN_SetValue: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, path: *const c_char, value: *const c_void) -> c_uint>,
@@ -155,6 +156,22 @@ impl USBCANFD800UApi<'_> {
         }
     }
 
+    /// Receive the data objects of all channels by the device handler, the merged receive should be enabled.
+    pub(crate) fn receive_merged(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<ZCanDataObjV2>, ZCanError> {
+        let mut objects = Vec::new();
+        objects.resize_with(size as usize, ZCanDataObjV2::default);
+
+        let ret = unsafe { (self.ZCAN_ReceiveData)(context.device_handler()?, objects.as_mut_ptr(), size, timeout) };
+        if ret < size {
+            log::warn!("ZLGCAN - receive data object expect: {}, actual: {}!", size, ret);
+        }
+        else {
+            log::debug!("ZLGCAN - receive data object: {}", ret);
+        }
+        objects.truncate(ret as usize);
+        Ok(objects)
+    }
+
     #[inline]
     pub(crate) fn self_get_reference(
         &self,
@@ -359,6 +376,7 @@ impl ZCanApi for USBCANFD800UApi<'_> {
         else {
             log::debug!("ZLGCAN - receive CAN frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 
@@ -386,6 +404,7 @@ impl ZCanApi for USBCANFD800UApi<'_> {
         else {
             log::debug!("ZLGCAN - receive CAN-FD frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 
@@ -8,6 +8,7 @@ pub(crate) mod windows;
 use std::ffi::{c_char, c_void};
 use zlgcan_common::can::{CanChlCfg, ZCanChlError, ZCanChlStatus, ZCanFrameType};
 use zlgcan_common::cloud::{ZCloudGpsFrame, ZCloudServerInfo, ZCloudUserData};
+use zlgcan_common::data::ZCanDataObj;
 use zlgcan_common::device::{CmdPath, IProperty, ZChannelContext, ZDeviceContext, ZDeviceInfo};
 use zlgcan_common::error::ZCanError;
 use zlgcan_common::lin::{ZLinChlCfg, ZLinFrame, ZLinPublish, ZLinPublishEx, ZLinSubscribe};
@@ -67,6 +68,12 @@ pub trait ZCanApi {
     fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<Self::FdFrame>) -> Result<u32, ZCanError> {
         Err(ZCanError::MethodNotSupported)
     }
+    fn receive_data(&self, context: &ZChannelContext, size: u32, timeout: u32, resize: impl Fn(&mut Vec<ZCanDataObj>, usize)) -> Result<Vec<ZCanDataObj>, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn transmit_data(&self, context: &ZChannelContext, objects: Vec<ZCanDataObj>) -> Result<u32, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
 }
 
 #[allow(unused_variables, dead_code)]
@@ -3,12 +3,13 @@ use std::pin::Pin;
 use dlopen2::symbor::{Symbol, SymBorApi};
 use zlgcan_common::can::{CanChlCfg, ZCanChlError, ZCanChlErrorV2, ZCanChlStatus, ZCanChlType, ZCanFdFrameV2, ZCanFrameV3, ZCanFrameType, ZCanChlCfgV1};
 use zlgcan_common::cloud::{ZCloudGpsFrame, ZCloudServerInfo, ZCloudUserData};
+use zlgcan_common::data::ZCanDataObjV2;
 use zlgcan_common::device::{CmdPath, IProperty, ZCanDeviceType, ZCanError, ZChannelContext, ZDeviceContext, ZDeviceInfo};
 use zlgcan_common::lin::{ZLinChlCfg, ZLinFrame, ZLinPublish, ZLinPublishEx, ZLinSubscribe};
 use zlgcan_common::utils::c_str_to_string;
 
 use crate::api::{ZCanApi, ZCloudApi, ZDeviceApi, ZLinApi};
-use crate::constant::{STATUS_OFFLINE, STATUS_ONLINE, INTERNAL_RESISTANCE, PROTOCOL, CANFD_ABIT_BAUD_RATE, CANFD_DBIT_BAUD_RATE, BAUD_RATE, CLOCK};
+use crate::constant::{STATUS_OFFLINE, STATUS_ONLINE, INTERNAL_RESISTANCE, PROTOCOL, CANFD_ABIT_BAUD_RATE, CANFD_DBIT_BAUD_RATE, BAUD_RATE, CLOCK, SET_DEVICE_RECV_MERGE};
 
 #[allow(non_snake_case)]
 #[derive(Debug, Clone, SymBorApi)]
@@ -25,7 +26,7 @@ pub(crate) struct Api<'a> {
     /// UINT FUNC_CALL ZCAN_TransmitData(DEVICE_HANDLE device_handle, ZCANDataObj* pTransmit, UINT len);
     // ZCAN_TransmitData: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, data: *const ZCANDataObj, len: c_uint) -> c_uint>,
     /// UINT FUNC_CALL ZCAN_ReceiveData(DEVICE_HANDLE device_handle, ZCANDataObj* pReceive, UINT len, int wait_time DEF(-1));
-    // ZCAN_ReceiveData: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, data: *mut ZCANDataObj, timeout: c_uint) -> c_uint>,
+    ZCAN_ReceiveData: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, data: *mut ZCanDataObjV2, len: c_uint, timeout: c_uint) -> c_uint>,
 
     /// UINT FUNC_CALL ZCAN_SetValue(DEVICE_HANDLE device_handle, const char* path, const void* value);
     ZCAN_SetValue: Symbol<'a, unsafe extern "C" fn(dev_hdl: c_uint, path: *const c_char, value: *const c_void) -> c_uint>,
@@ -116,6 +117,30 @@ impl Api<'_> {
     const INVALID_DEVICE_HANDLE: u32 = 0;
     const INVALID_CHANNEL_HANDLE: u32 = 0;
     const STATUS_OK: u32 = 1;
+
+    /// Enable or disable the merged receive of device, the frames of all channels are received by `receive_merged`.
+    pub(crate) fn set_receive_merge(&self, context: &ZChannelContext, enable: bool) -> Result<(), ZCanError> {
+        let path = format!("{}/{}", 0, SET_DEVICE_RECV_MERGE);
+        let path = CmdPath::new_path(path.as_str());
+        let value = CString::new((enable as u32).to_string()).map_err(|e| ZCanError::CStringConvertFailed(e.to_string()))?;
+        self.set_value(context, &path, value.as_ptr() as *const c_void)
+    }
+
+    /// Receive the data objects of all channels by the device handler, the merged receive should be enabled.
+    pub(crate) fn receive_merged(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<ZCanDataObjV2>, ZCanError> {
+        let mut objects = Vec::new();
+        objects.resize_with(size as usize, ZCanDataObjV2::default);
+
+        let ret = unsafe { (self.ZCAN_ReceiveData)(context.device_handler()?, objects.as_mut_ptr(), size, timeout) };
+        if ret < size {
+            log::warn!("ZLGCAN - receive data object expect: {}, actual: {}!", size, ret);
+        }
+        else {
+            log::debug!("ZLGCAN - receive data object: {}", ret);
+        }
+        objects.truncate(ret as usize);
+        Ok(objects)
+    }
 }
 
 impl ZDeviceApi for Api<'_> {
@@ -494,6 +519,7 @@ impl ZLinApi for Api<'_> {
         else {
             log::debug!("ZLGCAN - receive LIN frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
     fn transmit_lin(&self, context: &ZChannelContext, frames: Vec<ZLinFrame>) -> Result<u32, ZCanError> {
@@ -595,6 +621,7 @@ impl ZCloudApi for Api<'_> {
         else {
             log::debug!("ZLGCAN - receive GPS frame: {}", ret);
         }
+        frames.truncate(ret as usize);
         Ok(frames)
     }
 }
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::Arc;
+use dlopen2::symbor::Container;
+use zlgcan_common::can::{CanChlCfg, CanMessage, ZCanChlError, ZCanChlStatus, ZCanFdFrameV1, ZCanFdFrameV2, ZCanFrameType, ZCanFrameV1, ZCanFrameV2, ZCanFrameV3};
+use zlgcan_common::data::{DataObject, ZCanDataObj};
+use zlgcan_common::device::{CmdPath, DeriveInfo, Handler, ZCanDeviceType, ZCanError, ZChannelContext, ZDeviceContext, ZDeviceInfo};
+use zlgcan_common::lin::{ZLinChlCfg, ZLinDataType, ZLinFrame, ZLinFrameDataUnion, ZLinPublish, ZLinSubscribe};
+use zlgcan_common::TryFromIterator;
+use crate::api::linux::usbcan::USBCANApi;
+use crate::api::linux::usbcan_e::USBCANEApi;
+use crate::api::linux::usbcanfd::USBCANFDApi;
+use crate::api::linux::usbcanfd_800u::USBCANFD800UApi;
+use crate::api::{ZCanApi, ZDeviceApi, ZLinApi};
+use crate::driver::backend::{init_can_chls_each, reinit_can_chl, BackendFactory, ZCanBackend};
+use crate::driver::library::{load_library, LibraryFamily};
+
+pub(super) fn register(backends: &mut HashMap<ZCanDeviceType, BackendFactory>) {
+    let usbcan: BackendFactory = Arc::new(|_, derive| {
+        Ok(Arc::new(USBCANBackend { api: load_library(LibraryFamily::USBCAN)?, derive: derive.cloned() }))
+    });
+    backends.insert(ZCanDeviceType::ZCAN_USBCAN1, Arc::clone(&usbcan));
+    backends.insert(ZCanDeviceType::ZCAN_USBCAN2, usbcan);
+
+    backends.insert(ZCanDeviceType::ZCAN_USBCAN_4E_U, Arc::new(|dev_type, _| {
+        Ok(Arc::new(USBCANEBackend { api: load_library(LibraryFamily::USBCAN_4E)?, dev_type }))
+    }));
+    backends.insert(ZCanDeviceType::ZCAN_USBCAN_8E_U, Arc::new(|dev_type, _| {
+        Ok(Arc::new(USBCANEBackend { api: load_library(LibraryFamily::USBCAN_8E)?, dev_type }))
+    }));
+
+    let usbcanfd: BackendFactory = Arc::new(|_, _| {
+        Ok(Arc::new(USBCANFDBackend { api: load_library(LibraryFamily::USBCANFD)? }))
+    });
+    backends.insert(ZCanDeviceType::ZCAN_USBCANFD_MINI, Arc::clone(&usbcanfd));
+    backends.insert(ZCanDeviceType::ZCAN_USBCANFD_100U, Arc::clone(&usbcanfd));
+    backends.insert(ZCanDeviceType::ZCAN_USBCANFD_200U, usbcanfd);
+
+    backends.insert(ZCanDeviceType::ZCAN_USBCANFD_800U, Arc::new(|_, _| {
+        Ok(Arc::new(USBCANFD800UBackend { api: load_library(LibraryFamily::USBCANFD_800U)? }))
+    }));
+}
+
+/// The backend of USBCAN1 and USBCAN2(`libusbcan.so`).
+pub(crate) struct USBCANBackend {
+    api: Arc<Container<USBCANApi<'static>>>,
+    derive: Option<DeriveInfo>,
+}
+
+impl ZCanBackend for USBCANBackend {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.open(context)
+    }
+
+    fn close(&self, context: &ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.close(context)
+    }
+
+    fn read_device_info(&self, context: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        match &self.derive {
+            Some(v) => ZDeviceInfo::try_from(v),
+            None => self.api.read_device_info(context),
+        }
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        self.api.init_can_chl(context, cfg)
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.reset_can_chl(context)
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        self.api.read_can_chl_status(context)
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        self.api.read_can_chl_error(context)
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.clear_can_buffer(context)
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        self.api.get_can_num(context, can_type)
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let results = self.api.receive_can(context, size, timeout, |frames, size| {
+            frames.resize_with(size, ZCanFrameV1::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let frames = Vec::try_from_iter(frames, context.timestamp())?;
+        self.api.transmit_can(context, frames)
+    }
+}
+
+/// The backend of USBCAN-4E-U(`libusbcan-4e.so`) and USBCAN-8E-U(`libusbcan-8e.so`).
+pub(crate) struct USBCANEBackend {
+    api: Arc<Container<USBCANEApi<'static>>>,
+    dev_type: ZCanDeviceType,
+}
+
+impl ZCanBackend for USBCANEBackend {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.open(context)
+    }
+
+    fn close(&self, context: &ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.close(context)
+    }
+
+    fn read_device_info(&self, context: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        self.api.read_device_info(context)
+    }
+
+    fn init_can_chls(&self, handler: &mut Handler, cfg: &[CanChlCfg]) -> Result<(), ZCanError> {
+        match self.dev_type {
+            ZCanDeviceType::ZCAN_USBCAN_4E_U => {
+                let channels = handler.device_info().can_channels();
+                self.api.init_can_chl_ex(handler, channels, cfg)
+            },
+            _ => init_can_chls_each(self, handler, cfg),
+        }
+    }
+
+    fn init_can_channel(&self, handler: &mut Handler, channel: u8, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        match self.dev_type {
+            ZCanDeviceType::ZCAN_USBCAN_4E_U => self.api.init_can_channel_ex(handler, channel, cfg),
+            _ => reinit_can_chl(self, handler, channel, cfg),
+        }
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        self.api.init_can_chl(context, cfg)
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.reset_can_chl(context)
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        self.api.read_can_chl_status(context)
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        self.api.read_can_chl_error(context)
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.clear_can_buffer(context)
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        self.api.get_can_num(context, can_type)
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let results = self.api.receive_can(context, size, timeout, |frames, size| {
+            frames.resize_with(size, ZCanFrameV3::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let frames = Vec::try_from_iter(frames, context.timestamp())?;
+        self.api.transmit_can(context, frames)
+    }
+}
+
+/// The backend of USBCANFD-MINI, USBCANFD-100U and USBCANFD-200U(`libusbcanfd.so`).
+pub(crate) struct USBCANFDBackend {
+    api: Arc<Container<USBCANFDApi<'static>>>,
+}
+
+impl ZCanBackend for USBCANFDBackend {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.open(context)
+    }
+
+    fn close(&self, context: &ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.close(context)
+    }
+
+    fn read_device_info(&self, context: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        self.api.read_device_info(context)
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        self.api.init_can_chl(context, cfg)
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.reset_can_chl(context)
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        self.api.read_can_chl_status(context)
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        self.api.read_can_chl_error(context)
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.clear_can_buffer(context)
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        self.api.get_can_num(context, can_type)
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let results = self.api.receive_can(context, size, timeout, |frames, size| {
+            frames.resize_with(size, ZCanFrameV2::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let frames = Vec::try_from_iter(frames, context.timestamp())?;
+        self.api.transmit_can(context, frames)
+    }
+
+    fn receive_canfd(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let results = self.api.receive_canfd(context, size, timeout, |frames, size| {
+            frames.resize_with(size, ZCanFdFrameV1::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let frames = Vec::try_from_iter(frames, context.timestamp())?;
+        self.api.transmit_canfd(context, frames)
+    }
+
+    fn receive_data(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<DataObject>, ZCanError> {
+        let results = self.api.receive_data(context, size, timeout, |objects, size| {
+            objects.resize_with(size, ZCanDataObj::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_data(&self, context: &ZChannelContext, objects: Vec<DataObject>) -> Result<u32, ZCanError> {
+        let objects = Vec::try_from_iter(objects, context.timestamp())?;
+        self.api.transmit_data(context, objects)
+    }
+
+    fn set_receive_merge(&self, context: &ZChannelContext, enable: bool) -> Result<(), ZCanError> {
+        let value = enable as u32;
+        let cmd_path = CmdPath::new_reference(USBCANFDApi::CMD_SET_CHNL_RECV_MERGE);
+        self.api.set_reference(context, &cmd_path, &value as *const u32 as *const c_void)
+    }
+
+    fn init_lin_chl(&self, context: &mut ZChannelContext, cfg: &ZLinChlCfg) -> Result<(), ZCanError> {
+        self.api.init_lin_chl(context, cfg)
+    }
+
+    fn reset_lin_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.reset_lin_chl(context)
+    }
+
+    fn clear_lin_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.clear_lin_buffer(context)
+    }
+
+    fn get_lin_num(&self, context: &ZChannelContext) -> Result<u32, ZCanError> {
+        self.api.get_lin_num(context)
+    }
+
+    fn receive_lin(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<ZLinFrame>, ZCanError> {
+        let channel = context.channel();
+        self.api.receive_lin(context, size, timeout, |frames, size| {
+            frames.resize_with(size, || ZLinFrame::new(channel, ZLinDataType::TypeData, ZLinFrameDataUnion::from_data(Default::default())))
+        })
+    }
+
+    fn transmit_lin(&self, context: &ZChannelContext, frames: Vec<ZLinFrame>) -> Result<u32, ZCanError> {
+        self.api.transmit_lin(context, frames)
+    }
+
+    fn set_lin_subscribe(&self, context: &ZChannelContext, cfg: Vec<ZLinSubscribe>) -> Result<(), ZCanError> {
+        self.api.set_lin_subscribe(context, cfg)
+    }
+
+    fn set_lin_publish(&self, context: &ZChannelContext, cfg: Vec<ZLinPublish>) -> Result<(), ZCanError> {
+        self.api.set_lin_publish(context, cfg)
+    }
+
+    fn wakeup_lin(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.wakeup_lin(context)
+    }
+
+    #[allow(deprecated)]
+    fn set_lin_slave_msg(&self, context: &ZChannelContext, msg: Vec<ZLinFrame>) -> Result<(), ZCanError> {
+        self.api.set_lin_slave_msg(context, msg)
+    }
+
+    #[allow(deprecated)]
+    fn clear_lin_slave_msg(&self, context: &ZChannelContext, pids: Vec<u8>) -> Result<(), ZCanError> {
+        self.api.clear_lin_slave_msg(context, pids)
+    }
+}
+
+/// The backend of USBCANFD-800U(`libusbcanfd800u.so`).
+pub(crate) struct USBCANFD800UBackend {
+    api: Arc<Container<USBCANFD800UApi<'static>>>,
+}
+
+impl ZCanBackend for USBCANFD800UBackend {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.open(context)
+    }
+
+    fn close(&self, context: &ZDeviceContext) -> Result<(), ZCanError> {
+        self.api.close(context)
+    }
+
+    fn read_device_info(&self, context: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        self.api.read_device_info(context)
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        self.api.init_can_chl_ex(context.device_type(), context.device_index(), context.channel(), cfg)?;
+        self.api.init_can_chl(context, cfg)
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.reset_can_chl(context)
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        self.api.read_can_chl_status(context)
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        self.api.read_can_chl_error(context)
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.api.clear_can_buffer(context)
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        self.api.get_can_num(context, can_type)
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let results = self.api.receive_can(context, size, timeout, |frames, size| {
+            frames.resize_with(size, ZCanFrameV3::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let frames = Vec::try_from_iter(frames, context.timestamp())?;
+        self.api.transmit_can(context, frames)
+    }
+
+    fn receive_canfd(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let results = self.api.receive_canfd(context, size, timeout, |frames, size| {
+            frames.resize_with(size, ZCanFdFrameV2::default);
+        })?;
+
+        Vec::try_from_iter(results, context.timestamp())
+    }
+
+    fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let frames = Vec::try_from_iter(frames, context.timestamp())?;
+        self.api.transmit_canfd(context, frames)
+    }
+
+    fn set_receive_merge(&self, context: &ZChannelContext, enable: bool) -> Result<(), ZCanError> {
+        let value = enable as u32;
+        let cmd_path = CmdPath::new_reference(USBCANFD800UApi::REF_SET_DATA_RECV_MERGE);
+        self.api.self_set_reference(
+            context.device_type(), context.device_index(), context.channel(),
+            cmd_path.get_reference(), &value as *const u32 as *const c_void
+        )
+    }
+
+    fn receive_merged(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<DataObject>, ZCanError> {
+        let results = self.api.receive_merged(context, size, timeout)?;
+        Vec::try_from_iter(results, context.timestamp())
+    }
+}
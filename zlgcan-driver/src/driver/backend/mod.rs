@@ -0,0 +1,217 @@
+//! The backend of `ZCanDriver`.
+//!
+//! A backend unifies the device, CAN and LIN API of a device family with `CanMessage` level frames.
+//! The backends are created by factories that registered by device type,
+//! so that a simulator or a network device can be used by `ZCanDriver` without editing the driver.
+#[cfg(target_os = "linux")]
+mod linux;
+mod offline_device;
+#[cfg(target_os = "linux")]
+mod socketcan;
+mod virtual_device;
+
+pub use offline_device::{clear_offline_config, set_offline_config, OfflineConfig, ReplaySpeed};
+#[cfg(target_os = "linux")]
+pub use socketcan::register_socketcan;
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use zlgcan_common::can::{CanChlCfg, CanMessage, ChannelError, ZCanChlError, ZCanChlStatus, ZCanFrameType};
+use zlgcan_common::data::DataObject;
+use zlgcan_common::device::{DeriveInfo, Handler, ZCanDeviceType, ZCanError, ZChannelContext, ZDeviceContext, ZDeviceInfo};
+use zlgcan_common::lin::{ZLinChlCfg, ZLinFrame, ZLinPublish, ZLinPublishEx, ZLinSubscribe};
+
+/// The factory to create a backend for the device type and derive information.
+pub type BackendFactory = Arc<dyn Fn(ZCanDeviceType, Option<&DeriveInfo>) -> Result<Arc<dyn ZCanBackend>, ZCanError> + Send + Sync>;
+
+#[allow(unused_variables)]
+pub trait ZCanBackend: Send + Sync {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError>;
+    fn close(&self, context: &ZDeviceContext) -> Result<(), ZCanError>;
+    fn read_device_info(&self, context: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError>;
+    fn is_online(&self, context: &ZDeviceContext) -> Result<bool, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// The received frames are stamped with host time by backend,
+    /// so the hardware timestamps are not mapped by the `TimestampTracker` of channel.
+    fn host_timestamp(&self) -> bool {
+        false
+    }
+    /// Initialize the CAN channels by configuration index.
+    /// The channel opened before will be reset and initialized again.
+    fn init_can_chls(&self, handler: &mut Handler, cfg: &[CanChlCfg]) -> Result<(), ZCanError> {
+        init_can_chls_each(self, handler, cfg)
+    }
+    /// Initialize a CAN channel, the other channels are not touched.
+    /// The channel opened before will be reset and initialized again.
+    fn init_can_channel(&self, handler: &mut Handler, channel: u8, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        reinit_can_chl(self, handler, channel, cfg)
+    }
+    fn init_can_chl(&self, context: &mut ZChannelContext, cfg: &CanChlCfg) -> Result<(), ZCanError>;
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError>;
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError>;
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError>;
+    /// Read and decode the channel error, the empty result means no error.
+    fn read_channel_error(&self, context: &ZChannelContext) -> Result<Vec<ChannelError>, ZCanError> {
+        let error = self.read_can_chl_error(context)?;
+        Ok(ChannelError::decode(context.device_type(), &error))
+    }
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError>;
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError>;
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError>;
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError>;
+    fn receive_canfd(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// Receive the CAN(FD), error and LIN frames by one call, the hardware timestamps are kept.
+    fn receive_data(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<DataObject>, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// Transmit the CAN(FD) and LIN frames by one call.
+    fn transmit_data(&self, context: &ZChannelContext, objects: Vec<DataObject>) -> Result<u32, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// Enable or disable the merged receive of device,
+    /// the `receive_merged` returns the frames of all channels when it's enabled.
+    fn set_receive_merge(&self, context: &ZChannelContext, enable: bool) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// Receive the frames of all channels by one call when the merged receive is enabled,
+    /// the `context` is any opened channel of device.
+    fn receive_merged(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<DataObject>, ZCanError> {
+        self.receive_data(context, size, timeout)
+    }
+    fn init_lin_chl(&self, context: &mut ZChannelContext, cfg: &ZLinChlCfg) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn reset_lin_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn clear_lin_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn get_lin_num(&self, context: &ZChannelContext) -> Result<u32, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn receive_lin(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<ZLinFrame>, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn transmit_lin(&self, context: &ZChannelContext, frames: Vec<ZLinFrame>) -> Result<u32, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn set_lin_subscribe(&self, context: &ZChannelContext, cfg: Vec<ZLinSubscribe>) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn set_lin_publish(&self, context: &ZChannelContext, cfg: Vec<ZLinPublish>) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn set_lin_publish_ext(&self, context: &ZChannelContext, cfg: Vec<ZLinPublishEx>) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    fn wakeup_lin(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    #[deprecated(since = "0.1.0", note = "This method is deprecated!")]
+    fn set_lin_slave_msg(&self, context: &ZChannelContext, msg: Vec<ZLinFrame>) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    #[deprecated(since = "0.1.0", note = "This method is deprecated!")]
+    fn clear_lin_slave_msg(&self, context: &ZChannelContext, pids: Vec<u8>) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+}
+
+/// Reset the CAN channel if it is opened, and initialize it with `ZCanBackend::init_can_chl`.
+pub fn reinit_can_chl<B: ZCanBackend + ?Sized>(backend: &B, handler: &mut Handler, channel: u8, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+    reinit_can_chl_with(
+        handler,
+        channel,
+        |context| backend.reset_can_chl(context),
+        |context| backend.init_can_chl(context, cfg),
+    )
+}
+
+/// Same as `reinit_can_chl`, the channel is reset by `reset` and initialized by `init`.
+pub(crate) fn reinit_can_chl_with<R, I>(handler: &mut Handler, channel: u8, reset: R, init: I) -> Result<(), ZCanError>
+    where
+        R: FnOnce(&ZChannelContext) -> Result<(), ZCanError>,
+        I: FnOnce(&mut ZChannelContext) -> Result<(), ZCanError> {
+    if let Some(context) = handler.find_can(channel) {
+        reset(context).unwrap_or_else(|e| log::warn!("{}", e));
+        handler.remove_can(channel);
+    }
+
+    let mut context = ZChannelContext::new(*handler.device_context(), channel, None);
+    init(&mut context)?;
+    handler.add_can(channel, context);
+    Ok(())
+}
+
+/// Initialize the CAN channels one by one with `ZCanBackend::init_can_channel`.
+pub fn init_can_chls_each<B: ZCanBackend + ?Sized>(backend: &B, handler: &mut Handler, cfg: &[CanChlCfg]) -> Result<(), ZCanError> {
+    let channels = handler.device_info().can_channels();
+    for (idx, cfg) in cfg.iter().enumerate() {
+        let idx = idx as u8;
+        if idx >= channels {
+            log::warn!("ZLGCAN - the length of CAN channel configuration is out of channels!");
+            break;
+        }
+
+        backend.init_can_channel(handler, idx, cfg)?;
+    }
+
+    Ok(())
+}
+
+fn registry() -> &'static RwLock<HashMap<ZCanDeviceType, BackendFactory>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<ZCanDeviceType, BackendFactory>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut backends = HashMap::new();
+        #[cfg(target_os = "linux")]
+        linux::register(&mut backends);
+        offline_device::register(&mut backends);
+        virtual_device::register(&mut backends);
+        RwLock::new(backends)
+    })
+}
+
+/// Register the backend factory for the device types.
+/// The factory registered before for the same device type will be replaced.
+pub fn register_backend<F>(dev_types: &[ZCanDeviceType], factory: F)
+    where
+        F: Fn(ZCanDeviceType, Option<&DeriveInfo>) -> Result<Arc<dyn ZCanBackend>, ZCanError> + Send + Sync + 'static {
+    let factory: BackendFactory = Arc::new(factory);
+    let mut backends = registry().write()
+        .unwrap_or_else(|e| e.into_inner());
+    for dev_type in dev_types {
+        backends.insert(*dev_type, Arc::clone(&factory));
+    }
+}
+
+/// Unregister the backend factory of the device type, return `true` if it was registered.
+pub fn unregister_backend(dev_type: ZCanDeviceType) -> bool {
+    registry().write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&dev_type)
+        .is_some()
+}
+
+/// Check the device type has a registered backend.
+pub fn backend_registered(dev_type: ZCanDeviceType) -> bool {
+    registry().read()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains_key(&dev_type)
+}
+
+/// Create the backend of the device type by the registered factory.
+pub(crate) fn new_backend(dev_type: ZCanDeviceType, derive: Option<&DeriveInfo>) -> Result<Arc<dyn ZCanBackend>, ZCanError> {
+    let factory = registry().read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&dev_type)
+        .cloned()
+        .ok_or(ZCanError::DeviceNotSupported)?;
+    factory(dev_type, derive)
+}
@@ -0,0 +1,579 @@
+//! The offline replay device(`ZCAN_OFFLINE_DEVICE`).
+//!
+//! The device replays a recorded trace file in candump log format, such as
+//! `(1700000000.123456) can0 123#1122334455667788`, CAN-FD frames are written as `123##<flags><data>`.
+//! The channel is the number at the end of interface name. Received frames keep the recorded timestamp.
+//!
+//! The trace is replayed from the time the device opened, with original timing,
+//! accelerated timing or as fast as possible. The transmitted frames are written into an output
+//! trace file in the same format. The receiving returns without waiting when the trace of channel is exhausted.
+//!
+//! The configuration is set by `set_offline_config` for the device index, or else is read from
+//! `ZCAN_OFFLINE_INPUT`, `ZCAN_OFFLINE_OUTPUT` and `ZCAN_OFFLINE_SPEED`(`max` or a speed factor)
+//! that defined in `zcan.env` or environment.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use isotp_rs::can::frame::{Direct, Frame};
+use isotp_rs::can::identifier::Id;
+use zlgcan_common::can::{CanChlCfg, CanMessage, ZCanChlError, ZCanChlErrorV1, ZCanChlStatus, ZCanFrameType};
+use zlgcan_common::device::{DeriveInfo, ZCanDeviceType, ZChannelContext, ZDeviceContext, ZDeviceInfo};
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::utils::{hex_decode, system_timestamp};
+use crate::driver::backend::{BackendFactory, ZCanBackend};
+
+const CAN_EFF_FLAG: u32 = 0x8000_0000;
+const CAN_ERR_FLAG: u32 = 0x2000_0000;
+const CAN_EFF_MASK: u32 = 0x1FFF_FFFF;
+const CANFD_BRS: u8 = 0x01;
+const CANFD_ESI: u8 = 0x02;
+
+/// The replay speed of offline device.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Replay with the recorded timing.
+    #[default]
+    Original,
+    /// Replay with the recorded timing that accelerated by the factor.
+    Accelerated(f64),
+    /// Replay all frames as fast as possible.
+    Max,
+}
+
+impl TryFrom<&str> for ReplaySpeed {
+    type Error = ZCanError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "max" => Ok(Self::Max),
+            "original" => Ok(Self::Original),
+            v => match v.parse::<f64>() {
+                Ok(v) if v > 0. => Ok(Self::Accelerated(v)),
+                _ => Err(ZCanError::ConfigurationError(format!("invalid replay speed: `{}`", value))),
+            },
+        }
+    }
+}
+
+/// The configuration of offline device.
+#[derive(Debug, Clone)]
+pub struct OfflineConfig {
+    input: PathBuf,
+    output: Option<PathBuf>,
+    speed: ReplaySpeed,
+}
+
+impl OfflineConfig {
+    pub fn new<P: Into<PathBuf>>(input: P, output: Option<P>, speed: ReplaySpeed) -> Self {
+        Self { input: input.into(), output: output.map(Into::into), speed }
+    }
+    #[inline]
+    pub fn input(&self) -> &Path {
+        &self.input
+    }
+    #[inline]
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+    #[inline]
+    pub fn speed(&self) -> ReplaySpeed {
+        self.speed
+    }
+
+    fn from_env() -> Result<Self, ZCanError> {
+        let _ = dotenvy::from_filename("zcan.env");
+        let input = std::env::var("ZCAN_OFFLINE_INPUT")
+            .map_err(|_| ZCanError::ConfigurationError("the trace file of offline device is not set".to_string()))?;
+        let output = std::env::var("ZCAN_OFFLINE_OUTPUT").ok();
+        let speed = match std::env::var("ZCAN_OFFLINE_SPEED") {
+            Ok(v) => ReplaySpeed::try_from(v.as_str())?,
+            Err(_) => Default::default(),
+        };
+        Ok(Self::new(input, output, speed))
+    }
+}
+
+/// The configurations of all offline devices, keyed by device index.
+static OFFLINE_CONFIGS: Mutex<BTreeMap<u32, OfflineConfig>> = Mutex::new(BTreeMap::new());
+
+/// Set the configuration of offline device index, it is used when the device opened.
+pub fn set_offline_config(dev_idx: u32, cfg: OfflineConfig) {
+    OFFLINE_CONFIGS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(dev_idx, cfg);
+}
+
+/// Clear the configuration of offline device index, the environment is used after.
+pub fn clear_offline_config(dev_idx: u32) {
+    OFFLINE_CONFIGS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&dev_idx);
+}
+
+/// Parse a line of candump log, return `None` if the line is empty.
+fn parse_line(line: &str) -> Result<Option<CanMessage>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut items = line.split_whitespace();
+    let (Some(timestamp), Some(interface), Some(frame)) = (items.next(), items.next(), items.next()) else {
+        return Err("missing items".to_string());
+    };
+
+    let timestamp = timestamp.strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+        .ok_or("invalid timestamp")?;
+    let (secs, micros) = timestamp.split_once('.')
+        .unwrap_or((timestamp, "0"));
+    let secs = secs.parse::<u64>()
+        .map_err(|_| "invalid timestamp")?;
+    let micros = format!("{:0<6}", micros).get(..6)
+        .and_then(|v| v.parse::<u64>().ok())
+        .ok_or("invalid timestamp")?;
+
+    let channel = interface.trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse::<u8>()
+        .unwrap_or_default();
+
+    let (id, payload) = frame.split_once('#')
+        .ok_or("invalid frame")?;
+    let raw = u32::from_str_radix(id, 16)
+        .map_err(|_| "invalid identifier")?;
+    let extended = id.len() > 3;
+    let error = extended && (raw & CAN_ERR_FLAG) > 0;
+    let id = Id::from_bits(raw & CAN_EFF_MASK, extended && !error);
+
+    let mut message = if let Some(payload) = payload.strip_prefix('#') {
+        let flags = payload.get(..1)
+            .and_then(|v| u8::from_str_radix(v, 16).ok())
+            .ok_or("invalid flags")?;
+        let data = hex_decode(&payload[1..].replace('.', ""))?;
+        let mut message = CanMessage::new(id, &data)
+            .ok_or("invalid data length")?;
+        message.set_can_fd(true)
+            .set_bitrate_switch(flags & CANFD_BRS > 0)
+            .set_esi(flags & CANFD_ESI > 0);
+        message
+    }
+    else if let Some(len) = payload.strip_prefix('R') {
+        let len = if len.is_empty() { 0 } else { len.parse::<usize>().map_err(|_| "invalid remote length")? };
+        CanMessage::new_remote(id, len)
+            .ok_or("invalid data length")?
+    }
+    else {
+        let data = hex_decode(&payload.replace('.', ""))?;
+        CanMessage::new(id, &data)
+            .ok_or("invalid data length")?
+    };
+
+    message.set_timestamp(Some(secs * 1_000_000 + micros))
+        .set_channel(channel)
+        .set_direct(Direct::Receive)
+        .set_error_frame(error);
+    Ok(Some(message))
+}
+
+/// Format the frame as a line of candump log.
+fn format_line(frame: &CanMessage) -> String {
+    let timestamp = frame.timestamp();
+    let raw = frame.id().as_raw();
+    let id = if frame.is_error_frame() {
+        format!("{:08X}", raw | CAN_ERR_FLAG)
+    }
+    else if frame.is_extended() {
+        format!("{:08X}", raw & !CAN_EFF_FLAG)
+    }
+    else {
+        format!("{:03X}", raw)
+    };
+    let data = frame.data().iter()
+        .take(frame.length())
+        .map(|v| format!("{:02X}", v))
+        .collect::<String>();
+    let payload = if frame.is_remote() {
+        match frame.length() {
+            0 => "R".to_string(),
+            v => format!("R{}", v),
+        }
+    }
+    else if frame.is_can_fd() {
+        let mut flags = 0;
+        if frame.is_bitrate_switch() { flags |= CANFD_BRS; }
+        if frame.is_esi() { flags |= CANFD_ESI; }
+        format!("#{:X}{}", flags, data)
+    }
+    else {
+        data
+    };
+
+    format!("({}.{:06}) can{} {}#{}", timestamp / 1_000_000, timestamp % 1_000_000, frame.channel(), id, payload)
+}
+
+#[derive(Debug, Default)]
+struct OfflineChannel {
+    opened: bool,
+    can: VecDeque<(u64, CanMessage)>,
+    canfd: VecDeque<(u64, CanMessage)>,
+}
+
+impl OfflineChannel {
+    #[inline]
+    fn queue(&mut self, can_type: ZCanFrameType) -> &mut VecDeque<(u64, CanMessage)> {
+        match can_type {
+            ZCanFrameType::CANFD => &mut self.canfd,
+            _ => &mut self.can,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ReplayClock {
+    start: Instant,
+    speed: ReplaySpeed,
+}
+
+impl ReplayClock {
+    /// The replayed offset in microseconds.
+    fn offset(&self) -> u64 {
+        let elapsed = self.start.elapsed().as_secs_f64() * 1_000_000.;
+        match self.speed {
+            ReplaySpeed::Original => elapsed as u64,
+            ReplaySpeed::Accelerated(v) => (elapsed * v) as u64,
+            ReplaySpeed::Max => u64::MAX,
+        }
+    }
+
+    /// The duration until the offset is replayed.
+    fn until(&self, offset: u64) -> Duration {
+        let offset = Duration::from_micros(offset);
+        let offset = match self.speed {
+            ReplaySpeed::Original => offset,
+            ReplaySpeed::Accelerated(v) => offset.div_f64(v),
+            ReplaySpeed::Max => Duration::ZERO,
+        };
+        offset.saturating_sub(self.start.elapsed())
+    }
+}
+
+#[derive(Debug)]
+struct OfflineState {
+    clock: ReplayClock,
+    channels: HashMap<u8, OfflineChannel>,
+    output: Option<BufWriter<File>>,
+}
+
+impl OfflineState {
+    fn new(cfg: &OfflineConfig) -> Result<Self, ZCanError> {
+        let input = cfg.input();
+        if let ReplaySpeed::Accelerated(v) = cfg.speed() {
+            if v.is_nan() || v <= 0. {
+                return Err(ZCanError::ConfigurationError(format!("invalid replay speed: `{}`", v)));
+            }
+        }
+        let file = File::open(input)
+            .map_err(|e| ZCanError::ConfigurationError(format!("open trace file `{}` failed: {}", input.display(), e)))?;
+
+        let mut start = None;
+        let mut channels: HashMap<u8, OfflineChannel> = HashMap::new();
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| ZCanError::ConfigurationError(format!("read trace file `{}` failed: {}", input.display(), e)))?;
+            let frame = parse_line(&line)
+                .map_err(|e| ZCanError::ConfigurationError(format!("{} at line {} of trace file `{}`", e, idx + 1, input.display())))?;
+            if let Some(frame) = frame {
+                let start = *start.get_or_insert(frame.timestamp());
+                let offset = frame.timestamp().saturating_sub(start);
+                let can_type = if frame.is_can_fd() { ZCanFrameType::CANFD } else { ZCanFrameType::CAN };
+                channels.entry(frame.channel())
+                    .or_default()
+                    .queue(can_type)
+                    .push_back((offset, frame));
+            }
+        }
+
+        let output = match cfg.output() {
+            Some(v) => Some(BufWriter::new(
+                File::create(v)
+                    .map_err(|e| ZCanError::ConfigurationError(format!("create trace file `{}` failed: {}", v.display(), e)))?
+            )),
+            None => None,
+        };
+        log::debug!("ZLGCAN - offline device replay trace file: {}", input.display());
+
+        Ok(Self {
+            clock: ReplayClock { start: Instant::now(), speed: cfg.speed() },
+            channels,
+            output,
+        })
+    }
+
+    #[inline]
+    fn channel(&mut self, channel: u8) -> Result<&mut OfflineChannel, ZCanError> {
+        match self.channels.get_mut(&channel) {
+            Some(v) if v.opened => Ok(v),
+            _ => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct OfflineBackend {
+    canfd: bool,
+    channels: Option<u8>,
+    state: Mutex<Option<OfflineState>>,
+}
+
+impl OfflineBackend {
+    pub(crate) const DEFAULT_CHANNELS: u8 = 2;
+
+    pub(crate) fn new(derive: Option<&DeriveInfo>) -> Self {
+        match derive {
+            Some(v) => Self { canfd: v.canfd(), channels: Some(v.channels()), state: Default::default() },
+            None => Self { canfd: true, channels: None, state: Default::default() },
+        }
+    }
+
+    #[inline]
+    fn state(&self) -> MutexGuard<'_, Option<OfflineState>> {
+        self.state.lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut OfflineState) -> Result<R, ZCanError>) -> Result<R, ZCanError> {
+        match self.state().as_mut() {
+            Some(v) => f(v),
+            None => Err(ZCanError::DeviceNotOpened),
+        }
+    }
+
+    fn channels(&self, state: &OfflineState) -> u8 {
+        match self.channels {
+            Some(v) => v,
+            None => state.channels.keys()
+                .map(|v| v.saturating_add(1))
+                .fold(Self::DEFAULT_CHANNELS, u8::max),
+        }
+    }
+
+    fn transmit(&self, context: &ZChannelContext, can_type: ZCanFrameType, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let channel = context.channel();
+        self.with_state(|state| {
+            state.channel(channel)?;
+            let len = frames.len() as u32;
+            if let Some(output) = state.output.as_mut() {
+                for mut frame in frames {
+                    frame.try_set_can_fd(matches!(can_type, ZCanFrameType::CANFD))?
+                        .set_channel(channel)
+                        .set_timestamp(Some(system_timestamp()));
+                    writeln!(output, "{}", format_line(&frame))
+                        .map_err(|e| ZCanError::Other(format!("write trace file failed: {}", e)))?;
+                }
+                output.flush()
+                    .map_err(|e| ZCanError::Other(format!("write trace file failed: {}", e)))?;
+            }
+            log::debug!("ZLGCAN - offline device transmit frame: {}", len);
+
+            Ok(len)
+        })
+    }
+
+    fn receive(&self, context: &ZChannelContext, can_type: ZCanFrameType, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let channel = context.channel();
+        let deadline = Instant::now().checked_add(Duration::from_millis(timeout as u64));
+        loop {
+            let (frames, wait) = self.with_state(|state| {
+                let offset = state.clock.offset();
+                let queue = state.channel(channel)?
+                    .queue(can_type);
+                let count = queue.iter()
+                    .take_while(|(v, _)| *v <= offset)
+                    .count()
+                    .min(size as usize);
+                if count > 0 || size == 0 {
+                    return Ok((Some(queue.drain(..count).map(|(_, v)| v).collect()), Duration::ZERO));
+                }
+                match queue.front().map(|(v, _)| *v) {
+                    Some(v) => Ok((None, state.clock.until(v))),
+                    // the trace is exhausted, no more frames will be received
+                    None => Ok((Some(Vec::new()), Duration::ZERO)),
+                }
+            })?;
+            if let Some(frames) = frames {
+                return Ok(frames);
+            }
+
+            let remain = match deadline {
+                Some(v) => v.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            if remain.is_zero() {
+                log::warn!("ZLGCAN - receive {} frame expect: {}, actual: 0!", can_type, size);
+                return Ok(Vec::new());
+            }
+            std::thread::sleep(wait.min(remain));
+        }
+    }
+}
+
+impl ZCanBackend for OfflineBackend {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        let cfg = OFFLINE_CONFIGS.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&context.device_index())
+            .cloned();
+        let cfg = match cfg {
+            Some(v) => v,
+            None => OfflineConfig::from_env()?,
+        };
+        *self.state() = Some(OfflineState::new(&cfg)?);
+        Ok(())
+    }
+
+    fn close(&self, _: &ZDeviceContext) -> Result<(), ZCanError> {
+        if let Some(mut state) = self.state().take() {
+            if let Some(output) = state.output.as_mut() {
+                output.flush()
+                    .map_err(|e| ZCanError::Other(format!("write trace file failed: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_device_info(&self, _: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        let channels = self.with_state(|state| Ok(self.channels(state)))?;
+        ZDeviceInfo::try_from(&DeriveInfo::new(self.canfd, channels))
+    }
+
+    fn is_online(&self, _: &ZDeviceContext) -> Result<bool, ZCanError> {
+        Ok(self.state().is_some())
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, _: &CanChlCfg) -> Result<(), ZCanError> {
+        let channel = context.channel();
+        self.with_state(|state| {
+            if channel >= self.channels(state) {
+                return Err(ZCanError::ParamNotSupported);
+            }
+            state.channels.entry(channel)
+                .or_default()
+                .opened = true;
+            Ok(())
+        })?;
+        context.set_channel_handler(None);
+        Ok(())
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.with_state(|state| {
+            state.channel(context.channel())?
+                .opened = false;
+            Ok(())
+        })
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        self.with_state(|state| {
+            state.channel(context.channel())?;
+            Ok(Default::default())
+        })
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        self.with_state(|state| {
+            state.channel(context.channel())?;
+            Ok(ZCanChlError::from(ZCanChlErrorV1::default()))
+        })
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.with_state(|state| {
+            let offset = state.clock.offset();
+            let chl = state.channel(context.channel())?;
+            chl.can.retain(|(v, _)| *v > offset);
+            chl.canfd.retain(|(v, _)| *v > offset);
+            Ok(())
+        })
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        self.with_state(|state| {
+            let offset = state.clock.offset();
+            let chl = state.channel(context.channel())?;
+            let count = |queue: &VecDeque<(u64, CanMessage)>| queue.iter()
+                .take_while(|(v, _)| *v <= offset)
+                .count();
+            let ret = match can_type {
+                ZCanFrameType::CAN => count(&chl.can),
+                ZCanFrameType::CANFD => count(&chl.canfd),
+                ZCanFrameType::ALL => count(&chl.can) + count(&chl.canfd),
+            };
+            log::debug!("ZLGCAN - get receive {} number: {}.", can_type, ret);
+            Ok(ret as u32)
+        })
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        self.receive(context, ZCanFrameType::CAN, size, timeout)
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        self.transmit(context, ZCanFrameType::CAN, frames)
+    }
+
+    fn receive_canfd(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        if !self.canfd {
+            return Err(ZCanError::MethodNotSupported);
+        }
+        self.receive(context, ZCanFrameType::CANFD, size, timeout)
+    }
+
+    fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        if !self.canfd {
+            return Err(ZCanError::MethodNotSupported);
+        }
+        self.transmit(context, ZCanFrameType::CANFD, frames)
+    }
+}
+
+pub(super) fn register(backends: &mut HashMap<ZCanDeviceType, BackendFactory>) {
+    backends.insert(ZCanDeviceType::ZCAN_OFFLINE_DEVICE, Arc::new(|_, derive| {
+        Ok(Arc::new(OfflineBackend::new(derive)))
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use isotp_rs::can::frame::Frame;
+    use super::{format_line, parse_line};
+
+    #[test]
+    fn candump_line() {
+        let frame = parse_line("(1700000000.123456) can1 12345678#0102").unwrap().unwrap();
+        assert_eq!(frame.timestamp(), 1_700_000_000_123_456);
+        assert_eq!(frame.channel(), 1);
+        assert!(frame.is_extended());
+        assert_eq!(frame.data(), [0x01, 0x02].as_slice());
+        assert_eq!(format_line(&frame), "(1700000000.123456) can1 12345678#0102");
+
+        let frame = parse_line("(0.5) vcan0 7DF##3112233445566778899").unwrap().unwrap();
+        assert_eq!(frame.timestamp(), 500_000);
+        assert!(frame.is_can_fd());
+        assert!(frame.is_bitrate_switch());
+        assert!(frame.is_esi());
+        // the data is padded to the valid length of CANFD frame
+        assert_eq!(frame.length(), 12);
+        assert_eq!(format_line(&frame), "(0.500000) can0 7DF##3112233445566778899AAAAAA");
+
+        let frame = parse_line("(1.000001) can0 123#R").unwrap().unwrap();
+        assert!(frame.is_remote());
+        assert_eq!(format_line(&frame), "(1.000001) can0 123#R");
+
+        assert!(parse_line("  ").unwrap().is_none());
+        assert!(parse_line("(1.0) can0 123#1").is_err());
+        assert!(parse_line("can0 123#11").is_err());
+    }
+}
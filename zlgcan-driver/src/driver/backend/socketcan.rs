@@ -0,0 +1,731 @@
+//! The SocketCAN backend on linux.
+//!
+//! Each channel of the device is bound to a network interface such as `can0` or `vcan0` by a raw CAN socket,
+//! the CAN-FD frames are enabled when the MTU of interface is `CANFD_MTU`.
+//! The bitrate of interface is configured by system(for example `ip link set can0 type can bitrate 500000`),
+//! so the channel configuration is ignored.
+//!
+//! The frame timestamp is the receive time of kernel, and the hardware timestamp is the microseconds of interface
+//! clock that truncated to 32-bit when it is supported.
+//! The error counters of channel status are read from `IFLA_CAN_BERR_COUNTER` of interface, or else from
+//! the error frames received, and `regStatus` is the `IFLA_CAN_STATE` of interface.
+//!
+//! `CAN_RAW_RECV_OWN_MSGS` of socket is enabled when the first frame with `ZCanTxMode::SelfReception`
+//! or `ZCanTxMode::SelfReceptionOnce` is transmitted, only the own frames transmitted with self reception
+//! are received with `Direct::Transmit`.
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_int, c_uint, c_void, CString};
+use std::io;
+use std::mem::{size_of, zeroed};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use isotp_rs::can::frame::{Direct, Frame};
+use isotp_rs::can::identifier::Id;
+use zlgcan_common::can::{CanChlCfg, CanMessage, ChannelError, ErrorCounters, ProtocolError, ZCanChlError, ZCanChlErrorV1, ZCanChlStatus, ZCanFrameType, ZCanTxMode, CANERR_FRAME_LENGTH};
+use zlgcan_common::device::{DeriveInfo, ZCanDeviceType, ZChannelContext, ZDeviceContext, ZDeviceInfo};
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::utils::system_timestamp;
+use crate::driver::backend::{register_backend, ZCanBackend};
+
+/// The max time to wait the transmit queue of interface.
+const TX_TIMEOUT: Duration = Duration::from_millis(100);
+const NLA_TYPE_MASK: u16 = 0x3FFF;
+
+/// Register the SocketCAN backend for the device types, the channel N of device is bound to `interfaces[N]`.
+pub fn register_socketcan<S: AsRef<str>>(dev_types: &[ZCanDeviceType], interfaces: &[S]) {
+    let interfaces = interfaces.iter()
+        .map(|v| v.as_ref().to_owned())
+        .collect::<Vec<_>>();
+    register_backend(dev_types, move |_, derive| {
+        Ok(Arc::new(SocketCanBackend::new(interfaces.clone(), derive)))
+    });
+}
+
+#[inline]
+fn interface_index(interface: &str) -> Result<c_uint, ZCanError> {
+    let name = CString::new(interface)
+        .map_err(|e| ZCanError::CStringConvertFailed(e.to_string()))?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(ZCanError::ConfigurationError(format!("the interface `{}` is not existed", interface))),
+        v => Ok(v),
+    }
+}
+
+#[inline]
+fn interface_attr(interface: &str, attr: &str) -> Option<u32> {
+    let value = std::fs::read_to_string(format!("/sys/class/net/{}/{}", interface, attr)).ok()?;
+    let value = value.trim();
+    match value.strip_prefix("0x") {
+        Some(v) => u32::from_str_radix(v, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[inline]
+fn interface_canfd(interface: &str) -> bool {
+    interface_attr(interface, "mtu") == Some(libc::CANFD_MTU as u32)
+}
+
+#[inline]
+fn set_option<T>(fd: &OwnedFd, level: c_int, name: c_int, value: &T) -> io::Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(fd.as_raw_fd(), level, name, value as *const T as *const c_void, size_of::<T>() as libc::socklen_t)
+    };
+    if ret < 0 { Err(io::Error::last_os_error()) } else { Ok(()) }
+}
+
+/// Find the attribute of netlink message.
+fn find_attr(buf: &[u8], kind: u16) -> Option<&[u8]> {
+    let mut offset = 0;
+    while offset + 4 <= buf.len() {
+        let len = u16::from_ne_bytes([buf[offset], buf[offset + 1]]) as usize;
+        let ty = u16::from_ne_bytes([buf[offset + 2], buf[offset + 3]]) & NLA_TYPE_MASK;
+        if len < 4 || offset + len > buf.len() {
+            break;
+        }
+        if ty == kind {
+            return Some(&buf[offset + 4..offset + len]);
+        }
+        offset += (len + 3) & !3;
+    }
+    None
+}
+
+/// Read the `IFLA_CAN_STATE` and `IFLA_CAN_BERR_COUNTER` of interface by rtnetlink.
+fn read_can_link(ifindex: c_uint) -> io::Result<(Option<u32>, Option<libc::can_berr_counter>)> {
+    #[repr(C)]
+    struct Request {
+        hdr: libc::nlmsghdr,
+        info: libc::ifinfomsg,
+    }
+
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_ROUTE) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    set_option(&fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, &libc::timeval { tv_sec: 0, tv_usec: 100_000 })?;
+
+    let mut req: Request = unsafe { zeroed() };
+    req.hdr.nlmsg_len = size_of::<Request>() as u32;
+    req.hdr.nlmsg_type = libc::RTM_GETLINK;
+    req.hdr.nlmsg_flags = libc::NLM_F_REQUEST as u16;
+    req.hdr.nlmsg_seq = 1;
+    req.info.ifi_family = libc::AF_UNSPEC as u8;
+    req.info.ifi_index = ifindex as c_int;
+    let ret = unsafe { libc::send(fd.as_raw_fd(), &req as *const Request as *const c_void, size_of::<Request>(), 0) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut buf = vec![0u8; 16384];
+    let ret = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr() as *mut c_void, buf.len(), 0) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    buf.truncate(ret as usize);
+
+    let hdr_len = size_of::<libc::nlmsghdr>();
+    if buf.len() < hdr_len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let hdr = unsafe { std::ptr::read_unaligned(buf.as_ptr() as *const libc::nlmsghdr) };
+    if hdr.nlmsg_type == libc::NLMSG_ERROR as u16 {
+        let code = buf.get(hdr_len..hdr_len + 4)
+            .map(|v| i32::from_ne_bytes([v[0], v[1], v[2], v[3]]))
+            .unwrap_or_default();
+        return Err(io::Error::from_raw_os_error(-code));
+    }
+
+    let start = hdr_len + ((size_of::<libc::ifinfomsg>() + 3) & !3);
+    let end = (hdr.nlmsg_len as usize).min(buf.len());
+    let data = buf.get(start..end)
+        .and_then(|v| find_attr(v, libc::IFLA_LINKINFO))
+        .and_then(|v| find_attr(v, libc::IFLA_INFO_DATA));
+    let Some(data) = data else {
+        return Ok((None, None));
+    };
+
+    let state = find_attr(data, libc::IFLA_CAN_STATE as u16)
+        .filter(|v| v.len() >= 4)
+        .map(|v| u32::from_ne_bytes([v[0], v[1], v[2], v[3]]));
+    let counter = find_attr(data, libc::IFLA_CAN_BERR_COUNTER as u16)
+        .filter(|v| v.len() >= 4)
+        .map(|v| libc::can_berr_counter {
+            txerr: u16::from_ne_bytes([v[0], v[1]]),
+            rxerr: u16::from_ne_bytes([v[2], v[3]]),
+        });
+    Ok((state, counter))
+}
+
+/// Convert the frame that received from socket, `None` if it is an error frame.
+fn frame_to_message(frame: &libc::canfd_frame, canfd: bool) -> Option<CanMessage> {
+    let can_id = frame.can_id;
+    if can_id & libc::CAN_ERR_FLAG > 0 {
+        return None;
+    }
+
+    let extended = can_id & libc::CAN_EFF_FLAG > 0;
+    let raw = if extended { can_id & libc::CAN_EFF_MASK } else { can_id & libc::CAN_SFF_MASK };
+    let id = Id::from_bits(raw, extended);
+    let len = (frame.len as usize).min(if canfd { libc::CANFD_MAX_DLEN } else { libc::CAN_MAX_DLEN });
+    let mut message = if can_id & libc::CAN_RTR_FLAG > 0 {
+        CanMessage::new_remote(id, len)?
+    }
+    else {
+        CanMessage::new(id, &frame.data[..len])?
+    };
+    if canfd {
+        message.set_can_fd(true)
+            .set_bitrate_switch(frame.flags as c_int & libc::CANFD_BRS > 0)
+            .set_esi(frame.flags as c_int & libc::CANFD_ESI > 0);
+    }
+    Some(message)
+}
+
+/// Convert the frame to transmit, return the frame and size to write.
+fn message_to_frame(message: &CanMessage, canfd: bool) -> (libc::canfd_frame, usize) {
+    let mut frame: libc::canfd_frame = unsafe { zeroed() };
+    let mut can_id = message.id().as_raw();
+    if message.is_extended() {
+        can_id = (can_id & libc::CAN_EFF_MASK) | libc::CAN_EFF_FLAG;
+    }
+    if message.is_remote() {
+        can_id |= libc::CAN_RTR_FLAG;
+    }
+    frame.can_id = can_id;
+
+    let max = if canfd { libc::CANFD_MAX_DLEN } else { libc::CAN_MAX_DLEN };
+    let len = message.length().min(message.data().len()).min(max);
+    frame.len = len as u8;
+    if !message.is_remote() {
+        frame.data[..len].copy_from_slice(&message.data()[..len]);
+    }
+
+    if canfd {
+        let mut flags = libc::CANFD_FDF;
+        if message.is_bitrate_switch() { flags |= libc::CANFD_BRS; }
+        if message.is_esi() { flags |= libc::CANFD_ESI; }
+        frame.flags = flags as u8;
+        (frame, libc::CANFD_MTU)
+    }
+    else {
+        (frame, libc::CAN_MTU)
+    }
+}
+
+#[derive(Debug, Default)]
+struct SocketState {
+    can: VecDeque<CanMessage>,
+    canfd: VecDeque<CanMessage>,
+    /// The (TX, RX) error counters from error frame.
+    counters: (u8, u8),
+    /// The class and data of last error frame.
+    class: u32,
+    error: [u8; CANERR_FRAME_LENGTH],
+    /// The own frames are received by socket.
+    recv_own: bool,
+    /// The own frames that are not received yet, `true` if it's transmitted with self reception.
+    echoes: VecDeque<bool>,
+}
+
+impl SocketState {
+    #[inline]
+    fn queue(&mut self, can_type: ZCanFrameType) -> &mut VecDeque<CanMessage> {
+        match can_type {
+            ZCanFrameType::CANFD => &mut self.canfd,
+            _ => &mut self.can,
+        }
+    }
+
+    fn update_error(&mut self, frame: &libc::canfd_frame) {
+        let class = frame.can_id & libc::CAN_ERR_MASK;
+        if class & libc::CAN_ERR_CNT > 0 {
+            self.counters = (frame.data[6], frame.data[7]);
+        }
+        if class & libc::CAN_ERR_BUSOFF > 0 {
+            self.counters.0 = u8::MAX;
+        }
+        self.class = class;
+        self.error.copy_from_slice(&frame.data[..CANERR_FRAME_LENGTH]);
+        log::debug!("ZLGCAN - SocketCAN error frame class: {:#X}, data: {:02X?}", class, self.error);
+    }
+
+    /// Decode the last error frame.
+    fn channel_error(&self) -> Vec<ChannelError> {
+        let (class, data) = (self.class, &self.error);
+        let (ctrl, prot, location) = (data[1] as c_int, data[2] as c_int, data[3] as c_int);
+        let counters = ErrorCounters { rx: self.counters.1, tx: self.counters.0 };
+        let mut result = Vec::new();
+        if class & libc::CAN_ERR_LOSTARB > 0 {
+            result.push(ChannelError::ArbitrationLost { bit: data[0] });
+        }
+        if class & libc::CAN_ERR_CRTL > 0 && ctrl & (libc::CAN_ERR_CRTL_RX_OVERFLOW | libc::CAN_ERR_CRTL_TX_OVERFLOW) > 0 {
+            result.push(ChannelError::Overflow);
+        }
+        if class & libc::CAN_ERR_PROT > 0 {
+            let kind = if prot & (libc::CAN_ERR_PROT_BIT | libc::CAN_ERR_PROT_BIT0 | libc::CAN_ERR_PROT_BIT1) > 0 {
+                ProtocolError::Bit
+            }
+            else if prot & libc::CAN_ERR_PROT_FORM > 0 {
+                ProtocolError::Form
+            }
+            else if prot & libc::CAN_ERR_PROT_STUFF > 0 {
+                ProtocolError::Stuff
+            }
+            else if prot & libc::CAN_ERR_PROT_OVERLOAD > 0 {
+                ProtocolError::Overload
+            }
+            else {
+                match location {
+                    libc::CAN_ERR_PROT_LOC_CRC_SEQ | libc::CAN_ERR_PROT_LOC_CRC_DEL => ProtocolError::Crc,
+                    libc::CAN_ERR_PROT_LOC_ACK | libc::CAN_ERR_PROT_LOC_ACK_DEL => ProtocolError::Ack,
+                    _ => ProtocolError::Other,
+                }
+            };
+            result.push(ChannelError::Protocol { kind, rx: prot & libc::CAN_ERR_PROT_TX == 0, counters });
+        }
+        else if class & libc::CAN_ERR_ACK > 0 {
+            result.push(ChannelError::Protocol { kind: ProtocolError::Ack, rx: false, counters });
+        }
+        if class & libc::CAN_ERR_BUSOFF > 0 {
+            result.push(ChannelError::BusOff(counters));
+        }
+        else if class & libc::CAN_ERR_CRTL > 0 {
+            if ctrl & (libc::CAN_ERR_CRTL_RX_PASSIVE | libc::CAN_ERR_CRTL_TX_PASSIVE) > 0 {
+                result.push(ChannelError::ErrorPassive(counters));
+            }
+            else if ctrl & (libc::CAN_ERR_CRTL_RX_WARNING | libc::CAN_ERR_CRTL_TX_WARNING) > 0 {
+                result.push(ChannelError::ErrorWarning(counters));
+            }
+        }
+
+        result
+    }
+}
+
+#[derive(Debug)]
+struct SocketChannel {
+    interface: String,
+    ifindex: c_uint,
+    canfd: bool,
+    fd: OwnedFd,
+    state: Mutex<SocketState>,
+}
+
+impl SocketChannel {
+    fn open(interface: &str, canfd: bool) -> Result<Self, ZCanError> {
+        let ifindex = interface_index(interface)?;
+        let error = |e: io::Error| ZCanError::Other(format!("open interface `{}` failed: {}", interface, e));
+
+        let fd = unsafe { libc::socket(libc::PF_CAN, libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC, libc::CAN_RAW) };
+        if fd < 0 {
+            return Err(error(io::Error::last_os_error()));
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        set_option(&fd, libc::SOL_CAN_RAW, libc::CAN_RAW_ERR_FILTER, &libc::CAN_ERR_MASK)
+            .map_err(error)?;
+        if canfd {
+            set_option(&fd, libc::SOL_CAN_RAW, libc::CAN_RAW_FD_FRAMES, &(1 as c_int))
+                .map_err(error)?;
+        }
+        let flags = libc::SOF_TIMESTAMPING_RX_HARDWARE | libc::SOF_TIMESTAMPING_RAW_HARDWARE
+            | libc::SOF_TIMESTAMPING_RX_SOFTWARE | libc::SOF_TIMESTAMPING_SOFTWARE;
+        set_option(&fd, libc::SOL_SOCKET, libc::SO_TIMESTAMPING, &flags)
+            .unwrap_or_else(|e| log::warn!("ZLGCAN - the timestamp of interface `{}` is not enabled: {}", interface, e));
+
+        let mut addr: libc::sockaddr_can = unsafe { zeroed() };
+        addr.can_family = libc::AF_CAN as libc::sa_family_t;
+        addr.can_ifindex = ifindex as c_int;
+        let ret = unsafe {
+            libc::bind(fd.as_raw_fd(), &addr as *const libc::sockaddr_can as *const libc::sockaddr, size_of::<libc::sockaddr_can>() as libc::socklen_t)
+        };
+        if ret < 0 {
+            return Err(error(io::Error::last_os_error()));
+        }
+
+        Ok(Self { interface: interface.to_owned(), ifindex, canfd, fd, state: Default::default() })
+    }
+
+    #[inline]
+    fn state(&self) -> MutexGuard<'_, SocketState> {
+        self.state.lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Read all frames that received by socket into queues.
+    fn fill(&self, state: &mut SocketState, channel: u8) -> Result<(), ZCanError> {
+        loop {
+            let mut frame: libc::canfd_frame = unsafe { zeroed() };
+            let mut iov = libc::iovec {
+                iov_base: &mut frame as *mut libc::canfd_frame as *mut c_void,
+                iov_len: size_of::<libc::canfd_frame>(),
+            };
+            let mut control = [0u64; 16];
+            let mut msg: libc::msghdr = unsafe { zeroed() };
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr() as *mut c_void;
+            msg.msg_controllen = size_of::<[u64; 16]>() as _;
+
+            let ret = unsafe { libc::recvmsg(self.fd.as_raw_fd(), &mut msg, libc::MSG_DONTWAIT) };
+            if ret < 0 {
+                let e = io::Error::last_os_error();
+                return match e.kind() {
+                    io::ErrorKind::WouldBlock => Ok(()),
+                    io::ErrorKind::Interrupted => continue,
+                    _ => Err(ZCanError::Other(format!("receive from interface `{}` failed: {}", self.interface, e))),
+                };
+            }
+
+            let canfd = match ret as usize {
+                libc::CAN_MTU => false,
+                libc::CANFD_MTU => true,
+                v => {
+                    log::warn!("ZLGCAN - receive invalid frame size: {} from interface `{}`", v, self.interface);
+                    continue;
+                },
+            };
+            if frame.can_id & libc::CAN_ERR_FLAG > 0 {
+                state.update_error(&frame);
+                continue;
+            }
+            // the own frame transmitted without self reception is dropped
+            let own = msg.msg_flags & libc::MSG_CONFIRM > 0;
+            if own && !state.echoes.pop_front().unwrap_or_default() {
+                continue;
+            }
+            if let Some(mut message) = frame_to_message(&frame, canfd) {
+                let (timestamp, hardware) = unsafe { Self::timestamps(&msg) };
+                message.set_channel(channel)
+                    .set_direct(if own { Direct::Transmit } else { Direct::Receive })
+                    .set_timestamp(Some(timestamp))
+                    .set_hardware_timestamp(hardware);
+                state.queue(if canfd { ZCanFrameType::CANFD } else { ZCanFrameType::CAN })
+                    .push_back(message);
+            }
+        }
+    }
+
+    /// Get the software and hardware timestamps in microseconds from `SCM_TIMESTAMPING`,
+    /// the hardware timestamp is truncated to 32-bit as the raw ticks of device.
+    unsafe fn timestamps(msg: &libc::msghdr) -> (u64, Option<u32>) {
+        let micros = |ts: &libc::timespec| match (ts.tv_sec, ts.tv_nsec) {
+            (0, 0) => None,
+            (sec, nsec) => Some(sec as u64 * 1_000_000 + nsec as u64 / 1000),
+        };
+        let mut cmsg = libc::CMSG_FIRSTHDR(msg);
+        while !cmsg.is_null() {
+            let hdr = &*cmsg;
+            if hdr.cmsg_level == libc::SOL_SOCKET && hdr.cmsg_type == libc::SCM_TIMESTAMPING {
+                let ts = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const [libc::timespec; 3]);
+                let timestamp = micros(&ts[0]).unwrap_or_else(system_timestamp);
+                return (timestamp, micros(&ts[2]).map(|v| v as u32));
+            }
+            cmsg = libc::CMSG_NXTHDR(msg, cmsg);
+        }
+        (system_timestamp(), None)
+    }
+
+    /// Wait the socket readable until timeout.
+    fn poll(&self, timeout: Duration) -> Result<(), ZCanError> {
+        let mut fds = libc::pollfd { fd: self.fd.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        let timeout = timeout.as_millis().min(c_int::MAX as u128) as c_int;
+        let ret = unsafe { libc::poll(&mut fds, 1, timeout) };
+        if ret < 0 {
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(ZCanError::Other(format!("poll interface `{}` failed: {}", self.interface, e)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the frame to socket, return `false` when the transmit queue of interface is full until timeout.
+    fn write(&self, message: &CanMessage, canfd: bool) -> Result<bool, ZCanError> {
+        let self_reception = matches!(
+            ZCanTxMode::try_from(message.tx_mode()),
+            Ok(ZCanTxMode::SelfReception) | Ok(ZCanTxMode::SelfReceptionOnce)
+        );
+        // the state is locked until written, so the own frame can't be received before it's recorded
+        let mut state = self.state();
+        if self_reception && !state.recv_own {
+            set_option(&self.fd, libc::SOL_CAN_RAW, libc::CAN_RAW_RECV_OWN_MSGS, &(1 as c_int))
+                .map_err(|e| ZCanError::Other(format!("enable self reception of interface `{}` failed: {}", self.interface, e)))?;
+            state.recv_own = true;
+        }
+
+        let (frame, size) = message_to_frame(message, canfd);
+        let deadline = Instant::now() + TX_TIMEOUT;
+        loop {
+            let ret = unsafe { libc::write(self.fd.as_raw_fd(), &frame as *const libc::canfd_frame as *const c_void, size) };
+            if ret >= 0 {
+                if state.recv_own {
+                    state.echoes.push_back(self_reception);
+                }
+                return Ok(true);
+            }
+
+            let e = io::Error::last_os_error();
+            match e.raw_os_error() {
+                Some(libc::EAGAIN) | Some(libc::ENOBUFS) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(1));
+                },
+                Some(libc::EAGAIN) | Some(libc::ENOBUFS) => return Ok(false),
+                Some(libc::EINTR) => {},
+                _ => return Err(ZCanError::Other(format!("transmit to interface `{}` failed: {}", self.interface, e))),
+            }
+        }
+    }
+}
+
+pub(crate) struct SocketCanBackend {
+    interfaces: Vec<String>,
+    derive: Option<DeriveInfo>,
+    channels: Mutex<HashMap<u8, Arc<SocketChannel>>>,
+}
+
+impl SocketCanBackend {
+    pub(crate) fn new(interfaces: Vec<String>, derive: Option<&DeriveInfo>) -> Self {
+        Self { interfaces, derive: derive.cloned(), channels: Default::default() }
+    }
+
+    #[inline]
+    fn channels(&self) -> MutexGuard<'_, HashMap<u8, Arc<SocketChannel>>> {
+        self.channels.lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[inline]
+    fn channel(&self, channel: u8) -> Result<Arc<SocketChannel>, ZCanError> {
+        self.channels()
+            .get(&channel)
+            .cloned()
+            .ok_or(ZCanError::ChannelNotOpened)
+    }
+
+    fn canfd(&self) -> bool {
+        match &self.derive {
+            Some(v) => v.canfd(),
+            None => !self.interfaces.is_empty() && self.interfaces.iter().all(|v| interface_canfd(v)),
+        }
+    }
+
+    fn transmit(&self, context: &ZChannelContext, can_type: ZCanFrameType, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let chl = self.channel(context.channel())?;
+        let canfd = matches!(can_type, ZCanFrameType::CANFD);
+        if canfd && !chl.canfd {
+            return Err(ZCanError::MethodNotSupported);
+        }
+
+        let len = frames.len() as u32;
+        let mut ret = 0;
+        for frame in &frames {
+            if !chl.write(frame, canfd)? {
+                break;
+            }
+            ret += 1;
+        }
+        if ret < len {
+            log::warn!("ZLGCAN - transmit {} frame expect: {}, actual: {}!", can_type, len, ret);
+        }
+        else {
+            log::debug!("ZLGCAN - transmit {} frame: {}", can_type, ret);
+        }
+        Ok(ret)
+    }
+
+    fn receive(&self, context: &ZChannelContext, can_type: ZCanFrameType, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let channel = context.channel();
+        let chl = self.channel(channel)?;
+        let deadline = Instant::now().checked_add(Duration::from_millis(timeout as u64));
+        loop {
+            {
+                let mut state = chl.state();
+                chl.fill(&mut state, channel)?;
+                let queue = state.queue(can_type);
+                if !queue.is_empty() || size == 0 {
+                    let count = queue.len().min(size as usize);
+                    return Ok(queue.drain(..count).collect());
+                }
+            }
+
+            let remain = match deadline {
+                Some(v) => v.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            if remain.is_zero() {
+                log::warn!("ZLGCAN - receive {} frame expect: {}, actual: 0!", can_type, size);
+                return Ok(Vec::new());
+            }
+            chl.poll(remain)?;
+        }
+    }
+}
+
+impl ZCanBackend for SocketCanBackend {
+    fn open(&self, _: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        for interface in &self.interfaces {
+            interface_index(interface)?;
+        }
+        Ok(())
+    }
+
+    fn close(&self, _: &ZDeviceContext) -> Result<(), ZCanError> {
+        self.channels().clear();
+        Ok(())
+    }
+
+    fn read_device_info(&self, _: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        let channels = match &self.derive {
+            Some(v) => v.channels(),
+            None => self.interfaces.len() as u8,
+        };
+        ZDeviceInfo::try_from(&DeriveInfo::new(self.canfd(), channels))
+    }
+
+    fn is_online(&self, _: &ZDeviceContext) -> Result<bool, ZCanError> {
+        Ok(self.interfaces.iter().all(|v| {
+            interface_attr(v, "flags").is_some_and(|flags| flags & libc::IFF_UP as u32 > 0)
+        }))
+    }
+
+    fn host_timestamp(&self) -> bool {
+        true
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, _: &CanChlCfg) -> Result<(), ZCanError> {
+        let channel = context.channel();
+        let interface = self.interfaces.get(channel as usize)
+            .ok_or(ZCanError::ParamNotSupported)?;
+        let canfd = self.canfd() && interface_canfd(interface);
+        let chl = SocketChannel::open(interface, canfd)?;
+        self.channels().insert(channel, Arc::new(chl));
+        context.set_channel_handler(None);
+        Ok(())
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        match self.channels().remove(&context.channel()) {
+            Some(_) => Ok(()),
+            None => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        let channel = context.channel();
+        let chl = self.channel(channel)?;
+        let mut state = chl.state();
+        chl.fill(&mut state, channel)?;
+
+        let mut status = ZCanChlStatus { regTECounter: state.counters.0, regRECounter: state.counters.1, ..Default::default() };
+        match read_can_link(chl.ifindex) {
+            Ok((can_state, counter)) => {
+                if let Some(v) = can_state {
+                    status.regStatus = v as u8;
+                }
+                if let Some(v) = counter {
+                    status.regTECounter = v.txerr.min(u8::MAX as u16) as u8;
+                    status.regRECounter = v.rxerr.min(u8::MAX as u16) as u8;
+                }
+            },
+            Err(e) => log::debug!("ZLGCAN - read link of interface `{}` failed: {}", chl.interface, e),
+        }
+        Ok(status)
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        let channel = context.channel();
+        let chl = self.channel(channel)?;
+        let mut state = chl.state();
+        chl.fill(&mut state, channel)?;
+        Ok(ZCanChlError::from(ZCanChlErrorV1 { hdr: Default::default(), data: state.error }))
+    }
+
+    fn read_channel_error(&self, context: &ZChannelContext) -> Result<Vec<ChannelError>, ZCanError> {
+        let channel = context.channel();
+        let chl = self.channel(channel)?;
+        let mut state = chl.state();
+        chl.fill(&mut state, channel)?;
+        Ok(state.channel_error())
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        let channel = context.channel();
+        let chl = self.channel(channel)?;
+        let mut state = chl.state();
+        chl.fill(&mut state, channel)?;
+        state.can.clear();
+        state.canfd.clear();
+        Ok(())
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        let channel = context.channel();
+        let chl = self.channel(channel)?;
+        let mut state = chl.state();
+        chl.fill(&mut state, channel)?;
+        let ret = match can_type {
+            ZCanFrameType::CAN => state.can.len(),
+            ZCanFrameType::CANFD => state.canfd.len(),
+            ZCanFrameType::ALL => state.can.len() + state.canfd.len(),
+        };
+        log::debug!("ZLGCAN - get receive {} number: {}.", can_type, ret);
+        Ok(ret as u32)
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        self.receive(context, ZCanFrameType::CAN, size, timeout)
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        self.transmit(context, ZCanFrameType::CAN, frames)
+    }
+
+    fn receive_canfd(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        if !self.canfd() {
+            return Err(ZCanError::MethodNotSupported);
+        }
+        self.receive(context, ZCanFrameType::CANFD, size, timeout)
+    }
+
+    fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        self.transmit(context, ZCanFrameType::CANFD, frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isotp_rs::can::{frame::Frame, identifier::Id};
+    use zlgcan_common::can::CanMessage;
+    use super::{find_attr, frame_to_message, message_to_frame};
+
+    #[test]
+    fn socketcan_frame() {
+        let mut msg = CanMessage::new(Id::from_bits(0x18DA10F1, true), [0x55; 12].as_slice()).unwrap();
+        msg.set_bitrate_switch(true);
+        let (frame, size) = message_to_frame(&msg, true);
+        assert_eq!(size, libc::CANFD_MTU);
+        assert_eq!(frame.can_id, 0x18DA10F1 | libc::CAN_EFF_FLAG);
+        assert_eq!(frame.flags as i32, libc::CANFD_FDF | libc::CANFD_BRS);
+        let msg = frame_to_message(&frame, true).unwrap();
+        assert!(msg.is_extended() && msg.is_can_fd() && msg.is_bitrate_switch() && !msg.is_esi());
+        assert_eq!(msg.data(), [0x55; 12].as_slice());
+
+        let msg = CanMessage::new_remote(Id::from_bits(0x7DF, false), 2).unwrap();
+        let (frame, size) = message_to_frame(&msg, false);
+        assert_eq!(size, libc::CAN_MTU);
+        assert_eq!(frame.can_id, 0x7DF | libc::CAN_RTR_FLAG);
+        assert!(frame_to_message(&frame, false).unwrap().is_remote());
+    }
+
+    #[test]
+    fn netlink_attr() {
+        // two attributes: type 1 with 2 bytes(padded to 8), type 2 with 4 bytes
+        let buf = [6, 0, 1, 0, 0xAA, 0xBB, 0, 0, 8, 0, 2, 0, 1, 2, 3, 4];
+        assert_eq!(find_attr(&buf, 1), Some([0xAA, 0xBB].as_slice()));
+        assert_eq!(find_attr(&buf, 2), Some([1, 2, 3, 4].as_slice()));
+        assert_eq!(find_attr(&buf, 3), None);
+    }
+}
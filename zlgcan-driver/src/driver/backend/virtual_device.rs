@@ -0,0 +1,250 @@
+//! The in-process virtual device(`ZCAN_VIRTUAL_DEVICE`).
+//!
+//! Every virtual device index owns a bus, all channels opened on the same bus
+//! receive the frames transmitted by the other channels. A frame transmitted with
+//! `ZCanTxMode::SelfReception` or `ZCanTxMode::SelfReceptionOnce` is also received
+//! by the transmitting channel with `Direct::Transmit`.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use isotp_rs::can::frame::{Direct, Frame};
+use zlgcan_common::can::{CanChlCfg, CanMessage, ZCanChlError, ZCanChlErrorV1, ZCanChlStatus, ZCanFrameType, ZCanTxMode};
+use zlgcan_common::device::{DeriveInfo, ZCanDeviceType, ZChannelContext, ZDeviceContext, ZDeviceInfo};
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::utils::system_timestamp;
+use crate::driver::backend::{BackendFactory, ZCanBackend};
+
+#[derive(Debug, Default)]
+struct VirtualChannel {
+    can: VecDeque<CanMessage>,
+    canfd: VecDeque<CanMessage>,
+    status: ZCanChlStatus,
+}
+
+impl VirtualChannel {
+    #[inline]
+    fn queue(&mut self, can_type: ZCanFrameType) -> &mut VecDeque<CanMessage> {
+        match can_type {
+            ZCanFrameType::CANFD => &mut self.canfd,
+            _ => &mut self.can,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct VirtualBus {
+    channels: Mutex<HashMap<u8, VirtualChannel>>,
+    cond: Condvar,
+}
+
+impl VirtualBus {
+    #[inline]
+    fn channels(&self) -> MutexGuard<'_, HashMap<u8, VirtualChannel>> {
+        self.channels.lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The buses of all virtual devices, keyed by device index.
+static VIRTUAL_BUSES: Mutex<BTreeMap<u32, Arc<VirtualBus>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone)]
+pub(crate) struct VirtualBackend {
+    canfd: bool,
+    channels: u8,
+}
+
+impl VirtualBackend {
+    pub(crate) const DEFAULT_CHANNELS: u8 = 2;
+
+    pub(crate) fn new(derive: Option<&DeriveInfo>) -> Self {
+        match derive {
+            Some(v) => Self { canfd: v.canfd(), channels: v.channels() },
+            None => Self { canfd: true, channels: Self::DEFAULT_CHANNELS },
+        }
+    }
+
+    #[inline]
+    fn bus(&self, dev_idx: u32) -> Arc<VirtualBus> {
+        let mut buses = VIRTUAL_BUSES.lock()
+            .unwrap_or_else(|e| e.into_inner());
+        buses.entry(dev_idx)
+            .or_default()
+            .clone()
+    }
+
+    fn transmit(&self, context: &ZChannelContext, can_type: ZCanFrameType, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let channel = context.channel();
+        let bus = self.bus(context.device_index());
+        let mut channels = bus.channels();
+        if !channels.contains_key(&channel) {
+            return Err(ZCanError::ChannelNotOpened);
+        }
+
+        let len = frames.len() as u32;
+        for mut frame in frames {
+            let self_reception = matches!(
+                ZCanTxMode::try_from(frame.tx_mode()),
+                Ok(ZCanTxMode::SelfReception) | Ok(ZCanTxMode::SelfReceptionOnce)
+            );
+            frame.try_set_can_fd(matches!(can_type, ZCanFrameType::CANFD))?
+                .set_timestamp(Some(system_timestamp()));
+
+            for (&idx, chl) in channels.iter_mut() {
+                if idx == channel && !self_reception {
+                    continue;
+                }
+
+                let mut frame = frame.clone();
+                frame.set_channel(idx)
+                    .set_direct(if idx == channel { Direct::Transmit } else { Direct::Receive });
+                chl.queue(can_type).push_back(frame);
+            }
+        }
+        bus.cond.notify_all();
+        log::debug!("ZLGCAN - virtual device transmit frame: {}", len);
+
+        Ok(len)
+    }
+
+    fn receive(&self, context: &ZChannelContext, can_type: ZCanFrameType, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        let channel = context.channel();
+        let bus = self.bus(context.device_index());
+        let deadline = Instant::now().checked_add(Duration::from_millis(timeout as u64));
+        let mut channels = bus.channels();
+        loop {
+            let queue = channels.get_mut(&channel)
+                .ok_or(ZCanError::ChannelNotOpened)?
+                .queue(can_type);
+            if !queue.is_empty() || size == 0 {
+                let count = queue.len().min(size as usize);
+                return Ok(queue.drain(..count).collect());
+            }
+
+            let remain = match deadline {
+                Some(v) => v.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            if remain.is_zero() {
+                log::warn!("ZLGCAN - receive {} frame expect: {}, actual: 0!", can_type, size);
+                return Ok(Vec::new());
+            }
+            channels = bus.cond.wait_timeout(channels, remain)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+}
+
+impl ZCanBackend for VirtualBackend {
+    fn open(&self, context: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        let _ = self.bus(context.device_index());
+        Ok(())
+    }
+
+    fn close(&self, context: &ZDeviceContext) -> Result<(), ZCanError> {
+        let mut buses = VIRTUAL_BUSES.lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(bus) = buses.get(&context.device_index()) {
+            if bus.channels().is_empty() {
+                buses.remove(&context.device_index());
+            }
+        }
+        Ok(())
+    }
+
+    fn read_device_info(&self, _: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        ZDeviceInfo::try_from(&DeriveInfo::new(self.canfd, self.channels))
+    }
+
+    fn is_online(&self, _: &ZDeviceContext) -> Result<bool, ZCanError> {
+        Ok(true)
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, _: &CanChlCfg) -> Result<(), ZCanError> {
+        let channel = context.channel();
+        if channel >= self.channels {
+            return Err(ZCanError::ParamNotSupported);
+        }
+        self.bus(context.device_index())
+            .channels()
+            .insert(channel, Default::default());
+        context.set_channel_handler(None);
+        Ok(())
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        match self.bus(context.device_index()).channels().remove(&context.channel()) {
+            Some(_) => Ok(()),
+            None => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+
+    fn read_can_chl_status(&self, context: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        match self.bus(context.device_index()).channels().get(&context.channel()) {
+            Some(v) => Ok(v.status),
+            None => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+
+    fn read_can_chl_error(&self, context: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        match self.bus(context.device_index()).channels().get(&context.channel()) {
+            Some(_) => Ok(ZCanChlError::from(ZCanChlErrorV1::default())),
+            None => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        match self.bus(context.device_index()).channels().get_mut(&context.channel()) {
+            Some(v) => {
+                v.can.clear();
+                v.canfd.clear();
+                Ok(())
+            },
+            None => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, can_type: ZCanFrameType) -> Result<u32, ZCanError> {
+        match self.bus(context.device_index()).channels().get(&context.channel()) {
+            Some(v) => {
+                let ret = match can_type {
+                    ZCanFrameType::CAN => v.can.len(),
+                    ZCanFrameType::CANFD => v.canfd.len(),
+                    ZCanFrameType::ALL => v.can.len() + v.canfd.len(),
+                };
+                log::debug!("ZLGCAN - get receive {} number: {}.", can_type, ret);
+                Ok(ret as u32)
+            },
+            None => Err(ZCanError::ChannelNotOpened),
+        }
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        self.receive(context, ZCanFrameType::CAN, size, timeout)
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        self.transmit(context, ZCanFrameType::CAN, frames)
+    }
+
+    fn receive_canfd(&self, context: &ZChannelContext, size: u32, timeout: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        if !self.canfd {
+            return Err(ZCanError::MethodNotSupported);
+        }
+        self.receive(context, ZCanFrameType::CANFD, size, timeout)
+    }
+
+    fn transmit_canfd(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        if !self.canfd {
+            return Err(ZCanError::MethodNotSupported);
+        }
+        self.transmit(context, ZCanFrameType::CANFD, frames)
+    }
+}
+
+pub(super) fn register(backends: &mut HashMap<ZCanDeviceType, BackendFactory>) {
+    backends.insert(ZCanDeviceType::ZCAN_VIRTUAL_DEVICE, Arc::new(|_, derive| {
+        Ok(Arc::new(VirtualBackend::new(derive)))
+    }));
+}
@@ -0,0 +1,200 @@
+//! The vendor library loader.
+//!
+//! Each device family has its own vendor library which is loaded at the first time
+//! a device of the family is created, and shared by the devices created after.
+//! The library is searched in order of:
+//! 1. the path set by `set_library_path` for the family;
+//! 2. the directory set by `set_library_dir`;
+//! 3. the `ZCAN_LIBRARY` directory defined in `zcan.env` or environment;
+//! 4. the default `library` directory under current directory;
+//! 5. the library name only that searched by the system loader.
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use dlopen2::symbor::{Container, SymBorApi};
+use zlgcan_common::device::{ZCanDeviceType, ZCanError};
+
+#[cfg(all(target_os = "linux", target_arch = "x86"))]
+const LIB_PATH: &str = "library/linux/x86/";
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const LIB_PATH: &str = "library/linux/x86_64/";
+#[cfg(all(target_os = "windows", target_arch = "x86"))]
+const LIB_PATH: &str = "library/windows/x86/";
+#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+const LIB_PATH: &str = "library/windows/x86_64/";
+
+/// The device family that shares the same vendor library.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LibraryFamily {
+    /// USBCAN1 and USBCAN2.
+    #[cfg(target_os = "linux")]
+    USBCAN,
+    /// USBCAN-4E-U.
+    #[cfg(target_os = "linux")]
+    USBCAN_4E,
+    /// USBCAN-8E-U.
+    #[cfg(target_os = "linux")]
+    USBCAN_8E,
+    /// USBCANFD-MINI, USBCANFD-100U and USBCANFD-200U.
+    #[cfg(target_os = "linux")]
+    USBCANFD,
+    /// USBCANFD-800U.
+    #[cfg(target_os = "linux")]
+    USBCANFD_800U,
+    /// All devices supported by `zlgcan.dll`.
+    #[cfg(target_os = "windows")]
+    ZLGCAN,
+}
+
+impl LibraryFamily {
+    /// Get the family of the device type, `None` if the device is not served by a vendor library.
+    pub fn from_device_type(dev_type: ZCanDeviceType) -> Option<Self> {
+        #[cfg(target_os = "linux")]
+        let family = match dev_type {
+            ZCanDeviceType::ZCAN_USBCAN1 | ZCanDeviceType::ZCAN_USBCAN2 => Some(Self::USBCAN),
+            ZCanDeviceType::ZCAN_USBCAN_4E_U => Some(Self::USBCAN_4E),
+            ZCanDeviceType::ZCAN_USBCAN_8E_U => Some(Self::USBCAN_8E),
+            ZCanDeviceType::ZCAN_USBCANFD_MINI
+            | ZCanDeviceType::ZCAN_USBCANFD_100U
+            | ZCanDeviceType::ZCAN_USBCANFD_200U => Some(Self::USBCANFD),
+            ZCanDeviceType::ZCAN_USBCANFD_800U => Some(Self::USBCANFD_800U),
+            _ => None,
+        };
+        #[cfg(target_os = "windows")]
+        let family = match dev_type {
+            ZCanDeviceType::ZCAN_VIRTUAL_DEVICE => None,
+            _ => Some(Self::ZLGCAN),
+        };
+        family
+    }
+
+    /// The file name of the vendor library.
+    pub fn library_name(&self) -> &'static str {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::USBCAN => "libusbcan.so",
+            #[cfg(target_os = "linux")]
+            Self::USBCAN_4E => "libusbcan-4e.so",
+            #[cfg(target_os = "linux")]
+            Self::USBCAN_8E => "libusbcan-8e.so",
+            #[cfg(target_os = "linux")]
+            Self::USBCANFD => "libusbcanfd.so",
+            #[cfg(target_os = "linux")]
+            Self::USBCANFD_800U => "libusbcanfd800u.so",
+            #[cfg(target_os = "windows")]
+            Self::ZLGCAN => "zlgcan.dll",
+        }
+    }
+}
+
+impl Display for LibraryFamily {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Default)]
+struct LibraryState {
+    paths: HashMap<LibraryFamily, PathBuf>,
+    dir: Option<PathBuf>,
+    loaded: HashMap<LibraryFamily, Arc<dyn Any + Send + Sync>>,
+}
+
+fn state() -> &'static Mutex<LibraryState> {
+    static STATE: OnceLock<Mutex<LibraryState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+/// Set the library file path of the family, it is searched before the others.
+/// The library loaded before is still used by the created devices, the devices created after will load it again.
+pub fn set_library_path<P: Into<PathBuf>>(family: LibraryFamily, path: P) {
+    let mut state = state().lock()
+        .unwrap_or_else(|e| e.into_inner());
+    state.paths.insert(family, path.into());
+    state.loaded.remove(&family);
+}
+
+/// Set the directory that contains the vendor libraries, the libraries are searched in it directly.
+pub fn set_library_dir<P: Into<PathBuf>>(dir: P) {
+    let mut state = state().lock()
+        .unwrap_or_else(|e| e.into_inner());
+    state.dir = Some(dir.into());
+    state.loaded.clear();
+}
+
+/// Clear the library paths and directory set before.
+pub fn clear_library_paths() {
+    let mut state = state().lock()
+        .unwrap_or_else(|e| e.into_inner());
+    state.paths.clear();
+    state.dir = None;
+    state.loaded.clear();
+}
+
+/// Get the paths of the family library in order that searched.
+pub fn library_search_paths(family: LibraryFamily) -> Vec<PathBuf> {
+    let state = state().lock()
+        .unwrap_or_else(|e| e.into_inner());
+    search_paths(&state, family)
+}
+
+fn search_paths(state: &LibraryState, family: LibraryFamily) -> Vec<PathBuf> {
+    let name = family.library_name();
+    let mut paths = Vec::new();
+    if let Some(path) = state.paths.get(&family) {
+        paths.push(path.clone());
+    }
+    if let Some(dir) = &state.dir {
+        paths.push(dir.join(name));
+    }
+    let _ = dotenvy::from_filename("zcan.env");
+    if let Ok(dir) = std::env::var("ZCAN_LIBRARY") {
+        paths.push(Path::new(&dir).join(LIB_PATH).join(name));
+    }
+    paths.push(Path::new(LIB_PATH).join(name));
+    paths.push(PathBuf::from(name));
+    paths
+}
+
+/// Load the library of the family, the library loaded before is returned directly.
+pub(crate) fn load_library<T>(family: LibraryFamily) -> Result<Arc<Container<T>>, ZCanError>
+    where
+        T: SymBorApi<'static> + Send + Sync + 'static {
+    let mut state = state().lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(library) = state.loaded.get(&family) {
+        if let Ok(library) = Arc::clone(library).downcast::<Container<T>>() {
+            return Ok(library);
+        }
+    }
+
+    let paths = search_paths(&state, family);
+    let mut errors = Vec::new();
+    for path in &paths {
+        // the bare library name is resolved by the system loader, others are skipped when not existed.
+        if path.components().count() > 1 && !path.exists() {
+            continue;
+        }
+        match unsafe { Container::<T>::load(path) } {
+            Ok(library) => {
+                log::debug!("ZLGCAN - {} library loaded from: {}", family, path.display());
+                let library = Arc::new(library);
+                state.loaded.insert(family, Arc::clone(&library) as Arc<dyn Any + Send + Sync>);
+                return Ok(library);
+            },
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    let searched = paths.iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(ZCanError::LibraryLoadFailed(format!(
+        "{} library `{}` is not loaded, searched: [{}], errors: [{}]",
+        family, family.library_name(), searched, errors.join("; ")
+    )))
+}
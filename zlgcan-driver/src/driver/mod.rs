@@ -1,8 +1,18 @@
-use zlgcan_common::can::{CanChlCfg, CanMessage, ZCanChlError, ZCanChlStatus, ZCanFrameType};
+use zlgcan_common::can::{CanChlCfg, CanMessage, ChannelError, ZCanChlError, ZCanChlStatus, ZCanFrameType};
 use zlgcan_common::cloud::{ZCloudGpsFrame, ZCloudServerInfo, ZCloudUserData};
+use zlgcan_common::data::DataObject;
 use zlgcan_common::device::{DeriveInfo, Handler, ZCanDeviceType, ZCanError, ZChannelContext, ZDeviceInfo};
 use zlgcan_common::lin::{ZLinChlCfg, ZLinFrame, ZLinPublish, ZLinPublishEx, ZLinSubscribe};
 
+mod backend;
+mod library;
+mod profile;
+pub use backend::{backend_registered, clear_offline_config, init_can_chls_each, register_backend, reinit_can_chl, set_offline_config, unregister_backend, BackendFactory, OfflineConfig, ReplaySpeed, ZCanBackend};
+#[cfg(target_os = "linux")]
+pub use backend::register_socketcan;
+pub use library::{clear_library_paths, library_search_paths, set_library_dir, set_library_path, LibraryFamily};
+pub use profile::{CanChannelProfile, DeriveSection, DeviceProfile, DeviceSection, LinChannelProfile};
+
 #[cfg(target_os = "windows")]
 mod windows;
 #[cfg(target_os = "windows")]
@@ -26,11 +36,33 @@ pub trait ZDevice {
     fn is_online(&self) -> Result<bool, ZCanError> {
         Err(ZCanError::MethodNotSupported)
     }
+    /// Initialize the CAN channels by configuration index.
     fn init_can_chl(&mut self, cfg: Vec<CanChlCfg>) -> Result<(), ZCanError>;
+    /// Initialize a CAN channel, the other channels are not touched.
+    /// The channel opened before will be reset and initialized again.
+    fn init_can_channel(&mut self, channel: u8, cfg: CanChlCfg) -> Result<(), ZCanError>;
+    /// Initialize the CAN channels with channel and configuration pairs, the channels not given are skipped.
+    fn init_can_channels<I>(&mut self, cfg: I) -> Result<(), ZCanError>
+        where
+            I: IntoIterator<Item = (u8, CanChlCfg)> {
+        cfg.into_iter()
+            .try_for_each(|(channel, cfg)| self.init_can_channel(channel, cfg))
+    }
+    /// Reconfigure the opened CAN channel, the other channels keep running.
+    /// The channel is closed if it is failed to initialize with the new configuration.
+    fn reconfigure(&mut self, channel: u8, cfg: CanChlCfg) -> Result<(), ZCanError> {
+        self.reset_can_chl(channel)?;
+        self.init_can_channel(channel, cfg)
+    }
     fn reset_can_chl(&mut self, channel: u8) -> Result<(), ZCanError>;
     // fn resistance_state(&self, dev_idx: u32, channel: u8) -> Result<(), ZCanError>;
     fn read_can_chl_status(&self, channel: u8) -> Result<ZCanChlStatus, ZCanError>;
     fn read_can_chl_error(&self, channel: u8) -> Result<ZCanChlError, ZCanError>;
+    /// Read and decode the channel error by device type, the empty result means no error.
+    fn read_channel_error(&self, channel: u8) -> Result<Vec<ChannelError>, ZCanError> {
+        let error = self.read_can_chl_error(channel)?;
+        Ok(ChannelError::decode(self.device_type(), &error))
+    }
     fn clear_can_buffer(&self, channel: u8) -> Result<(), ZCanError>;
     fn get_can_num(&self, channel: u8, can_type: ZCanFrameType) -> Result<u32, ZCanError>;
     fn receive_can(&self, channel: u8, size: u32, timeout: Option<u32>) -> Result<Vec<CanMessage>, ZCanError>;
@@ -41,6 +73,29 @@ pub trait ZDevice {
     fn transmit_canfd(&self, channel: u8, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
         Err(ZCanError::MethodNotSupported)
     }
+    /// Receive the CAN(FD), error and LIN frames of channel in one time-ordered batch.
+    ///
+    /// The default implementation falls back to [`receive_all_each`] which only returns the CAN and CANFD frames,
+    /// the errors and LIN frames are read by `read_can_chl_error` and `receive_lin` for the devices that can't receive data objects.
+    fn receive_all(&self, channel: u8, size: u32, timeout: Option<u32>) -> Result<Vec<DataObject>, ZCanError> {
+        receive_all_each(self, channel, size, timeout)
+    }
+    /// Transmit the mixed CAN(FD) and LIN frames by one call.
+    fn transmit_all(&self, channel: u8, objects: Vec<DataObject>) -> Result<u32, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// Enable or disable the merged receive, the frames of all channels are received by `receive_merged` when it's enabled.
+    fn set_receive_merge(&mut self, enable: bool) -> Result<(), ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
+    /// Whether the merged receive is enabled.
+    fn receive_merge(&self) -> bool {
+        false
+    }
+    /// Receive the frames of all channels by one call in hardware timestamp order, each frame is tagged with its channel.
+    fn receive_merged(&self, size: u32, timeout: Option<u32>) -> Result<Vec<DataObject>, ZCanError> {
+        Err(ZCanError::MethodNotSupported)
+    }
     fn init_lin_chl(&mut self, cfg: Vec<ZLinChlCfg>) -> Result<(), ZCanError> {
         Err(ZCanError::MethodNotSupported)
     }
@@ -125,3 +180,23 @@ pub trait ZDevice {
         })
     }
 }
+
+/// Receive the CAN and CANFD frames of channel separately and merge them by timestamp.
+/// It's used by the devices that can't receive data objects, the error and LIN frames are not included.
+pub fn receive_all_each<D: ZDevice + ?Sized>(device: &D, channel: u8, size: u32, timeout: Option<u32>) -> Result<Vec<DataObject>, ZCanError> {
+    let mut objects = Vec::new();
+    let count = device.get_can_num(channel, ZCanFrameType::CAN)?.min(size);
+    if count > 0 {
+        objects.extend(device.receive_can(channel, count, timeout)?.into_iter().map(DataObject::Can));
+    }
+
+    if device.device_type().canfd_support() {
+        let count = device.get_can_num(channel, ZCanFrameType::CANFD)?.min(size - objects.len() as u32);
+        if count > 0 {
+            objects.extend(device.receive_canfd(channel, count, timeout)?.into_iter().map(DataObject::Can));
+        }
+    }
+
+    objects.sort_by_key(DataObject::timestamp);
+    Ok(objects)
+}
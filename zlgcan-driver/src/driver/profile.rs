@@ -0,0 +1,353 @@
+//! The device profile that describes a whole device, such as:
+//! ```yaml
+//! device:
+//!   type: 41          # ZCAN_USBCANFD_200U
+//!   index: 0          # or `serial: "XXXXXXXX"`
+//!   # derive: { canfd: true, channels: 2 }
+//!   # receive_merge: true  # receive the frames of all channels by one call
+//! # bitrate_cfg: bitrate.cfg.yaml
+//! can:                # the channels in order
+//!   - { bitrate: 500000, data_bitrate: 2000000, type: canfd_iso, resistance: true }
+//!   - { bitrate: 500000, mode: listen_only, filter: single, acc_code: 0, acc_mask: 0xFFFFFFFF }
+//! lin:
+//!   - { mode: master, bitrate: 19200, checksum: enhance, max_length: 8 }
+//! ```
+//! The profile is checked against the capabilities of device type before the library is loaded.
+//! Only the YAML format is supported.
+//! The relative `bitrate_cfg` is resolved against the directory of profile when loaded by `from_file`.
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::{de::Error, Deserialize, Deserializer};
+use zlgcan_common::can::{CanChlCfg, CanChlCfgFactory, ZCanChlMode, ZCanChlType, ZCanFilterType};
+use zlgcan_common::device::{DeriveInfo, ZCanDeviceType, ZCanError};
+use zlgcan_common::lin::{ZLinCheckSumMode, ZLinChlCfg, ZLinMode};
+use super::{ZCanDriver, ZDevice};
+
+/// The max device index to search the device by serial number.
+const SERIAL_SEARCH_MAX: u32 = 16;
+
+/// The device section of profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceSection {
+    #[serde(rename = "type")]
+    pub dev_type: u32,
+    pub index: Option<u32>,
+    pub serial: Option<String>,
+    pub derive: Option<DeriveSection>,
+    #[serde(default)]
+    pub receive_merge: bool,
+}
+
+/// The derive info of device section.
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeriveSection {
+    pub canfd: bool,
+    pub channels: u8,
+}
+
+/// The CAN channel of profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CanChannelProfile {
+    pub bitrate: u32,
+    pub data_bitrate: Option<u32>,
+    /// `can`, `canfd_iso` or `canfd_non_iso`, the default is `can`.
+    #[serde(rename = "type", default, deserialize_with = "can_type")]
+    pub can_type: ZCanChlType,
+    /// `normal` or `listen_only`, the default is `normal`.
+    #[serde(default, deserialize_with = "can_mode")]
+    pub mode: ZCanChlMode,
+    pub resistance: Option<bool>,
+    /// `double` or `single`, the default is `double`.
+    #[serde(default, deserialize_with = "filter_type")]
+    pub filter: ZCanFilterType,
+    pub acc_code: Option<u32>,
+    pub acc_mask: Option<u32>,
+    pub brp: Option<u32>,
+}
+
+/// The LIN channel of profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinChannelProfile {
+    /// `master` or `slave`.
+    #[serde(deserialize_with = "lin_mode")]
+    pub mode: u8,
+    pub bitrate: u32,
+    /// `classic`, `enhance` or `auto`, the default is `enhance`.
+    #[serde(default = "lin_checksum_default", deserialize_with = "lin_checksum")]
+    pub checksum: u8,
+    pub max_length: Option<u8>,
+}
+
+/// The profile to open and initialize a whole device.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceProfile {
+    pub device: DeviceSection,
+    /// The bitrate configuration file that merged into the default timing tables,
+    /// the relative path is resolved against the current directory when the profile is parsed from string.
+    pub bitrate_cfg: Option<PathBuf>,
+    #[serde(default)]
+    pub can: Vec<CanChannelProfile>,
+    #[serde(default)]
+    pub lin: Vec<LinChannelProfile>,
+}
+
+impl DeviceProfile {
+    /// Load the profile from YAML file, the relative `bitrate_cfg` is resolved against the directory of file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ZCanError> {
+        let path = path.as_ref();
+        let data = read_to_string(path)
+            .map_err(|e| ZCanError::ConfigurationError(format!("Unable to read `{}`: {:?}", path.display(), e)))?;
+        let mut profile: Self = data.parse()?;
+        if let (Some(cfg), Some(dir)) = (profile.bitrate_cfg.as_mut(), path.parent()) {
+            if cfg.is_relative() {
+                *cfg = dir.join(&cfg);
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Load the profile from YAML file, then open and initialize the device.
+    pub fn open_file<P: AsRef<Path>>(path: P) -> Result<ZCanDriver, ZCanError> {
+        Self::from_file(path)?.open()
+    }
+
+    #[inline]
+    pub fn device_type(&self) -> Result<ZCanDeviceType, ZCanError> {
+        ZCanDeviceType::try_from(self.device.dev_type)
+            .map_err(|_| ZCanError::ConfigurationError(format!("profile - device type: {} is invalid", self.device.dev_type)))
+    }
+
+    #[inline]
+    pub fn derive_info(&self) -> Option<DeriveInfo> {
+        self.device.derive.map(|v| DeriveInfo::new(v.canfd, v.channels))
+    }
+
+    /// Check the profile against the capabilities of device type.
+    pub fn validate(&self) -> Result<(), ZCanError> {
+        let error = |msg: String| Err(ZCanError::ConfigurationError(format!("profile - {}", msg)));
+        let dev_type = self.device_type()?;
+        let derive = self.derive_info();
+        let canfd = derive.as_ref().map_or(dev_type.canfd_support(), |v| v.canfd());
+
+        let channels = derive.as_ref().map_or(dev_type.capabilities().can_channels, |v| v.channels());
+        if self.can.len() > channels as usize {
+            return error(format!("{} CAN channels are configured, but the device has {}", self.can.len(), channels));
+        }
+        for (channel, cfg) in self.can.iter().enumerate() {
+            if !matches!(cfg.can_type, ZCanChlType::CAN) && !canfd {
+                return error(format!("CAN channel {}: device: {:?} is not supported CANFD", channel, dev_type));
+            }
+            if cfg.data_bitrate.is_some() && !canfd {
+                return error(format!("CAN channel {}: device: {:?} is not supported data bitrate", channel, dev_type));
+            }
+            if cfg.resistance.is_some() && !dev_type.has_resistance() {
+                return error(format!("CAN channel {}: device: {:?} is not supported resistance", channel, dev_type));
+            }
+        }
+
+        if self.device.receive_merge && !dev_type.capabilities().merged_receive {
+            return error(format!("device: {:?} is not supported merged receive", dev_type));
+        }
+
+        if !self.lin.is_empty() && !dev_type.lin_support() {
+            return error(format!("device: {:?} is not supported LIN", dev_type));
+        }
+        let lin_channels = dev_type.capabilities().lin_channels;
+        if self.lin.len() > lin_channels as usize {
+            return error(format!("{} LIN channels are configured, but the device has {}", self.lin.len(), lin_channels));
+        }
+        for (channel, cfg) in self.lin.iter().enumerate() {
+            if !(1000..=20000).contains(&cfg.bitrate) {
+                return error(format!("LIN channel {}: bitrate: {} is out of range 1000..=20000", channel, cfg.bitrate));
+            }
+            if let Some(v) = cfg.max_length {
+                if !(8..=64).contains(&v) {
+                    return error(format!("LIN channel {}: max length: {} is out of range 8..=64", channel, v));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the factory with `bitrate_cfg`.
+    pub fn factory(&self) -> Result<CanChlCfgFactory, ZCanError> {
+        match &self.bitrate_cfg {
+            Some(path) => CanChlCfgFactory::from_file(path),
+            None => CanChlCfgFactory::new(),
+        }
+    }
+
+    /// Create the CAN channel configurations, the factory must be kept until the channels are initialized.
+    pub fn can_chl_cfg(&self, factory: &CanChlCfgFactory) -> Result<Vec<CanChlCfg>, ZCanError> {
+        let dev_type = self.device_type()?;
+        self.can.iter()
+            .map(|v| {
+                let mut builder = factory.builder(dev_type, v.bitrate)
+                    .can_type(v.can_type)
+                    .mode(v.mode)
+                    .filter(v.filter);
+                if let Some(dbitrate) = v.data_bitrate {
+                    builder = builder.dbitrate(dbitrate);
+                }
+                if let Some(resistance) = v.resistance {
+                    builder = builder.resistance(resistance);
+                }
+                if let Some(acc_code) = v.acc_code {
+                    builder = builder.acc_code(acc_code);
+                }
+                if let Some(acc_mask) = v.acc_mask {
+                    builder = builder.acc_mask(acc_mask);
+                }
+                if let Some(brp) = v.brp {
+                    builder = builder.brp(brp);
+                }
+                builder.build()
+            })
+            .collect()
+    }
+
+    /// Create the LIN channel configurations.
+    pub fn lin_chl_cfg(&self) -> Result<Vec<ZLinChlCfg>, ZCanError> {
+        self.lin.iter()
+            .map(|v| ZLinChlCfg::new(v.mode, v.checksum, v.bitrate, v.max_length))
+            .collect()
+    }
+
+    /// Open the device and initialize the channels.
+    /// The device is searched by serial number when `index` is not set.
+    pub fn open(&self) -> Result<ZCanDriver, ZCanError> {
+        self.validate()?;
+        let factory = self.factory()?;
+        let can_cfg = self.can_chl_cfg(&factory)?;
+        let lin_cfg = self.lin_chl_cfg()?;
+
+        let mut driver = match (self.device.index, &self.device.serial) {
+            (Some(dev_idx), _) => self.open_device(dev_idx)?,
+            (None, Some(serial)) => self.find_device(serial)?,
+            (None, None) => self.open_device(0)?,
+        };
+
+        // the device is closed when any check or initialization is failed
+        if let Err(e) = self.init_device(&mut driver, can_cfg, lin_cfg) {
+            driver.close();
+            return Err(e);
+        }
+
+        Ok(driver)
+    }
+
+    fn init_device(&self, driver: &mut ZCanDriver, can_cfg: Vec<CanChlCfg>, lin_cfg: Vec<ZLinChlCfg>) -> Result<(), ZCanError> {
+        let dev_info = driver.device_info()?;
+        let (sn, channels) = (dev_info.sn(), dev_info.can_channels() as usize);
+        if let (Some(dev_idx), Some(serial)) = (self.device.index, &self.device.serial) {
+            if &sn != serial {
+                return Err(ZCanError::ConfigurationError(
+                    format!("profile - the serial number of device index {} is `{}`, expected: `{}`", dev_idx, sn, serial)
+                ));
+            }
+        }
+
+        if can_cfg.len() > channels {
+            return Err(ZCanError::ConfigurationError(
+                format!("profile - {} CAN channels are configured, but the device has {}", can_cfg.len(), channels)
+            ));
+        }
+
+        self.init_channels(driver, can_cfg, lin_cfg)
+    }
+
+    fn init_channels(&self, driver: &mut ZCanDriver, can_cfg: Vec<CanChlCfg>, lin_cfg: Vec<ZLinChlCfg>) -> Result<(), ZCanError> {
+        if !can_cfg.is_empty() {
+            driver.init_can_chl(can_cfg)?;
+        }
+        if !lin_cfg.is_empty() {
+            driver.init_lin_chl(lin_cfg)?;
+        }
+        if self.device.receive_merge {
+            driver.set_receive_merge(true)?;
+        }
+        Ok(())
+    }
+
+    fn open_device(&self, dev_idx: u32) -> Result<ZCanDriver, ZCanError> {
+        let mut driver = ZCanDriver::new(self.device.dev_type, dev_idx, self.derive_info())?;
+        driver.open()?;
+        Ok(driver)
+    }
+
+    fn find_device(&self, serial: &str) -> Result<ZCanDriver, ZCanError> {
+        for dev_idx in 0..SERIAL_SEARCH_MAX {
+            // the device indexes may be not continuous
+            let mut driver = match self.open_device(dev_idx) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            if matches!(driver.device_info(), Ok(v) if v.sn() == serial) {
+                return Ok(driver);
+            }
+            driver.close();
+        }
+
+        Err(ZCanError::ConfigurationError(format!("profile - the device with serial number `{}` is not found", serial)))
+    }
+}
+
+impl FromStr for DeviceProfile {
+    type Err = ZCanError;
+
+    /// Parse the YAML profile.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_yaml::from_str(s)
+            .map_err(|e| ZCanError::ConfigurationError(format!("Error parsing profile: {}", e)))
+    }
+}
+
+fn named<'de, D: Deserializer<'de>, T: Copy>(deserializer: D, values: &[(&str, T)]) -> Result<T, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    values.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(&name))
+        .map(|(_, v)| *v)
+        .ok_or_else(|| D::Error::custom(format!(
+            "unknown value `{}`, expected: {}", name, values.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(", ")
+        )))
+}
+
+fn can_type<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ZCanChlType, D::Error> {
+    named(deserializer, &[
+        ("can", ZCanChlType::CAN),
+        ("canfd_iso", ZCanChlType::CANFD_ISO),
+        ("canfd_non_iso", ZCanChlType::CANFD_NON_ISO),
+    ])
+}
+
+fn can_mode<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ZCanChlMode, D::Error> {
+    named(deserializer, &[("normal", ZCanChlMode::Normal), ("listen_only", ZCanChlMode::ListenOnly)])
+}
+
+fn filter_type<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ZCanFilterType, D::Error> {
+    named(deserializer, &[("double", ZCanFilterType::Double), ("single", ZCanFilterType::Single)])
+}
+
+fn lin_mode<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    named(deserializer, &[("slave", ZLinMode::Slave as u8), ("master", ZLinMode::Master as u8)])
+}
+
+fn lin_checksum<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    named(deserializer, &[
+        ("classic", ZLinCheckSumMode::Classic as u8),
+        ("enhance", ZLinCheckSumMode::Enhance as u8),
+        ("auto", ZLinCheckSumMode::Auto as u8),
+    ])
+}
+
+#[inline]
+fn lin_checksum_default() -> u8 {
+    ZLinCheckSumMode::Enhance as u8
+}
@@ -2,18 +2,16 @@ use std::sync::Arc;
 use dlopen2::symbor::Container;
 use zlgcan_common::can::{CanChlCfg, CanMessage, ZCanChlError, ZCanChlStatus, ZCanFdFrameV2, ZCanFrameType, ZCanFrameV3};
 use zlgcan_common::cloud::{ZCloudGpsFrame, ZCloudServerInfo, ZCloudUserData};
+use zlgcan_common::data::DataObject;
 use zlgcan_common::device::{DeriveInfo, Handler, ZCanDeviceType, ZCanError, ZChannelContext, ZDeviceContext, ZDeviceInfo};
 use zlgcan_common::lin::{ZLinChlCfg, ZLinDataType, ZLinFrame, ZLinFrameDataUnion, ZLinPublish, ZLinPublishEx, ZLinSubscribe};
 use zlgcan_common::TryFromIterator;
 use crate::api::{ZCanApi, ZCloudApi, ZDeviceApi, ZLinApi};
 use crate::api::windows::Api;
+use crate::driver::backend::reinit_can_chl_with;
+use crate::driver::library::{load_library, LibraryFamily};
 use crate::driver::ZDevice;
 
-#[cfg(target_arch = "x86")]
-const LIB_PATH: &str = "library/windows/x86/";
-#[cfg(target_arch = "x86_64")]
-const LIB_PATH: &str = "library/windows/x86_64/";
-
 #[derive(Clone)]
 pub struct ZCanDriver {
     pub(crate) handler:    Option<Handler>,
@@ -21,23 +19,25 @@ pub struct ZCanDriver {
     pub(crate) dev_type:   ZCanDeviceType,
     pub(crate) dev_idx:    u32,
     pub(crate) derive:     Option<DeriveInfo>,
+    pub(crate) merge:      bool,
+}
+
+impl ZCanDriver {
+    fn reinit_can_chl(api: &Api, dev_hdl: &mut Handler, channel: u8, cfg: &CanChlCfg) -> Result<(), ZCanError> {
+        reinit_can_chl_with(
+            dev_hdl,
+            channel,
+            |context| api.reset_can_chl(context),
+            |context| api.init_can_chl(context, cfg),
+        )
+    }
 }
 
 impl ZDevice for ZCanDriver {
     fn new(dev_type: u32, dev_idx: u32, derive: Option<DeriveInfo>) -> Result<Self, ZCanError> where Self: Sized {
-        let libpath = match dotenvy::from_filename("zcan.env") {
-            Ok(_) => match std::env::var("ZCAN_LIBRARY") {
-                Ok(v) => format!("{}/{}", v, LIB_PATH),
-                Err(_) => LIB_PATH.into(),
-            },
-            Err(_) => LIB_PATH.into(),
-        };
-        let api =  Arc::new(unsafe {
-            Container::load(format!("{}zlgcan.dll", libpath))
-                .map_err(|_| ZCanError::LibraryLoadFailed(libpath))
-        }?);
         let dev_type = ZCanDeviceType::try_from(dev_type)?;
-        Ok(Self { handler: Default::default(), api, dev_type, dev_idx, derive })
+        let api = load_library(LibraryFamily::ZLGCAN)?;
+        Ok(Self { handler: Default::default(), api, dev_type, dev_idx, derive, merge: Default::default() })
     }
 
     fn device_type(&self) -> ZCanDeviceType {
@@ -74,7 +74,8 @@ impl ZDevice for ZCanDriver {
             }
 
             self.api.close(handler.device_context()).unwrap_or_else(|e| log::warn!("{}", e));
-            self.handler = None
+            self.handler = None;
+            self.merge = false;
         }
 
     }
@@ -108,15 +109,7 @@ impl ZDevice for ZCanDriver {
                         break;
                     }
 
-                    if let Some(v) = dev_hdl.find_can(idx) {
-                        self.api.reset_can_chl(&v).unwrap_or_else(|e| log::warn!("{}", e));
-                        dev_hdl.remove_can(idx);
-                    }
-
-                    let mut context =  ZChannelContext::new(dev_hdl.device_context().clone(), idx, None);
-                    self.api.init_can_chl(&mut context, cfg)?;
-
-                    dev_hdl.add_can(idx, context);
+                    Self::reinit_can_chl(&self.api, dev_hdl, idx, cfg)?;
                 }
                 Ok(())
             },
@@ -124,6 +117,19 @@ impl ZDevice for ZCanDriver {
         }
     }
 
+    fn init_can_channel(&mut self, channel: u8, cfg: CanChlCfg) -> Result<(), ZCanError> {
+        match &mut self.handler {
+            Some(dev_hdl) => {
+                let channels = dev_hdl.device_info().can_channels();
+                if channel >= channels {
+                    return Err(ZCanError::ConfigurationError(format!("the channel: {} is out of channels: {}", channel, channels)));
+                }
+                Self::reinit_can_chl(&self.api, dev_hdl, channel, &cfg)
+            },
+            None => Err(ZCanError::DeviceNotOpened),
+        }
+    }
+
     fn reset_can_chl(&mut self, channel: u8) -> Result<(), ZCanError> {
         match &mut self.handler {
             Some(dev_hdl) => {
@@ -166,13 +172,14 @@ impl ZDevice for ZCanDriver {
 
     fn receive_can(&self, channel: u8, size: u32, timeout: Option<u32>) -> Result<Vec<CanMessage>, ZCanError> {
         let timeout = timeout.unwrap_or(u32::MAX);
-        let frames = self.can_handler(channel, |context| {
-            self.api.receive_can(context, size, timeout, |frames, size| {
+        self.can_handler(channel, |context| {
+            let frames = self.api.receive_can(context, size, timeout, |frames, size| {
                 frames.resize_with(size, ZCanFrameV3::default);
-            })
-        })?;
-
-        Vec::try_from_iter(frames, self.timestamp(channel)?)
+            })?;
+            let mut frames = Vec::try_from_iter(frames, context.timestamp())?;
+            context.update_timestamp(&mut frames);
+            Ok(frames)
+        })
     }
 
     fn transmit_can(&self, channel: u8, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
@@ -184,13 +191,14 @@ impl ZDevice for ZCanDriver {
 
     fn receive_canfd(&self, channel: u8, size: u32, timeout: Option<u32>) -> Result<Vec<CanMessage>, ZCanError> {
         let timeout = timeout.unwrap_or(u32::MAX);
-        let frames = self.can_handler(channel, |context| {
-            self.api.receive_canfd(context, size, timeout, |frames, size| {
+        self.can_handler(channel, |context| {
+            let frames = self.api.receive_canfd(context, size, timeout, |frames, size| {
                 frames.resize_with(size, ZCanFdFrameV2::default);
-            })
-        })?;
-
-        Vec::try_from_iter(frames, self.timestamp(channel)?)
+            })?;
+            let mut frames = Vec::try_from_iter(frames, context.timestamp())?;
+            context.update_timestamp(&mut frames);
+            Ok(frames)
+        })
     }
 
     fn transmit_canfd(&self, channel: u8, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
@@ -200,6 +208,45 @@ impl ZDevice for ZCanDriver {
         })
     }
 
+    fn set_receive_merge(&mut self, enable: bool) -> Result<(), ZCanError> {
+        if !self.dev_type.capabilities().merged_receive {
+            return Err(ZCanError::MethodNotSupported);
+        }
+        match &self.handler {
+            Some(dev_hdl) => {
+                let context = ZChannelContext::new(*dev_hdl.device_context(), 0, None);
+                self.api.set_receive_merge(&context, enable)?;
+                self.merge = enable;
+                Ok(())
+            },
+            None => Err(ZCanError::DeviceNotOpened),
+        }
+    }
+
+    fn receive_merge(&self) -> bool {
+        self.merge
+    }
+
+    fn receive_merged(&self, size: u32, timeout: Option<u32>) -> Result<Vec<DataObject>, ZCanError> {
+        if !self.merge {
+            return Err(ZCanError::ConfigurationError("the merged receive is not enabled".to_string()));
+        }
+
+        self.device_handler(|hdl| {
+            // any opened channel is ok, the frames of all channels are received
+            let context = hdl.can_channels().iter()
+                .chain(hdl.lin_channels().iter())
+                .min_by_key(|(channel, _)| **channel)
+                .map(|(_, context)| context)
+                .ok_or(ZCanError::ChannelNotOpened)?;
+            let objects = self.api.receive_merged(context, size, timeout.unwrap_or(u32::MAX))?;
+            let mut objects = Vec::try_from_iter(objects, context.timestamp())?;
+            hdl.update_data_timestamp(&mut objects);
+            objects.sort_by_key(DataObject::timestamp);
+            Ok(objects)
+        })
+    }
+
     fn init_lin_chl(&mut self, cfg: Vec<ZLinChlCfg>) -> Result<(), ZCanError> {
         if !self.dev_type.lin_support() {
             return Err(ZCanError::MethodNotSupported);
@@ -0,0 +1,549 @@
+//! The ASC(Vector ASCII) trace file, such as:
+//! ```text
+//! date Sun Oct 18 10:00:00.000 am 2026
+//! base hex  timestamps absolute
+//! internal events logged
+//! Begin Triggerblock Sun Oct 18 10:00:00.000 am 2026
+//!    0.000000 Start of measurement
+//!    0.001000 1  7DF             Tx   d 8 02 10 01 00 00 00 00 00
+//!    0.002000 CANFD   2 Rx       18DAF110x                                  1 0 9 12 02 50 01 00 ...
+//! End TriggerBlock
+//! ```
+//! The channel of trace starts from 1, the time of event is the seconds since the start of measurement.
+//! The date is written and read as UTC.
+//!
+//! The event lines are only formatted by this module, the `Display` of `CanMessage` is not an ASC line.
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+use isotp_rs::device::Listener;
+use zlgcan_common::can::{len_to_dlc, CanMessage};
+use zlgcan_common::error::ZCanError;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const CANFD_EDL: u32 = 1 << 12;
+const CANFD_BRS: u32 = 1 << 13;
+const CANFD_ESI: u32 = 1 << 14;
+
+/// The days since UNIX epoch to the date(year, month, day).
+pub(super) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + (month <= 2) as i64, month as u32, day as u32)
+}
+
+/// The date(year, month, day) to the days since UNIX epoch.
+pub(super) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Format the time(µs) since UNIX epoch as the date of trace, such as `Sun Oct 18 10:00:00.000 am 2026`.
+fn format_date(time: u64) -> String {
+    let secs = time / 1_000_000;
+    let days = (secs / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let (hour, minute, second) = (secs % 86_400 / 3600, secs % 3600 / 60, secs % 60);
+    let (hour, meridiem) = match hour {
+        0 => (12, "am"),
+        1..=11 => (hour, "am"),
+        12 => (12, "pm"),
+        _ => (hour - 12, "pm"),
+    };
+
+    format!("{} {} {:02} {:02}:{:02}:{:02}.{:03} {} {}",
+            WEEKDAYS[(days + 4).rem_euclid(7) as usize],
+            MONTHS[month as usize - 1],
+            day,
+            hour, minute, second, time / 1000 % 1000,
+            meridiem,
+            year,
+    )
+}
+
+/// Parse the date of trace to the time(µs) since UNIX epoch, the 12-hour and 24-hour clock are supported.
+fn parse_date(date: &str) -> Option<u64> {
+    let items = date.split_whitespace().collect::<Vec<_>>();
+    let (month, day, time, meridiem, year) = match items.as_slice() {
+        [_, month, day, time, meridiem, year] => (*month, *day, *time, Some(*meridiem), *year),
+        [_, month, day, time, year] => (*month, *day, *time, None, *year),
+        _ => return None,
+    };
+
+    let month = MONTHS.iter().position(|v| v.eq_ignore_ascii_case(month))? as u32 + 1;
+    let day = day.parse::<u32>().ok()?;
+    let year = year.parse::<i64>().ok()?;
+    let (time, millis) = time.split_once('.').unwrap_or((time, "0"));
+    let millis = format!("{:0<3}", millis).get(..3)?.parse::<u64>().ok()?;
+    let mut hms = time.split(':').map(|v| v.parse::<u64>().ok());
+    let (hour, minute, second) = (hms.next()??, hms.next()??, hms.next()??);
+    let hour = match meridiem {
+        Some(v) if v.eq_ignore_ascii_case("am") => hour % 12,
+        Some(v) if v.eq_ignore_ascii_case("pm") => hour % 12 + 12,
+        Some(_) => return None,
+        None => hour,
+    };
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(((days * 86_400 + hour * 3600 + minute * 60 + second) * 1000 + millis) * 1000)
+}
+
+/// Format the time(µs) as seconds with microseconds.
+#[inline]
+fn format_time(time: u64) -> String {
+    format!("{}.{:06}", time / 1_000_000, time % 1_000_000)
+}
+
+/// Parse the seconds of event to microseconds.
+fn parse_time(time: &str) -> Option<u64> {
+    let (secs, micros) = time.split_once('.').unwrap_or((time, "0"));
+    let secs = secs.parse::<u64>().ok()?;
+    let micros = format!("{:0<6}", micros).get(..6)?.parse::<u64>().ok()?;
+    Some(secs * 1_000_000 + micros)
+}
+
+/// Format the message as an event line, the `start` is the time(µs) when measurement started.
+/// It's the only ASC formatter, keep the reader(`parse_line`) in sync with it.
+fn format_line(message: &CanMessage, start: u64) -> String {
+    let time = format_time(message.timestamp().saturating_sub(start));
+    let channel = message.channel() as u16 + 1;
+    if message.is_error_frame() {
+        return format!("{:>11} {:<2} ErrorFrame", time, channel);
+    }
+
+    let id = format!("{:X}{}", message.id().as_raw(), if message.is_extended() { "x" } else { "" });
+    let direct = match message.direct() {
+        Direct::Transmit => "Tx",
+        Direct::Receive => "Rx",
+    };
+    let data = message.data().iter()
+        .map(|v| format!("{:02X}", v))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let line = if message.is_can_fd() {
+        let mut flags = CANFD_EDL;
+        if message.is_bitrate_switch() { flags |= CANFD_BRS; }
+        if message.is_esi() { flags |= CANFD_ESI; }
+        format!("{:>11} CANFD {:>3} {:<4} {:>8} {:>32} {} {} {:x} {:>2} {} {:>8} {:>4} {:>8x} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                time, channel, direct, id, "",
+                message.is_bitrate_switch() as u8,
+                message.is_esi() as u8,
+                len_to_dlc(message.length()).unwrap_or_default(),
+                message.length(),
+                data,
+                0,      // message duration
+                0,      // message length
+                flags,
+                0,      // crc
+                0, 0, 0, 0,     // bit timing configurations
+        )
+    }
+    else if message.is_remote() {
+        format!("{:>11} {:<2} {:<15} {:<4} r {:x}", time, channel, id, direct, message.length())
+    }
+    else {
+        format!("{:>11} {:<2} {:<15} {:<4} d {:x} {}", time, channel, id, direct, message.length(), data)
+    };
+
+    line.trim_end().to_string()
+}
+
+/// Parse the identifier with `x` suffix for extended frame.
+fn parse_id(id: &str, radix: u32) -> Option<Id> {
+    match id.strip_suffix(['x', 'X']) {
+        Some(id) => u32::from_str_radix(id, radix).ok().map(|v| Id::from_bits(v, true)),
+        None => u32::from_str_radix(id, radix).ok().map(|v| Id::from_bits(v, false)),
+    }
+}
+
+fn parse_direct(direct: &str) -> Option<Direct> {
+    match direct {
+        "Rx" => Some(Direct::Receive),
+        "Tx" => Some(Direct::Transmit),
+        _ => None,
+    }
+}
+
+fn parse_data(items: &[&str], len: usize, radix: u32) -> Result<Vec<u8>, String> {
+    if items.len() < len {
+        return Err("missing data".to_string());
+    }
+    items[..len].iter()
+        .map(|v| u8::from_str_radix(v, radix).map_err(|_| "invalid data".to_string()))
+        .collect()
+}
+
+/// Parse an event line, return the time of event and the message if it is a CAN(FD) frame.
+/// Return `None` if the line is not an event.
+fn parse_line(line: &str, radix: u32) -> Result<Option<(u64, Option<CanMessage>)>, String> {
+    let items = line.split_whitespace().collect::<Vec<_>>();
+    let Some(time) = items.first().and_then(|v| parse_time(v)) else {
+        return Ok(None);
+    };
+
+    let message = match items.get(1..) {
+        Some(["CANFD", channel, direct, id, rest @ ..]) => {
+            let (Ok(channel), Some(direct), Some(id)) = (channel.parse::<u8>(), parse_direct(direct), parse_id(id, radix)) else {
+                return Ok(Some((time, None)));
+            };
+            // the symbolic name is optional
+            let rest = match rest.first() {
+                Some(&"0") | Some(&"1") => rest,
+                _ => rest.get(1..).unwrap_or_default(),
+            };
+            let [brs, esi, _, len, rest @ ..] = rest else {
+                return Err("missing items".to_string());
+            };
+            let len = len.parse::<usize>().map_err(|_| "invalid data length")?;
+            let data = parse_data(rest, len, radix)?;
+            let flags = rest.get(len + 2)
+                .and_then(|v| u32::from_str_radix(v, 16).ok())
+                .unwrap_or(CANFD_EDL);
+            let mut message = CanMessage::new(id, &data)
+                .ok_or("invalid data length")?;
+            message.try_set_can_fd(flags & CANFD_EDL > 0)
+                .map_err(|e| e.to_string())?
+                .set_bitrate_switch(*brs == "1")
+                .set_esi(*esi == "1")
+                .set_channel(channel.saturating_sub(1))
+                .set_direct(direct);
+            Some(message)
+        },
+        Some([channel, "ErrorFrame", ..]) => {
+            let Ok(channel) = channel.parse::<u8>() else {
+                return Ok(Some((time, None)));
+            };
+            let mut message = CanMessage::new(Id::from_bits(0, false), &[])
+                .ok_or("invalid data length")?;
+            message.set_error_frame(true)
+                .set_channel(channel.saturating_sub(1))
+                .set_direct(Direct::Receive);
+            Some(message)
+        },
+        Some([channel, id, direct, kind, rest @ ..]) => {
+            let (Ok(channel), Some(id), Some(direct)) = (channel.parse::<u8>(), parse_id(id, radix), parse_direct(direct)) else {
+                return Ok(Some((time, None)));
+            };
+            let len = rest.first()
+                .map(|v| u8::from_str_radix(v, 16).map_err(|_| "invalid DLC"))
+                .transpose()?
+                .unwrap_or_default()
+                .min(8) as usize;
+            let mut message = match *kind {
+                "d" => CanMessage::new(id, &parse_data(rest.get(1..).unwrap_or_default(), len, radix)?),
+                "r" => CanMessage::new_remote(id, len),
+                _ => return Ok(Some((time, None))),
+            }.ok_or("invalid data length")?;
+            message.set_channel(channel.saturating_sub(1))
+                .set_direct(direct);
+            Some(message)
+        },
+        _ => None,
+    };
+
+    Ok(Some((time, message)))
+}
+
+/// The writer of ASC trace, it's a listener that can be registered to `ZCanSync` or `ZCanAsync`.
+///
+/// The header is written with the timestamp of the first message,
+/// and the trigger block is ended when the writer is dropped.
+pub struct AscWriter<W: Write> {
+    writer: W,
+    start: Option<u64>,
+}
+
+impl AscWriter<BufWriter<File>> {
+    /// Create the trace file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, ZCanError> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| ZCanError::ConfigurationError(format!("create ASC file `{}` failed: {}", path.display(), e)))?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> AscWriter<W> {
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self { writer, start: Default::default() }
+    }
+
+    /// Write a message, the timestamp of message must not be earlier than the first one.
+    pub fn write(&mut self, message: &CanMessage) -> Result<(), ZCanError> {
+        let start = match self.start {
+            Some(v) => v,
+            None => {
+                // the date has milliseconds only
+                let start = message.timestamp() / 1000 * 1000;
+                let date = format_date(start);
+                write!(self.writer, "date {}\nbase hex  timestamps absolute\ninternal events logged\nBegin Triggerblock {}\n{:>11} Start of measurement\n",
+                       date, date, format_time(0))
+                    .map_err(Self::error)?;
+                *self.start.insert(start)
+            },
+        };
+
+        writeln!(self.writer, "{}", format_line(message, start))
+            .map_err(Self::error)
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), ZCanError> {
+        self.writer.flush()
+            .map_err(Self::error)
+    }
+
+    #[inline]
+    fn error(e: std::io::Error) -> ZCanError {
+        ZCanError::Other(format!("write ASC file failed: {}", e))
+    }
+}
+
+impl<W: Write> Drop for AscWriter<W> {
+    fn drop(&mut self) {
+        if self.start.is_some() {
+            if let Err(e) = writeln!(self.writer, "End TriggerBlock") {
+                log::warn!("ZLGCAN - error: {} when ending ASC file", e);
+            }
+        }
+        self.flush()
+            .unwrap_or_else(|e| log::warn!("{}", e));
+    }
+}
+
+impl<W: Write + Send> Listener<u8, u32, CanMessage> for AscWriter<W> {
+    fn on_frame_transmitting(&mut self, _: u8, frame: &CanMessage) {
+        let mut frame = frame.clone();
+        // the message to transmit may be not stamped
+        if frame.timestamp() == 0 {
+            frame.set_timestamp(None);
+        }
+        frame.set_direct(Direct::Transmit);
+        self.write(&frame)
+            .and_then(|_| self.flush())
+            .unwrap_or_else(|e| log::warn!("{}", e));
+    }
+
+    fn on_frame_transmitted(&mut self, _: u8, _: u32) {}
+
+    fn on_frame_received(&mut self, _: u8, frames: &[CanMessage]) {
+        frames.iter()
+            .try_for_each(|v| self.write(v))
+            .and_then(|_| self.flush())
+            .unwrap_or_else(|e| log::warn!("{}", e));
+    }
+}
+
+/// The reader of ASC trace, the messages are stamped with the date of trace and the time of event.
+pub struct AscReader<R: BufRead> {
+    lines: Lines<R>,
+    line: usize,
+    start: u64,
+    radix: u32,
+    relative: bool,
+    last: u64,
+}
+
+impl AscReader<BufReader<File>> {
+    /// Open the trace file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ZCanError> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| ZCanError::ConfigurationError(format!("open ASC file `{}` failed: {}", path.display(), e)))?;
+        Ok(Self::new(BufReader::new(file)))
+    }
+}
+
+impl<R: BufRead> AscReader<R> {
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: Default::default(),
+            start: Default::default(),
+            radix: 16,
+            relative: false,
+            last: Default::default(),
+        }
+    }
+
+    /// The time(µs) since UNIX epoch when measurement started, it's `0` before the date is read.
+    #[inline]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    fn parse_header(&mut self, line: &str) {
+        if let Some(date) = line.strip_prefix("date ") {
+            match parse_date(date) {
+                Some(v) => self.start = v,
+                None => log::warn!("ZLGCAN - unsupported date: `{}` of ASC file", date),
+            }
+        }
+        else if line.starts_with("base ") {
+            let items = line.split_whitespace().collect::<Vec<_>>();
+            if items.contains(&"dec") {
+                self.radix = 10;
+            }
+            self.relative = items.contains(&"relative");
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for AscReader<R> {
+    type Item = Result<CanMessage, ZCanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(v) => v,
+                Err(e) => return Some(Err(ZCanError::Other(format!("read ASC file failed: {}", e)))),
+            };
+            self.line += 1;
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            match parse_line(line, self.radix) {
+                Ok(Some((time, message))) => {
+                    let time = if self.relative { self.last + time } else { time };
+                    self.last = time;
+                    if let Some(mut message) = message {
+                        message.set_timestamp(Some(self.start + time));
+                        return Some(Ok(message));
+                    }
+                },
+                Ok(None) => self.parse_header(line),
+                Err(e) => return Some(Err(ZCanError::Other(format!("{} at line {} of ASC file", e, self.line)))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+    use zlgcan_common::can::CanMessage;
+    use super::{format_date, format_line, parse_date, AscReader, AscWriter};
+
+    #[test]
+    fn asc_date() {
+        let time = 1_792_317_600_123_000;
+        assert_eq!(format_date(time), "Sun Oct 18 10:00:00.123 am 2026");
+        assert_eq!(parse_date("Sun Oct 18 10:00:00.123 am 2026"), Some(time));
+        assert_eq!(parse_date("Sun Oct 18 10:00:00.123 2026"), Some(time));
+        assert_eq!(format_date(1_792_324_800_000_000), "Sun Oct 18 12:00:00.000 pm 2026");
+        assert_eq!(parse_date("Sun Oct 18 02:00:00 pm 2026"), Some(1_792_332_000_000_000));
+        assert_eq!(format_date(0), "Thu Jan 01 12:00:00.000 am 1970");
+        assert_eq!(parse_date("Thu Jan 01 12:00:00.000 am 1970"), Some(0));
+        assert_eq!(parse_date("So Okt 18 10:00:00.000 2026"), None);
+    }
+
+    #[test]
+    fn asc_line() {
+        let mut message = CanMessage::new(Id::from_bits(0x7DF, false), &[0x02, 0x10, 0x01]).unwrap();
+        message.set_timestamp(Some(1_001_000));
+        assert_eq!(format_line(&message, 0), "   1.001000 1  7DF             Tx   d 3 02 10 01");
+
+        let mut message = CanMessage::new(Id::from_bits(0x18DAF110, true), &[0x01; 12]).unwrap();
+        message.set_timestamp(Some(2_000_000))
+            .set_channel(1)
+            .set_bitrate_switch(true)
+            .set_direct(Direct::Receive);
+        let line = format_line(&message, 1_000_000);
+        assert!(line.starts_with("   1.000000 CANFD   2 Rx   18DAF110x"), "{}", line);
+        assert!(line.contains(" 1 0 9 12 01 01 01 01 01 01 01 01 01 01 01 01 "), "{}", line);
+    }
+
+    #[test]
+    fn asc_write_read() -> anyhow::Result<()> {
+        let mut can = CanMessage::new(Id::from_bits(0x7E0, false), &[0x02, 0x3E, 0x00]).unwrap();
+        can.set_timestamp(Some(1_700_000_000_123_456));
+        let mut remote = CanMessage::new_remote(Id::from_bits(0x1234, true), 8).unwrap();
+        remote.set_timestamp(Some(1_700_000_000_200_000))
+            .set_channel(2);
+        let mut canfd = CanMessage::new(Id::from_bits(0x7E8, false), &(0..16).collect::<Vec<u8>>()).unwrap();
+        canfd.set_timestamp(Some(1_700_000_001_000_001))
+            .set_channel(1)
+            .set_esi(true)
+            .set_direct(Direct::Receive);
+        let mut error = CanMessage::new(Id::from_bits(0, false), &[]).unwrap();
+        error.set_timestamp(Some(1_700_000_002_000_000))
+            .set_error_frame(true)
+            .set_direct(Direct::Receive);
+        let messages = vec![can, remote, canfd, error];
+
+        let mut buffer = Vec::new();
+        {
+            let mut writer = AscWriter::new(&mut buffer);
+            messages.iter().try_for_each(|v| writer.write(v))?;
+        }
+        let text = String::from_utf8(buffer)?;
+        assert!(text.starts_with("date Tue Nov 14 10:13:20.123 pm 2023\n"), "{}", text);
+        assert!(text.ends_with("End TriggerBlock\n"), "{}", text);
+
+        let mut reader = AscReader::new(text.as_bytes());
+        let results = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(reader.start(), 1_700_000_000_123_000);
+        assert_eq!(results, messages);
+        for (result, message) in results.iter().zip(&messages) {
+            assert_eq!(result.timestamp(), message.timestamp());
+            assert_eq!(result.channel(), message.channel());
+            assert_eq!(result.is_can_fd(), message.is_can_fd());
+            assert_eq!(result.is_remote(), message.is_remote());
+            assert_eq!(result.is_esi(), message.is_esi());
+            assert_eq!(result.direct(), message.direct());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn asc_read_canoe() -> anyhow::Result<()> {
+        let text = "\
+date Sun Oct 18 10:00:00.000 2026
+base dec  timestamps relative
+no internal events logged
+// version 13.0.0
+Begin Triggerblock Sun Oct 18 10:00:00.000 2026
+   0.000000 Start of measurement
+   0.010000 1  Statistic: D 0 R 0 XD 0 XR 0 E 0 O 0 B 0.00%
+   0.010000 1  2015            Rx   d 8 2 16 1 0 0 0 0 0  Length = 240000 BitCount = 124 ID = 2015
+   0.001000 CANFD   2 Rx       2024  EngineData   0 0 a 16 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15   0   0  1000 0 0 0 0 0
+   0.001000 1  2015            TxRq d 8 2 16 1 0 0 0 0 0
+End TriggerBlock
+";
+        let results = AscReader::new(text.as_bytes()).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(results.len(), 2);
+        let start = 1_792_317_600_000_000;
+        assert_eq!(results[0].timestamp(), start + 20_000);
+        assert_eq!(results[0].id(), Id::from_bits(0x7DF, false));
+        assert_eq!(results[0].data(), [0x02, 0x10, 0x01, 0, 0, 0, 0, 0]);
+        assert_eq!((results[0].channel(), results[0].direct()), (0, Direct::Receive));
+        assert_eq!(results[1].timestamp(), start + 21_000);
+        assert_eq!(results[1].id(), Id::from_bits(0x7E8, false));
+        assert!(results[1].is_can_fd() && !results[1].is_bitrate_switch());
+        assert_eq!(results[1].data(), (0..16).collect::<Vec<u8>>());
+        assert_eq!(results[1].channel(), 1);
+
+        assert!(AscReader::new("   0.001000 1  7DF             Rx   d 8 02 10\n".as_bytes()).next().unwrap().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,828 @@
+//! The BLF(Vector binary logging format) trace file.
+//!
+//! The file starts with a header of 144 bytes, the objects follow it and are packed into zlib-compressed log containers.
+//! The `CAN_MESSAGE2`, `CAN_FD_MESSAGE_64`, `CAN_ERROR_EXT` and `LIN_MESSAGE` objects are supported,
+//! the other objects are skipped by reader.
+//! The channel of trace starts from 1, the time of object is the nanoseconds since the start of measurement.
+//! The start and stop time in header are written and read as UTC.
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+use isotp_rs::device::Listener;
+use zlgcan_common::can::{dlc_to_len, len_to_dlc, CanMessage};
+use zlgcan_common::data::DataObject;
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::lin::{protected_id, LinMessage};
+use super::asc::{civil_from_days, days_from_civil};
+
+const FILE_SIGNATURE: &[u8; 4] = b"LOGG";
+const OBJECT_SIGNATURE: &[u8; 4] = b"LOBJ";
+const FILE_HEADER_SIZE: usize = 144;
+const OBJECT_HEADER_BASE_SIZE: usize = 16;
+const OBJECT_HEADER_SIZE: usize = 32;
+const CONTAINER_HEADER_SIZE: usize = 16;
+/// The max size of uncompressed objects in a log container.
+const MAX_CONTAINER_SIZE: usize = 128 * 1024;
+
+const CAN_MESSAGE: u32 = 1;
+const LOG_CONTAINER: u32 = 10;
+const LIN_MESSAGE: u32 = 11;
+const CAN_ERROR_EXT: u32 = 73;
+const CAN_MESSAGE2: u32 = 86;
+const CAN_FD_MESSAGE_64: u32 = 101;
+
+const NO_COMPRESSION: u16 = 0;
+const ZLIB_DEFLATE: u16 = 2;
+const TIME_TEN_MICS: u32 = 0x01;
+const TIME_ONE_NANS: u32 = 0x02;
+
+const CAN_MSG_EXT: u32 = 0x8000_0000;
+const CAN_DIR_TX: u8 = 0x01;
+const CAN_REMOTE: u8 = 0x80;
+const CANFD_REMOTE: u32 = 0x0010;
+const CANFD_EDL: u32 = 0x1000;
+const CANFD_BRS: u32 = 0x2000;
+const CANFD_ESI: u32 = 0x4000;
+
+/// The time(µs) since UNIX epoch to `SYSTEMTIME`(year, month, day of week, day, hour, minute, second, milliseconds).
+fn system_time(time: u64) -> [u16; 8] {
+    let secs = time / 1_000_000;
+    let days = (secs / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+
+    [
+        year as u16, month as u16, (days + 4).rem_euclid(7) as u16, day as u16,
+        (secs % 86_400 / 3600) as u16, (secs % 3600 / 60) as u16, (secs % 60) as u16, (time / 1000 % 1000) as u16,
+    ]
+}
+
+/// The `SYSTEMTIME` to the time(µs) since UNIX epoch, `None` if the time is not set.
+fn from_system_time(time: [u16; 8]) -> Option<u64> {
+    let [year, month, _, day, hour, minute, second, millis] = time;
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let days = days_from_civil(year as i64, month as u32, day as u32) as u64;
+    let secs = days * 86_400 + hour as u64 * 3600 + minute as u64 * 60 + second as u64;
+    Some(secs * 1_000_000 + millis as u64 * 1000)
+}
+
+#[inline]
+fn u8_at(data: &[u8], pos: usize) -> Option<u8> {
+    data.get(pos).copied()
+}
+
+#[inline]
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2)?.try_into().ok().map(u16::from_le_bytes)
+}
+
+#[inline]
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4)?.try_into().ok().map(u32::from_le_bytes)
+}
+
+#[inline]
+fn u64_at(data: &[u8], pos: usize) -> Option<u64> {
+    data.get(pos..pos + 8)?.try_into().ok().map(u64::from_le_bytes)
+}
+
+/// The data of classic frame is always 8 bytes in object.
+#[inline]
+fn data8(data: &[u8]) -> [u8; 8] {
+    let mut result = [0; 8];
+    let len = data.len().min(8);
+    result[..len].copy_from_slice(&data[..len]);
+    result
+}
+
+#[inline]
+fn channel_of(channel: u8) -> u16 {
+    channel as u16 + 1
+}
+
+/// The object type and the data of `CAN_MESSAGE2`, `CAN_FD_MESSAGE_64` or `CAN_ERROR_EXT`.
+fn encode_message(message: &CanMessage) -> (u32, Vec<u8>) {
+    let mut id = message.id().as_raw();
+    if message.is_extended() {
+        id |= CAN_MSG_EXT;
+    }
+    let tx = message.direct() == Direct::Transmit;
+    let mut result = Vec::new();
+
+    if message.is_error_frame() {
+        result.extend(channel_of(message.channel()).to_le_bytes());
+        result.extend(0u16.to_le_bytes());  // length
+        result.extend(0u32.to_le_bytes());  // flags
+        result.extend([0, 0, message.length().min(8) as u8, 0]);  // ECC, position, DLC, reserved
+        result.extend(0u32.to_le_bytes());  // frame length
+        result.extend(id.to_le_bytes());
+        result.extend(0u16.to_le_bytes());  // extended flags
+        result.extend(0u16.to_le_bytes());  // reserved
+        result.extend(data8(message.data()));
+        (CAN_ERROR_EXT, result)
+    }
+    else if message.is_can_fd() {
+        let mut flags = CANFD_EDL;
+        if message.is_bitrate_switch() {
+            flags |= CANFD_BRS;
+        }
+        if message.is_esi() {
+            flags |= CANFD_ESI;
+        }
+        let len = message.length();
+        result.extend([channel_of(message.channel()) as u8, len_to_dlc(len).unwrap_or_default(), len as u8, 0]);
+        result.extend(id.to_le_bytes());
+        result.extend(0u32.to_le_bytes());  // frame length
+        result.extend(flags.to_le_bytes());
+        result.extend([0; 16]);             // bit timings, BRS offset and CRC delimiter
+        result.extend(0u16.to_le_bytes());  // bit count
+        result.extend([tx as u8, 0]);       // direction, extended data offset
+        result.extend(0u32.to_le_bytes());  // CRC
+        result.extend(&message.data()[..len]);
+        (CAN_FD_MESSAGE_64, result)
+    }
+    else {
+        let mut flags = if tx { CAN_DIR_TX } else { 0 };
+        if message.is_remote() {
+            flags |= CAN_REMOTE;
+        }
+        result.extend(channel_of(message.channel()).to_le_bytes());
+        result.extend([flags, message.length().min(8) as u8]);
+        result.extend(id.to_le_bytes());
+        result.extend(data8(message.data()));
+        result.extend(0u32.to_le_bytes());  // frame length
+        result.extend([0; 4]);              // bit count and reserved
+        (CAN_MESSAGE2, result)
+    }
+}
+
+/// The data of `LIN_MESSAGE`.
+fn encode_lin(message: &LinMessage) -> Vec<u8> {
+    let mut result = Vec::new();
+    result.extend(channel_of(message.channel()).to_le_bytes());
+    result.extend([message.id(), message.length() as u8]);
+    result.extend(data8(message.data()));
+    result.extend([0; 4]);              // FSM id, FSM state, header time and full time
+    result.extend((message.checksum() as u16).to_le_bytes());
+    result.extend([(message.direct() == Direct::Transmit) as u8, 0]);
+    result
+}
+
+/// Decode the data of object, `None` if the type is not supported.
+fn decode_object(kind: u32, data: &[u8]) -> Result<Option<DataObject>, String> {
+    let invalid = || format!("invalid object: {}", kind);
+    let direct = |tx: bool| if tx { Direct::Transmit } else { Direct::Receive };
+
+    let object = match kind {
+        CAN_MESSAGE | CAN_MESSAGE2 => {
+            let (Some(channel), Some(flags), Some(dlc), Some(id), Some(payload)) =
+                (u16_at(data, 0), u8_at(data, 2), u8_at(data, 3), u32_at(data, 4), data.get(8..16)) else {
+                return Err(invalid());
+            };
+            let id = Id::from_bits(id & !CAN_MSG_EXT, id & CAN_MSG_EXT > 0);
+            let len = (dlc as usize).min(8);
+            let mut message = if flags & CAN_REMOTE > 0 {
+                CanMessage::new_remote(id, len)
+            }
+            else {
+                CanMessage::new(id, &payload[..len])
+            }.ok_or_else(invalid)?;
+            message.set_channel((channel as u8).saturating_sub(1))
+                .set_direct(direct(flags & CAN_DIR_TX > 0));
+            DataObject::Can(message)
+        },
+        CAN_FD_MESSAGE_64 => {
+            let (Some(channel), Some(dlc), Some(valid), Some(id), Some(flags), Some(dir)) =
+                (u8_at(data, 0), u8_at(data, 1), u8_at(data, 2), u32_at(data, 4), u32_at(data, 12), u8_at(data, 34)) else {
+                return Err(invalid());
+            };
+            let id = Id::from_bits(id & !CAN_MSG_EXT, id & CAN_MSG_EXT > 0);
+            let fd = flags & CANFD_EDL > 0;
+            let len = if fd { dlc_to_len(dlc & 0x0F).unwrap_or_default() } else { (dlc as usize).min(8) };
+            let mut message = if flags & CANFD_REMOTE > 0 {
+                CanMessage::new_remote(id, len)
+            }
+            else {
+                let payload = data.get(40..40 + (valid as usize).min(len))
+                    .ok_or_else(invalid)?;
+                CanMessage::new_padded(id, payload, len)
+            }.ok_or_else(invalid)?;
+            message.try_set_can_fd(fd)
+                .map_err(|e| e.to_string())?
+                .set_bitrate_switch(flags & CANFD_BRS > 0)
+                .set_esi(flags & CANFD_ESI > 0)
+                .set_channel(channel.saturating_sub(1))
+                .set_direct(direct(dir > 0));
+            DataObject::Can(message)
+        },
+        CAN_ERROR_EXT => {
+            let (Some(channel), Some(dlc), Some(id), Some(payload)) =
+                (u16_at(data, 0), u8_at(data, 10), u32_at(data, 16), data.get(24..32)) else {
+                return Err(invalid());
+            };
+            let id = Id::from_bits(id & !CAN_MSG_EXT, id & CAN_MSG_EXT > 0);
+            let mut frame = CanMessage::new(id, &payload[..(dlc as usize).min(8)])
+                .ok_or_else(invalid)?;
+            frame.set_error_frame(true)
+                .set_channel((channel as u8).saturating_sub(1))
+                .set_direct(Direct::Receive);
+            DataObject::Error { frame, errors: Default::default() }
+        },
+        LIN_MESSAGE => {
+            let (Some(channel), Some(id), Some(dlc), Some(payload), Some(crc), Some(dir)) =
+                (u16_at(data, 0), u8_at(data, 2), u8_at(data, 3), data.get(4..12), u16_at(data, 16), u8_at(data, 18)) else {
+                return Err(invalid());
+            };
+            let mut message = LinMessage::new(protected_id(id), &payload[..(dlc as usize).min(8)])
+                .ok_or_else(invalid)?;
+            message.set_channel((channel as u8).saturating_sub(1))
+                .set_checksum(crc as u8)
+                .set_direct(direct(dir > 0));
+            DataObject::Lin(message)
+        },
+        _ => return Ok(None),
+    };
+
+    Ok(Some(object))
+}
+
+/// The writer of BLF trace, it's a listener that can be registered to `ZCanSync` or `ZCanAsync`.
+///
+/// The start of measurement is the timestamp of the first object, the objects are compressed
+/// when a log container is full or the writer is flushed, and the header is updated when flushing.
+pub struct BlfWriter<W: Write + Seek> {
+    writer: W,
+    /// the position of header in `writer`.
+    offset: u64,
+    buffer: Vec<u8>,
+    start: Option<u64>,
+    stop: u64,
+    count: u32,
+    uncompressed: u64,
+}
+
+impl BlfWriter<BufWriter<File>> {
+    /// Create the trace file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, ZCanError> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| ZCanError::ConfigurationError(format!("create BLF file `{}` failed: {}", path.display(), e)))?;
+        Self::new(BufWriter::new(file))
+    }
+}
+
+impl<W: Write + Seek> BlfWriter<W> {
+    /// Create the writer, the header is written at the current position of `writer`.
+    pub fn new(mut writer: W) -> Result<Self, ZCanError> {
+        let offset = writer.stream_position()
+            .map_err(Self::error)?;
+        let mut result = Self {
+            writer,
+            offset,
+            buffer: Default::default(),
+            start: Default::default(),
+            stop: Default::default(),
+            count: Default::default(),
+            uncompressed: FILE_HEADER_SIZE as u64,
+        };
+        result.writer.write_all(&result.header(0))
+            .map_err(Self::error)?;
+        Ok(result)
+    }
+
+    /// Write a data object, the LIN error is not supported.
+    pub fn write(&mut self, object: &DataObject) -> Result<(), ZCanError> {
+        match object {
+            DataObject::Can(v) => self.write_message(v),
+            DataObject::Error { frame, .. } => {
+                let mut frame = frame.clone();
+                frame.set_error_frame(true);
+                self.write_message(&frame)
+            },
+            DataObject::Lin(v) => self.write_lin(v),
+            DataObject::LinError { .. } => Err(ZCanError::ParamNotSupported),
+        }
+    }
+
+    /// Write a CAN or CANFD message, the error frame is written as `CAN_ERROR_EXT`.
+    pub fn write_message(&mut self, message: &CanMessage) -> Result<(), ZCanError> {
+        let (kind, data) = encode_message(message);
+        self.write_object(kind, message.timestamp(), &data)
+    }
+
+    /// Write a LIN message.
+    pub fn write_lin(&mut self, message: &LinMessage) -> Result<(), ZCanError> {
+        self.write_object(LIN_MESSAGE, message.timestamp(), &encode_lin(message))
+    }
+
+    /// Compress the buffered objects and update the header.
+    pub fn flush(&mut self) -> Result<(), ZCanError> {
+        if !self.buffer.is_empty() {
+            self.write_container(self.buffer.len())?;
+        }
+
+        let position = self.writer.stream_position()
+            .map_err(Self::error)?;
+        let header = self.header(position - self.offset);
+        self.writer.seek(SeekFrom::Start(self.offset))
+            .and_then(|_| self.writer.write_all(&header))
+            .and_then(|_| self.writer.seek(SeekFrom::Start(position)))
+            .and_then(|_| self.writer.flush())
+            .map_err(Self::error)
+    }
+
+    fn write_object(&mut self, kind: u32, timestamp: u64, data: &[u8]) -> Result<(), ZCanError> {
+        // the start time has milliseconds only
+        let start = *self.start.get_or_insert(timestamp / 1000 * 1000);
+        self.stop = self.stop.max(timestamp);
+        let size = OBJECT_HEADER_SIZE + data.len();
+
+        self.buffer.extend(OBJECT_SIGNATURE);
+        self.buffer.extend((OBJECT_HEADER_SIZE as u16).to_le_bytes());
+        self.buffer.extend(1u16.to_le_bytes());     // header version
+        self.buffer.extend((size as u32).to_le_bytes());
+        self.buffer.extend(kind.to_le_bytes());
+        self.buffer.extend(TIME_ONE_NANS.to_le_bytes());
+        self.buffer.extend(0u16.to_le_bytes());     // client index
+        self.buffer.extend(0u16.to_le_bytes());     // object version
+        self.buffer.extend((timestamp.saturating_sub(start) * 1000).to_le_bytes());
+        self.buffer.extend(data);
+        self.buffer.resize(self.buffer.len() + size % 4, 0);
+        self.count += 1;
+
+        while self.buffer.len() >= MAX_CONTAINER_SIZE {
+            self.write_container(MAX_CONTAINER_SIZE)?;
+        }
+        Ok(())
+    }
+
+    fn write_container(&mut self, len: usize) -> Result<(), ZCanError> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&self.buffer[..len])
+            .map_err(Self::error)?;
+        let compressed = encoder.finish()
+            .map_err(Self::error)?;
+        let size = OBJECT_HEADER_BASE_SIZE + CONTAINER_HEADER_SIZE + compressed.len();
+
+        let mut header = Vec::with_capacity(OBJECT_HEADER_BASE_SIZE + CONTAINER_HEADER_SIZE);
+        header.extend(OBJECT_SIGNATURE);
+        header.extend((OBJECT_HEADER_BASE_SIZE as u16).to_le_bytes());
+        header.extend(1u16.to_le_bytes());
+        header.extend((size as u32).to_le_bytes());
+        header.extend(LOG_CONTAINER.to_le_bytes());
+        header.extend(ZLIB_DEFLATE.to_le_bytes());
+        header.extend([0; 6]);
+        header.extend((len as u32).to_le_bytes());
+        header.extend([0; 4]);
+
+        self.writer.write_all(&header)
+            .and_then(|_| self.writer.write_all(&compressed))
+            .and_then(|_| self.writer.write_all(&[0; 4][..size % 4]))
+            .map_err(Self::error)?;
+        self.buffer.drain(..len);
+        self.uncompressed += (OBJECT_HEADER_BASE_SIZE + CONTAINER_HEADER_SIZE + len) as u64;
+        Ok(())
+    }
+
+    fn header(&self, file_size: u64) -> Vec<u8> {
+        let mut result = Vec::with_capacity(FILE_HEADER_SIZE);
+        result.extend(FILE_SIGNATURE);
+        result.extend((FILE_HEADER_SIZE as u32).to_le_bytes());
+        result.extend([5, 0, 0, 0, 2, 6, 8, 1]);    // application ID, version and BL API version
+        result.extend(file_size.to_le_bytes());
+        result.extend(self.uncompressed.to_le_bytes());
+        result.extend(self.count.to_le_bytes());
+        result.extend(0u32.to_le_bytes());          // objects read
+        let (start, stop) = match self.start {
+            Some(v) => (system_time(v), system_time(self.stop)),
+            None => Default::default(),
+        };
+        start.iter()
+            .chain(stop.iter())
+            .for_each(|v| result.extend(v.to_le_bytes()));
+        result.resize(FILE_HEADER_SIZE, 0);
+        result
+    }
+
+    #[inline]
+    fn error(e: std::io::Error) -> ZCanError {
+        ZCanError::Other(format!("write BLF file failed: {}", e))
+    }
+}
+
+impl<W: Write + Seek> Drop for BlfWriter<W> {
+    fn drop(&mut self) {
+        self.flush()
+            .unwrap_or_else(|e| log::warn!("{}", e));
+    }
+}
+
+impl<W: Write + Seek + Send> Listener<u8, u32, CanMessage> for BlfWriter<W> {
+    fn on_frame_transmitting(&mut self, _: u8, frame: &CanMessage) {
+        let mut frame = frame.clone();
+        // the message to transmit may be not stamped
+        if frame.timestamp() == 0 {
+            frame.set_timestamp(None);
+        }
+        frame.set_direct(Direct::Transmit);
+        self.write_message(&frame)
+            .unwrap_or_else(|e| log::warn!("{}", e));
+    }
+
+    fn on_frame_transmitted(&mut self, _: u8, _: u32) {}
+
+    fn on_frame_received(&mut self, _: u8, frames: &[CanMessage]) {
+        frames.iter()
+            .try_for_each(|v| self.write_message(v))
+            .unwrap_or_else(|e| log::warn!("{}", e));
+    }
+}
+
+/// The reader of BLF trace, the objects are stamped with the start of measurement and the time of object.
+pub struct BlfReader<R: Read> {
+    reader: R,
+    start: u64,
+    buffer: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl BlfReader<BufReader<File>> {
+    /// Open the trace file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ZCanError> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| ZCanError::ConfigurationError(format!("open BLF file `{}` failed: {}", path.display(), e)))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> BlfReader<R> {
+    /// Create the reader, the header is read from `reader`.
+    pub fn new(mut reader: R) -> Result<Self, ZCanError> {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)
+            .map_err(Self::error)?;
+        if &header[..4] != FILE_SIGNATURE {
+            return Err(ZCanError::Other("invalid BLF file signature".to_string()));
+        }
+        let size = (u32_at(&header, 4).unwrap_or_default() as usize).max(56);
+        let mut rest = vec![0; size - header.len()];
+        reader.read_exact(&mut rest)
+            .map_err(Self::error)?;
+
+        let mut start = [0; 8];
+        start.iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = u16_at(&rest, 32 + i * 2).unwrap_or_default());
+        let start = from_system_time(start)
+            .unwrap_or_else(|| {
+                log::warn!("ZLGCAN - unsupported start time of BLF file");
+                Default::default()
+            });
+
+        Ok(Self { reader, start, buffer: Default::default(), position: Default::default(), done: false })
+    }
+
+    /// The time(µs) since UNIX epoch when measurement started.
+    #[inline]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Read the next object of file into buffer, the log container is uncompressed. `false` at the end of file.
+    fn read_more(&mut self) -> Result<bool, ZCanError> {
+        let mut header = [0; OBJECT_HEADER_BASE_SIZE];
+        let mut count = 0;
+        while count < header.len() {
+            match self.reader.read(&mut header[count..]).map_err(Self::error)? {
+                0 => return Ok(false),
+                n => count += n,
+            }
+        }
+        if &header[..4] != OBJECT_SIGNATURE {
+            return Err(ZCanError::Other("invalid BLF object signature".to_string()));
+        }
+
+        let size = u32_at(&header, 8).unwrap_or_default() as usize;
+        let mut data = vec![0; size.saturating_sub(header.len()) + size % 4];
+        self.reader.read_exact(&mut data[..size.saturating_sub(header.len())])
+            .map_err(Self::error)?;
+        // the padding of last object may be omitted
+        let _ = self.reader.read_exact(&mut data[size.saturating_sub(header.len())..]);
+
+        let consumed = self.position.min(self.buffer.len());
+        self.buffer.drain(..consumed);
+        self.position -= consumed;
+
+        if u32_at(&header, 12) != Some(LOG_CONTAINER) {
+            self.buffer.extend(header);
+            self.buffer.extend(data);
+            return Ok(true);
+        }
+
+        let (Some(compression), Some(len)) = (u16_at(&data, 0), u32_at(&data, 8)) else {
+            return Err(ZCanError::Other("invalid BLF log container".to_string()));
+        };
+        let data = data.get(CONTAINER_HEADER_SIZE..size.saturating_sub(OBJECT_HEADER_BASE_SIZE))
+            .unwrap_or_default();
+        match compression {
+            NO_COMPRESSION => self.buffer.extend(data),
+            ZLIB_DEFLATE => {
+                let mut decoder = ZlibDecoder::new(data);
+                let mut result = Vec::with_capacity(len as usize);
+                decoder.read_to_end(&mut result)
+                    .map_err(Self::error)?;
+                self.buffer.extend(result);
+            },
+            _ => return Err(ZCanError::Other(format!("unsupported compression: {} of BLF file", compression))),
+        }
+        Ok(true)
+    }
+
+    /// Read and decode the next object in buffer, the unknown objects are skipped.
+    fn read_object(&mut self) -> Option<Result<DataObject, ZCanError>> {
+        loop {
+            let rest = self.buffer.get(self.position..).unwrap_or_default();
+            let size = match (rest.get(..4), u32_at(rest, 8)) {
+                (Some(signature), _) if signature != OBJECT_SIGNATURE =>
+                    return Some(Err(ZCanError::Other("invalid BLF object signature".to_string()))),
+                (Some(_), Some(size)) if (size as usize) < OBJECT_HEADER_BASE_SIZE =>
+                    return Some(Err(ZCanError::Other(format!("invalid object size: {} of BLF file", size)))),
+                (Some(_), Some(size)) if rest.len() >= size as usize => size as usize,
+                _ => {
+                    let empty = rest.is_empty();
+                    match self.read_more() {
+                        Ok(true) => continue,
+                        Ok(false) if empty => return None,
+                        Ok(false) => return Some(Err(ZCanError::Other("truncated object of BLF file".to_string()))),
+                        Err(e) => return Some(Err(e)),
+                    }
+                },
+            };
+
+            let object = &self.buffer[self.position..self.position + size];
+            self.position += size + size % 4;
+            let (Some(header_size), Some(kind), Some(flags), Some(time)) =
+                (u16_at(object, 4), u32_at(object, 12), u32_at(object, 16), u64_at(object, 24)) else {
+                return Some(Err(ZCanError::Other("invalid object header of BLF file".to_string())));
+            };
+            let time = if flags == TIME_TEN_MICS { time * 10 } else { time / 1000 };
+            let data = object.get(header_size as usize..).unwrap_or_default();
+
+            match decode_object(kind, data) {
+                Ok(Some(mut object)) => {
+                    let timestamp = Some(self.start + time);
+                    match &mut object {
+                        DataObject::Can(v) | DataObject::Error { frame: v, .. } => {
+                            v.set_timestamp(timestamp);
+                        },
+                        DataObject::Lin(v) | DataObject::LinError { message: v, .. } => {
+                            v.set_timestamp(timestamp);
+                        },
+                    }
+                    return Some(Ok(object));
+                },
+                Ok(None) => continue,
+                Err(e) => return Some(Err(ZCanError::Other(format!("{} of BLF file", e)))),
+            }
+        }
+    }
+
+    #[inline]
+    fn error(e: std::io::Error) -> ZCanError {
+        ZCanError::Other(format!("read BLF file failed: {}", e))
+    }
+}
+
+impl<R: Read> Iterator for BlfReader<R> {
+    type Item = Result<DataObject, ZCanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read_object();
+        // the position of broken file can't be advanced, so the iteration is ended after an error
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+    use zlgcan_common::can::CanMessage;
+    use zlgcan_common::data::DataObject;
+    use zlgcan_common::lin::{protected_id, LinMessage};
+    use super::{from_system_time, system_time, BlfReader, BlfWriter};
+
+    #[test]
+    fn blf_system_time() {
+        let time = 1_792_317_600_123_000;
+        assert_eq!(system_time(time), [2026, 10, 0, 18, 10, 0, 0, 123]);
+        assert_eq!(from_system_time(system_time(time)), Some(time));
+        assert_eq!(from_system_time([0; 8]), None);
+    }
+
+    #[test]
+    fn blf_write_read() -> anyhow::Result<()> {
+        let mut can = CanMessage::new(Id::from_bits(0x7E0, false), &[0x02, 0x3E, 0x00]).unwrap();
+        can.set_timestamp(Some(1_700_000_000_123_456));
+        let mut remote = CanMessage::new_remote(Id::from_bits(0x1234, true), 8).unwrap();
+        remote.set_timestamp(Some(1_700_000_000_200_000))
+            .set_channel(2);
+        let mut canfd = CanMessage::new(Id::from_bits(0x7E8, false), &(0..16).collect::<Vec<u8>>()).unwrap();
+        canfd.set_timestamp(Some(1_700_000_001_000_001))
+            .set_channel(1)
+            .set_bitrate_switch(true)
+            .set_esi(true)
+            .set_direct(Direct::Receive);
+        let mut error = CanMessage::new(Id::from_bits(0x100, false), &[0x01, 0x02]).unwrap();
+        error.set_timestamp(Some(1_700_000_002_000_000))
+            .set_error_frame(true)
+            .set_direct(Direct::Receive);
+        let mut lin = LinMessage::new(protected_id(0x3D), &[0x11, 0x22, 0x33]).unwrap();
+        lin.set_timestamp(Some(1_700_000_003_000_000))
+            .set_channel(1)
+            .set_checksum(0x5A)
+            .set_direct(Direct::Receive);
+        let objects = vec![
+            DataObject::Can(can),
+            DataObject::Can(remote),
+            DataObject::Can(canfd),
+            DataObject::Error { frame: error, errors: vec![] },
+            DataObject::Lin(lin),
+        ];
+
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut writer = BlfWriter::new(&mut buffer)?;
+            objects.iter().try_for_each(|v| writer.write(v))?;
+        }
+        let buffer = buffer.into_inner();
+        assert_eq!(&buffer[..4], b"LOGG");
+        assert_eq!(u64::from_le_bytes(buffer[16..24].try_into()?), buffer.len() as u64);
+        assert_eq!(u32::from_le_bytes(buffer[32..36].try_into()?), 5);
+
+        let mut reader = BlfReader::new(buffer.as_slice())?;
+        let results = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(reader.start(), 1_700_000_000_123_000);
+        assert_eq!(results.len(), objects.len());
+        for (result, object) in results.iter().zip(&objects) {
+            match (result, object) {
+                (DataObject::Can(result), DataObject::Can(message)) |
+                (DataObject::Error { frame: result, .. }, DataObject::Error { frame: message, .. }) => {
+                    assert_eq!(result, message);
+                    assert_eq!(result.timestamp(), message.timestamp());
+                    assert_eq!(result.channel(), message.channel());
+                    assert_eq!(result.is_can_fd(), message.is_can_fd());
+                    assert_eq!(result.is_remote(), message.is_remote());
+                    assert_eq!(result.is_bitrate_switch(), message.is_bitrate_switch());
+                    assert_eq!(result.direct(), message.direct());
+                },
+                (DataObject::Lin(result), DataObject::Lin(message)) => {
+                    assert_eq!(result, message);
+                    assert_eq!(result.timestamp(), message.timestamp());
+                },
+                _ => panic!("unexpected object: {:?}", result),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn blf_multiple_containers() -> anyhow::Result<()> {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut writer = BlfWriter::new(&mut buffer)?;
+            for i in 0..10_000u64 {
+                let mut message = CanMessage::new(Id::from_bits(0x700 + (i % 0x100) as u32, false), &i.to_le_bytes()[..(i % 9) as usize]).unwrap();
+                message.set_timestamp(Some(1_700_000_000_000_000 + i * 100));
+                writer.write_message(&message)?;
+            }
+        }
+
+        let results = BlfReader::new(buffer.get_ref().as_slice())?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(results.len(), 10_000);
+        for (i, result) in results.iter().enumerate() {
+            let DataObject::Can(message) = result else { panic!("unexpected object: {:?}", result) };
+            let i = i as u64;
+            assert_eq!(message.timestamp(), 1_700_000_000_000_000 + i * 100);
+            assert_eq!(message.data(), &i.to_le_bytes()[..(i % 9) as usize]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn blf_write_at_offset() -> anyhow::Result<()> {
+        let mut message = CanMessage::new(Id::from_bits(0x7DF, false), &[0x02, 0x10, 0x01]).unwrap();
+        message.set_timestamp(Some(1_700_000_000_000_000));
+
+        // the header is updated at the position where the writer is created
+        let mut buffer = Cursor::new(b"PREFIX".to_vec());
+        buffer.set_position(6);
+        {
+            let mut writer = BlfWriter::new(&mut buffer)?;
+            writer.write_message(&message)?;
+        }
+        let buffer = buffer.into_inner();
+        assert_eq!(&buffer[..10], b"PREFIXLOGG");
+        assert_eq!(u64::from_le_bytes(buffer[22..30].try_into()?), buffer.len() as u64 - 6);
+
+        let results = BlfReader::new(&buffer[6..])?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(matches!(results.as_slice(), [DataObject::Can(v)] if v == &message));
+        Ok(())
+    }
+
+    /// The object with 32 bytes header.
+    fn object(kind: u32, flags: u32, time: u64, data: &[u8]) -> Vec<u8> {
+        let size = 32 + data.len();
+        let mut result = Vec::new();
+        result.extend(b"LOBJ");
+        result.extend(32u16.to_le_bytes());
+        result.extend(1u16.to_le_bytes());
+        result.extend((size as u32).to_le_bytes());
+        result.extend(kind.to_le_bytes());
+        result.extend(flags.to_le_bytes());
+        result.extend([0; 4]);
+        result.extend(time.to_le_bytes());
+        result.extend(data);
+        result.resize(result.len() + size % 4, 0);
+        result
+    }
+
+    /// The file that the objects are in an uncompressed log container.
+    fn file(objects: Vec<u8>) -> Vec<u8> {
+        let mut container = Vec::new();
+        container.extend(b"LOBJ");
+        container.extend(16u16.to_le_bytes());
+        container.extend(1u16.to_le_bytes());
+        container.extend(((32 + objects.len()) as u32).to_le_bytes());
+        container.extend(10u32.to_le_bytes());
+        container.extend([0; 8]);
+        container.extend((objects.len() as u32).to_le_bytes());
+        container.extend([0; 4]);
+        container.extend(objects);
+
+        let mut file = Vec::new();
+        file.extend(b"LOGG");
+        file.extend(144u32.to_le_bytes());
+        file.resize(40, 0);
+        [2026u16, 10, 0, 18, 10, 0, 0, 0].iter().for_each(|v| file.extend(v.to_le_bytes()));
+        file.resize(144, 0);
+        file.extend(container);
+        file
+    }
+
+    /// The CAN message object with 2 bytes data.
+    fn can_message() -> Vec<u8> {
+        let mut can = vec![0x01, 0x00, 0x01, 0x02, 0xF1, 0x10, 0xDA, 0x98, 0x11, 0x22];
+        can.resize(24, 0);
+        can
+    }
+
+    #[test]
+    fn blf_read_uncompressed() -> anyhow::Result<()> {
+        let mut objects = Vec::new();
+        // the unknown object is skipped
+        objects.extend(object(65, 2, 0, &[0; 6]));
+        objects.extend(object(86, 1, 1234, &can_message()));
+        let file = file(objects);
+
+        let results = BlfReader::new(file.as_slice())?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(results.len(), 1);
+        let DataObject::Can(message) = &results[0] else { panic!("unexpected object: {:?}", results[0]) };
+        assert_eq!(message.timestamp(), 1_792_317_600_012_340);
+        assert_eq!(message.id(), Id::from_bits(0x18DA10F1, true));
+        assert_eq!(message.data(), [0x11, 0x22]);
+        assert_eq!((message.channel(), message.direct()), (0, Direct::Transmit));
+
+        assert!(BlfReader::new(&b"LOBJ"[..]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn blf_read_invalid_size() -> anyhow::Result<()> {
+        let mut objects = object(86, 1, 0, &can_message());
+        objects[8..12].copy_from_slice(&0u32.to_le_bytes());
+        objects.extend(object(86, 1, 1234, &can_message()));
+        let file = file(objects);
+
+        // the object with zero size can't be skipped, the iteration is ended after the error
+        let mut reader = BlfReader::new(file.as_slice())?;
+        assert!(matches!(reader.next(), Some(Err(e)) if e.to_string().contains("invalid object size")));
+        assert!(reader.next().is_none());
+        Ok(())
+    }
+}
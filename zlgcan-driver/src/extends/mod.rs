@@ -4,17 +4,27 @@ pub use asynchronous::*;
 mod synchronous;
 pub use synchronous::*;
 
+mod asc;
+pub use asc::*;
+
+mod blf;
+pub use blf::*;
+
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::Receiver;
 use isotp_rs::can::frame::Frame;
 use isotp_rs::device::Listener;
 use zlgcan_common::can::{CanMessage, ZCanFrameType};
+use zlgcan_common::data::DataObject;
 use zlgcan_common::device::Handler;
 use crate::driver::{ZCanDriver, ZDevice};
 
 type ListenerType = Box<dyn Listener<u8, u32, CanMessage>>;
 
+/// The max count of frames received by one merged receive.
+const MERGED_RECEIVE_SIZE: u32 = 1000;
+
 #[inline]
 pub(crate) fn register_listener(
     listeners: &Arc<Mutex<HashMap<String, ListenerType>>>,
@@ -99,6 +109,38 @@ fn on_messages_util(
     }
 }
 
+/// Notify the CAN and error frames of all channels, the consecutive frames of same channel are notified together.
+/// The LIN frames are skipped because the listeners only accept the CAN frames.
+#[inline]
+fn on_objects_util(
+    listeners: &Arc<Mutex<HashMap<String, ListenerType>>>,
+    objects: Vec<DataObject>,
+) {
+    let mut messages: Vec<CanMessage> = Vec::new();
+    for object in objects {
+        let message = match object {
+            DataObject::Can(v) => v,
+            // the error frame is flagged by `is_error_frame`
+            DataObject::Error { frame, .. } => frame,
+            DataObject::Lin(_) | DataObject::LinError { .. } => {
+                log::debug!("ZLGCAN - the LIN frame of channel: {} is skipped", object.channel());
+                continue
+            },
+        };
+        if let Some(channel) = messages.last().map(|v| v.channel()) {
+            if channel != message.channel() {
+                on_messages_util(listeners, &messages, channel);
+                messages.clear();
+            }
+        }
+        messages.push(message);
+    }
+
+    if let Some(channel) = messages.last().map(|v| v.channel()) {
+        on_messages_util(listeners, &messages, channel);
+    }
+}
+
 #[inline]
 fn on_transmitting_util(
     listeners: &Arc<Mutex<HashMap<String, ListenerType>>>,
@@ -167,6 +209,14 @@ pub(crate) fn receive_callback(
     handler: Handler,
     listeners: &Arc<Mutex<HashMap<String, ListenerType>>>,
 ) {
+    if device.receive_merge() {
+        match device.receive_merged(MERGED_RECEIVE_SIZE, Some(0)) {
+            Ok(objects) => on_objects_util(listeners, objects),
+            Err(e) => log::warn!("ZLGCAN - merged receive failed: {}", e),
+        }
+        return;
+    }
+
     let can_chs = handler.can_channels().len() as u8;
     for channel in 0..can_chs {
         if let Ok(count) = device.get_can_num(channel, ZCanFrameType::CAN) {
@@ -189,3 +239,52 @@ pub(crate) fn receive_callback(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use isotp_rs::can::{frame::Frame, identifier::Id};
+    use isotp_rs::device::Listener;
+    use zlgcan_common::can::CanMessage;
+    use zlgcan_common::data::DataObject;
+    use zlgcan_common::lin::LinMessage;
+    use super::on_objects_util;
+
+    struct Collector(Arc<Mutex<Vec<(u8, usize, bool)>>>);
+
+    impl Listener<u8, u32, CanMessage> for Collector {
+        fn on_frame_transmitting(&mut self, _: u8, _: &CanMessage) {}
+        fn on_frame_transmitted(&mut self, _: u8, _: u32) {}
+        fn on_frame_received(&mut self, channel: u8, frames: &[CanMessage]) {
+            let error = frames.iter().any(|v| v.is_error_frame());
+            self.0.lock().unwrap().push((channel, frames.len(), error));
+        }
+    }
+
+    #[test]
+    fn test_on_objects() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut listeners: HashMap<String, super::ListenerType> = HashMap::new();
+        listeners.insert("collector".to_string(), Box::new(Collector(Arc::clone(&received))));
+        let listeners = Arc::new(Mutex::new(listeners));
+
+        let message = |channel: u8| {
+            let mut message = CanMessage::new(Id::Standard(0x7DF), &[0x02, 0x10, 0x01]).unwrap();
+            message.set_channel(channel);
+            message
+        };
+        let mut error = message(1);
+        error.set_error_frame(true);
+        let mut lin = LinMessage::new(0xC1, &[0x01]).unwrap();
+        lin.set_channel(1);
+        let objects = vec![
+            DataObject::from(message(0)),
+            DataObject::from(message(0)),
+            DataObject::Error { frame: error, errors: Default::default() },
+            DataObject::from(lin),
+            DataObject::from(message(1)),
+        ];
+        on_objects_util(&listeners, objects);
+        assert_eq!(*received.lock().unwrap(), vec![(0, 2, false), (1, 2, true)]);
+    }
+}
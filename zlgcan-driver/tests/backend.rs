@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+use zlgcan_common::can::{CanChlCfg, CanChlCfgFactory, CanMessage, ZCanChlError, ZCanChlErrorV1, ZCanChlMode, ZCanChlStatus, ZCanChlType, ZCanFrameType};
+use zlgcan_common::data::DataObject;
+use zlgcan_common::device::{DeriveInfo, ZCanDeviceType, ZChannelContext, ZDeviceContext, ZDeviceInfo};
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::lin::LinMessage;
+use zlgcan_driver::driver::{backend_registered, register_backend, unregister_backend, ZCanBackend, ZCanDriver, ZDevice};
+
+/// A backend that echo the transmitted frames to the same channel.
+#[derive(Default)]
+struct EchoBackend {
+    frames: Mutex<HashMap<u8, Vec<CanMessage>>>,
+    objects: Mutex<Vec<DataObject>>,
+}
+
+impl ZCanBackend for EchoBackend {
+    fn open(&self, _: &mut ZDeviceContext) -> Result<(), ZCanError> {
+        Ok(())
+    }
+
+    fn close(&self, _: &ZDeviceContext) -> Result<(), ZCanError> {
+        Ok(())
+    }
+
+    fn read_device_info(&self, _: &ZDeviceContext) -> Result<ZDeviceInfo, ZCanError> {
+        ZDeviceInfo::try_from(&DeriveInfo::new(false, 1))
+    }
+
+    fn init_can_chl(&self, context: &mut ZChannelContext, _: &CanChlCfg) -> Result<(), ZCanError> {
+        self.frames.lock().unwrap().insert(context.channel(), Vec::new());
+        context.set_channel_handler(None);
+        Ok(())
+    }
+
+    fn reset_can_chl(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.frames.lock().unwrap().remove(&context.channel());
+        Ok(())
+    }
+
+    fn read_can_chl_status(&self, _: &ZChannelContext) -> Result<ZCanChlStatus, ZCanError> {
+        Ok(Default::default())
+    }
+
+    fn read_can_chl_error(&self, _: &ZChannelContext) -> Result<ZCanChlError, ZCanError> {
+        Ok(ZCanChlError::from(ZCanChlErrorV1::default()))
+    }
+
+    fn clear_can_buffer(&self, context: &ZChannelContext) -> Result<(), ZCanError> {
+        self.frames.lock().unwrap().entry(context.channel()).or_default().clear();
+        Ok(())
+    }
+
+    fn get_can_num(&self, context: &ZChannelContext, _: ZCanFrameType) -> Result<u32, ZCanError> {
+        Ok(self.frames.lock().unwrap().entry(context.channel()).or_default().len() as u32)
+    }
+
+    fn receive_can(&self, context: &ZChannelContext, _: u32, _: u32) -> Result<Vec<CanMessage>, ZCanError> {
+        Ok(std::mem::take(self.frames.lock().unwrap().entry(context.channel()).or_default()))
+    }
+
+    fn transmit_can(&self, context: &ZChannelContext, frames: Vec<CanMessage>) -> Result<u32, ZCanError> {
+        let len = frames.len() as u32;
+        self.frames.lock().unwrap().entry(context.channel()).or_default().extend(frames);
+        Ok(len)
+    }
+
+    fn receive_data(&self, _: &ZChannelContext, _: u32, _: u32) -> Result<Vec<DataObject>, ZCanError> {
+        Ok(std::mem::take(&mut *self.objects.lock().unwrap()))
+    }
+
+    fn transmit_data(&self, _: &ZChannelContext, objects: Vec<DataObject>) -> Result<u32, ZCanError> {
+        let len = objects.len() as u32;
+        self.objects.lock().unwrap().extend(objects);
+        Ok(len)
+    }
+}
+
+#[test]
+fn custom_backend() -> anyhow::Result<()> {
+    let dev_type = ZCanDeviceType::ZCAN_PCI5121;
+    assert!(!backend_registered(dev_type));
+    assert!(matches!(ZCanDriver::new(dev_type as u32, 0, None), Err(ZCanError::DeviceNotSupported)));
+
+    register_backend(&[dev_type], |_, _| Ok(Arc::new(EchoBackend::default())));
+    assert!(backend_registered(dev_type));
+
+    let mut driver = ZCanDriver::new(dev_type as u32, 0, None)?;
+    driver.open()?;
+    assert_eq!(driver.device_info()?.can_channels(), 1);
+    let cfg = CanChlCfg::new(dev_type as u32, ZCanChlType::CAN as u8, ZCanChlMode::Normal as u8, 500_000, Default::default(), Weak::new());
+    driver.init_can_chl(vec![cfg])?;
+
+    let msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_can(0, vec![msg])?, 1);
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 1);
+    let frames = driver.receive_can(0, 1, None)?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].id(), Id::from_bits(0x7DF, false));
+    assert!(matches!(driver.receive_canfd(0, 1, None), Err(ZCanError::MethodNotSupported)));
+
+    // the mixed objects are ordered by the device time
+    let mut msg = CanMessage::new(Id::from_bits(0x7E8, false), [0x02, 0x50, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    msg.set_hardware_timestamp(Some(2_000));
+    let mut lin = LinMessage::new(0x3C, &[0x01, 0x02])
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    lin.set_direct(Direct::Receive)
+        .set_hardware_timestamp(Some(1_000));
+    assert_eq!(driver.transmit_all(0, vec![DataObject::from(msg), DataObject::from(lin)])?, 2);
+    let objects = driver.receive_all(0, 10, None)?;
+    assert!(matches!(&objects[0], DataObject::Lin(v) if v.pid() == 0x3C));
+    assert!(matches!(&objects[1], DataObject::Can(v) if v.id() == Id::from_bits(0x7E8, false)));
+    assert_eq!(objects[1].timestamp() - objects[0].timestamp(), 1_000);
+    driver.close();
+
+    assert!(unregister_backend(dev_type));
+    assert!(!backend_registered(dev_type));
+    Ok(())
+}
+
+#[test]
+fn builtin_bitrate_tables() -> anyhow::Result<()> {
+    let factory = CanChlCfgFactory::builtin()?;
+    ZCanDeviceType::ALL.iter()
+        // the device type of `custom_backend` is registered temporarily
+        .filter(|&&dev_type| dev_type != ZCanDeviceType::ZCAN_PCI5121 && backend_registered(dev_type))
+        .for_each(|&dev_type| assert!(factory.device_cfg(dev_type as u32).is_some(), "device: {:?} has no bitrate table", dev_type));
+    Ok(())
+}
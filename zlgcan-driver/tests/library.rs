@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use zlgcan_common::device::ZCanDeviceType;
+use zlgcan_common::error::ZCanError;
+use zlgcan_driver::driver::{clear_library_paths, library_search_paths, set_library_dir, set_library_path, LibraryFamily, ZCanDriver, ZDevice};
+
+#[test]
+fn library_loader() -> anyhow::Result<()> {
+    assert_eq!(LibraryFamily::from_device_type(ZCanDeviceType::ZCAN_USBCANFD_200U), Some(LibraryFamily::USBCANFD));
+    assert_eq!(LibraryFamily::from_device_type(ZCanDeviceType::ZCAN_USBCAN_4E_U), Some(LibraryFamily::USBCAN_4E));
+    assert_eq!(LibraryFamily::from_device_type(ZCanDeviceType::ZCAN_VIRTUAL_DEVICE), None);
+
+    let path = PathBuf::from("/nonexistent/libusbcanfd.so");
+    set_library_path(LibraryFamily::USBCANFD, &path);
+    set_library_dir("/nonexistent/zlgcan");
+    let paths = library_search_paths(LibraryFamily::USBCANFD);
+    assert_eq!(paths[0], path);
+    assert_eq!(paths[1], PathBuf::from("/nonexistent/zlgcan/libusbcanfd.so"));
+    assert_eq!(paths.last(), Some(&PathBuf::from("libusbcanfd.so")));
+
+    match ZCanDriver::new(ZCanDeviceType::ZCAN_USBCANFD_200U as u32, 0, None) {
+        Err(ZCanError::LibraryLoadFailed(e)) => {
+            assert!(e.contains("USBCANFD"));
+            assert!(e.contains("/nonexistent/libusbcanfd.so"));
+            assert!(e.contains("/nonexistent/zlgcan/libusbcanfd.so"));
+        },
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("the library should not be loaded"),
+    }
+
+    // the device without vendor library is not affected by the missing libraries.
+    let mut device = ZCanDriver::new(ZCanDeviceType::ZCAN_VIRTUAL_DEVICE as u32, 0, None)?;
+    device.open()?;
+    device.close();
+
+    clear_library_paths();
+    assert_eq!(library_search_paths(LibraryFamily::USBCANFD).first().map(|p| p.ends_with("libusbcanfd.so")), Some(true));
+    assert!(!library_search_paths(LibraryFamily::USBCANFD).contains(&path));
+
+    Ok(())
+}
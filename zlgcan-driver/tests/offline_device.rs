@@ -0,0 +1,122 @@
+use std::time::Instant;
+use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+use zlgcan_common::can::{CanChlCfgFactory, CanMessage, ZCanChlType, ZCanFrameType};
+use zlgcan_common::device::ZCanDeviceType;
+use zlgcan_common::error::ZCanError;
+use zlgcan_driver::driver::{clear_offline_config, set_offline_config, OfflineConfig, ReplaySpeed, ZCanDriver, ZDevice};
+
+const TRACE: &str = "\
+(1700000000.000000) can0 123#0102030405060708
+(1700000000.010000) can1 18DAF110##1000102030405060708090A0B
+(1700000000.020000) can0 7DF#R
+
+(1700000000.200000) can1 456#AABB
+";
+
+fn open_device(dev_idx: u32, name: &str, speed: ReplaySpeed) -> anyhow::Result<(ZCanDriver, std::path::PathBuf)> {
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("zlgcan-offline-{}-{}.log", name, std::process::id()));
+    let output = dir.join(format!("zlgcan-offline-{}-{}-tx.log", name, std::process::id()));
+    std::fs::write(&input, TRACE)?;
+    set_offline_config(dev_idx, OfflineConfig::new(input, Some(output.clone()), speed));
+
+    let dev_type = ZCanDeviceType::ZCAN_OFFLINE_DEVICE;
+    let mut driver = ZCanDriver::new(dev_type as u32, dev_idx, None)?;
+    driver.open()?;
+    clear_offline_config(dev_idx);
+
+    let factory = CanChlCfgFactory::new()?;
+    let mut cfg = Vec::new();
+    for _ in 0..driver.device_info()?.can_channels() {
+        cfg.push(factory.builder(dev_type, 500_000)
+            .can_type(ZCanChlType::CANFD_ISO)
+            .dbitrate(1_000_000)
+            .build()?);
+    }
+    driver.init_can_chl(cfg)?;
+
+    Ok((driver, output))
+}
+
+#[test]
+fn offline_device_max() -> anyhow::Result<()> {
+    let (mut driver, output) = open_device(0, "max", ReplaySpeed::Max)?;
+    assert!(driver.is_online()?);
+    assert_eq!(driver.device_info()?.can_channels(), 2);
+
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 2);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::ALL)?, 2);
+
+    let frames = driver.receive_can(0, 10, None)?;
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].timestamp(), 1_700_000_000_000_000);
+    assert_eq!(frames[0].direct(), Direct::Receive);
+    assert_eq!(frames[0].data(), [1, 2, 3, 4, 5, 6, 7, 8].as_slice());
+    assert!(frames[1].is_remote());
+    // the trace is exhausted, it's returned without waiting
+    let start = Instant::now();
+    assert!(driver.receive_can(0, 10, None)?.is_empty());
+    assert!(start.elapsed().as_millis() < 100);
+
+    let frames = driver.receive_canfd(1, 10, None)?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].channel(), 1);
+    assert!(frames[0].is_extended());
+    assert!(frames[0].is_bitrate_switch());
+    assert_eq!(frames[0].length(), 12);
+
+    driver.clear_can_buffer(1)?;
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::CAN)?, 0);
+    assert!(driver.receive_can(1, 10, Some(10))?.is_empty());
+
+    let msg = CanMessage::new(Id::from_bits(0x7E0, false), [0x02, 0x10, 0x03].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_can(1, vec![msg.clone()])?, 1);
+    let msg = CanMessage::new(Id::from_bits(0x18DA10F1, true), [0x55; 16].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_canfd(0, vec![msg])?, 1);
+    driver.close();
+
+    let lines = std::fs::read_to_string(output)?;
+    let lines = lines.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(") can1 7E0#021003"));
+    assert!(lines[1].ends_with(&format!(") can0 18DA10F1##0{}", "55".repeat(16))));
+
+    Ok(())
+}
+
+#[test]
+fn offline_device_accelerated() -> anyhow::Result<()> {
+    let (mut driver, _) = open_device(1, "accelerated", ReplaySpeed::Accelerated(2.))?;
+
+    let start = Instant::now();
+    let frames = driver.receive_can(1, 10, Some(1000))?;
+    let elapsed = start.elapsed().as_millis();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].data(), [0xAA, 0xBB].as_slice());
+    assert!((90..500).contains(&elapsed), "elapsed: {}", elapsed);
+
+    assert!(matches!(driver.reset_can_chl(0), Ok(())));
+    assert!(matches!(driver.get_can_num(0, ZCanFrameType::CAN), Err(ZCanError::ChannelNotOpened)));
+
+    driver.close();
+    Ok(())
+}
+
+#[test]
+fn offline_device_invalid_trace() -> anyhow::Result<()> {
+    let input = std::env::temp_dir().join(format!("zlgcan-offline-invalid-{}.log", std::process::id()));
+    std::fs::write(&input, "(1700000000.000000) can0 123#010\n")?;
+    set_offline_config(2, OfflineConfig::new(input, None, ReplaySpeed::Original));
+
+    let mut driver = ZCanDriver::new(ZCanDeviceType::ZCAN_OFFLINE_DEVICE as u32, 2, None)?;
+    let ret = driver.open();
+    clear_offline_config(2);
+    match ret {
+        Err(ZCanError::ConfigurationError(e)) => assert!(e.contains("line 1"), "{}", e),
+        _ => panic!("the invalid trace file is opened"),
+    }
+
+    Ok(())
+}
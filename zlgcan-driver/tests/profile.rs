@@ -0,0 +1,92 @@
+use isotp_rs::can::{frame::Frame, identifier::Id};
+use zlgcan_common::can::{CanMessage, ZCanChlMode, ZCanFrameType};
+use zlgcan_common::error::ZCanError;
+use zlgcan_driver::driver::{DeviceProfile, ZDevice};
+
+#[test]
+fn profile_virtual_device() -> anyhow::Result<()> {
+    let profile: DeviceProfile = "\
+device:
+  type: 99
+  index: 3
+  derive: { canfd: true, channels: 3 }
+can:
+  - { bitrate: 500000, data_bitrate: 2000000, type: canfd_iso }
+  - { bitrate: 250000, mode: listen_only, filter: single, acc_code: 0, acc_mask: 0xFFFFFFFF }
+".parse()?;
+    assert_eq!(profile.can.len(), 2);
+    assert!(matches!(profile.can[1].mode, ZCanChlMode::ListenOnly));
+
+    let mut driver = profile.open()?;
+    assert_eq!(driver.device_index(), 3);
+    assert_eq!(driver.device_info()?.can_channels(), 3);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::ALL)?, 0);
+    assert!(matches!(driver.get_can_num(2, ZCanFrameType::ALL), Err(ZCanError::ChannelNotOpened)));
+
+    let msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_can(0, vec![msg])?, 1);
+    let frames = driver.receive_can(1, 10, Some(10))?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].data(), [0x02, 0x10, 0x01].as_slice());
+
+    driver.close();
+
+    // the virtual device has no serial number
+    let profile: DeviceProfile = "device: { type: 99, serial: SN01 }\n".parse()?;
+    assert!(matches!(profile.open(), Err(ZCanError::ConfigurationError(e)) if e.contains("is not found")));
+    let profile: DeviceProfile = "device: { type: 99, index: 1, serial: SN01 }\n".parse()?;
+    assert!(matches!(profile.open(), Err(ZCanError::ConfigurationError(e)) if e.contains("expected: `SN01`")));
+
+    // the virtual device can't receive the frames of all channels by one call, it's rejected before opening
+    let profile: DeviceProfile = "device: { type: 99, receive_merge: true }\ncan:\n  - { bitrate: 500000 }\n".parse()?;
+    assert!(profile.device.receive_merge);
+    assert!(matches!(profile.validate(), Err(ZCanError::ConfigurationError(e)) if e.contains("merged receive")));
+    assert!(matches!(profile.open(), Err(ZCanError::ConfigurationError(_))));
+    Ok(())
+}
+
+#[test]
+fn profile_file() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("zlgcan-profile-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("bitrate.yaml"), "99: { bitrate: { 33333: { tseg1: 50, tseg2: 7, sjw: 2, smp: 0, brp: 29 } } }\n")?;
+    std::fs::write(dir.join("profile.yaml"), "device: { type: 99 }\nbitrate_cfg: bitrate.yaml\ncan:\n  - { bitrate: 33333 }\n")?;
+
+    // the relative `bitrate_cfg` is resolved against the directory of profile, not the current directory
+    let profile = DeviceProfile::from_file(dir.join("profile.yaml"))?;
+    assert_eq!(profile.bitrate_cfg.as_deref(), Some(dir.join("bitrate.yaml").as_path()));
+    assert!(profile.factory()?.device_cfg(99).unwrap().bitrate().contains_key(&33_333));
+    let mut driver = DeviceProfile::open_file(dir.join("profile.yaml"))?;
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::ALL)?, 0);
+    driver.close();
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn profile_invalid() {
+    let error = |s: &str| match s.parse::<DeviceProfile>().and_then(|v| v.open()) {
+        Err(ZCanError::ConfigurationError(e)) => e,
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("the profile is opened"),
+    };
+
+    let e = error("device: { type: 4 }\ncan:\n  - { bitrate: 500000, type: canfd_iso }\n");
+    assert!(e.contains("CAN channel 0") && e.contains("CANFD"), "{}", e);
+    let e = error("device: { type: 4 }\ncan:\n  - { bitrate: 500000, resistance: true }\n");
+    assert!(e.contains("resistance"), "{}", e);
+    let e = error("device: { type: 99 }\nlin:\n  - { mode: master, bitrate: 19200 }\n");
+    assert!(e.contains("not supported LIN"), "{}", e);
+    let e = error("device: { type: 41 }\nlin:\n  - { mode: master, bitrate: 30000 }\n");
+    assert!(e.contains("LIN channel 0: bitrate: 30000"), "{}", e);
+    let e = error("device: { type: 99, derive: { canfd: true, channels: 1 } }\ncan:\n  - { bitrate: 500000 }\n  - { bitrate: 500000 }\n");
+    assert!(e.contains("2 CAN channels"), "{}", e);
+    let e = error("device: { type: 42 }\ncan:\n  - { bitrate: 500000 }\n  - { bitrate: 500000 }\n");
+    assert!(e.contains("2 CAN channels are configured, but the device has 1"), "{}", e);
+    let e = error("device: { type: 74 }\n");
+    assert!(e.contains("device type"), "{}", e);
+    let e = error("device: { type: 99 }\ncan:\n  - { bitrate: 500000, mode: silent }\n");
+    assert!(e.contains("unknown value `silent`"), "{}", e);
+}
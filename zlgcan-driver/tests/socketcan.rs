@@ -0,0 +1,95 @@
+//! The test requires a virtual CAN interface:
+//! ```shell
+//! sudo ip link add dev vcan0 type vcan
+//! sudo ip link set vcan0 mtu 72
+//! sudo ip link set up vcan0
+//! ```
+#![cfg(target_os = "linux")]
+
+use std::path::Path;
+use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+use zlgcan_common::can::{CanChlCfgFactory, CanMessage, ZCanChlType, ZCanFrameType, ZCanTxMode};
+use zlgcan_common::device::ZCanDeviceType;
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::utils::system_timestamp;
+use zlgcan_driver::driver::{register_socketcan, ZCanDriver, ZDevice};
+
+const INTERFACE: &str = "vcan0";
+
+#[test]
+fn socketcan_vcan() -> anyhow::Result<()> {
+    if !Path::new("/sys/class/net").join(INTERFACE).exists() {
+        eprintln!("the interface {} is not existed, skipped", INTERFACE);
+        return Ok(());
+    }
+
+    // the both channels are bound to the same interface, so they receive the frames of each other.
+    let dev_type = ZCanDeviceType::ZCAN_USBCANFD_200U;
+    register_socketcan(&[dev_type], &[INTERFACE, INTERFACE]);
+
+    let mut driver = ZCanDriver::new(dev_type as u32, 0, None)?;
+    driver.open()?;
+    assert!(driver.is_online()?);
+    let (channels, canfd) = driver.device_info().map(|v| (v.can_channels(), v.canfd()))?;
+    assert_eq!(channels, 2);
+
+    let factory = CanChlCfgFactory::new()?;
+    let mut cfg = Vec::new();
+    for _ in 0..channels {
+        cfg.push(factory.builder(dev_type, 500_000)
+            .can_type(ZCanChlType::CANFD_ISO)
+            .dbitrate(1_000_000)
+            .build()?);
+    }
+    driver.init_can_chl(cfg)?;
+
+    let msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    let host = system_timestamp();
+    assert_eq!(driver.transmit_can(0, vec![msg])?, 1);
+    let frames = driver.receive_can(1, 10, Some(100))?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].channel(), 1);
+    assert_eq!(frames[0].direct(), Direct::Receive);
+    assert_eq!(frames[0].data(), [0x02, 0x10, 0x01].as_slice());
+    // the frame is stamped by kernel, the virtual interface has no hardware timestamp
+    assert!(frames[0].timestamp() >= host);
+    assert_eq!(frames[0].hardware_timestamp(), None);
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::ALL)?, 0);
+
+    // only the frame transmitted with self reception is received by the transmitting channel
+    let mut echo = CanMessage::new(Id::from_bits(0x7E0, false), [0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    echo.set_tx_mode(ZCanTxMode::SelfReception as u8);
+    let other = CanMessage::new(Id::from_bits(0x7E1, false), [0x02].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_can(0, vec![echo, other])?, 2);
+    let frames = driver.receive_can(0, 10, Some(100))?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].id(), Id::from_bits(0x7E0, false));
+    assert_eq!(frames[0].direct(), Direct::Transmit);
+    assert_eq!(driver.receive_can(1, 10, Some(100))?.len(), 2);
+
+    if canfd {
+        let mut msg = CanMessage::new(Id::from_bits(0x18DA10F1, true), [0x55; 16].as_slice())
+            .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+        msg.set_bitrate_switch(true);
+        assert_eq!(driver.transmit_canfd(1, vec![msg])?, 1);
+        let frames = driver.receive_canfd(0, 10, Some(100))?;
+        assert_eq!(frames.len(), 1);
+        assert!(frames[0].is_extended());
+        assert!(frames[0].is_bitrate_switch());
+        assert_eq!(frames[0].length(), 16);
+    }
+
+    let status = driver.read_can_chl_status(0)?;
+    assert_eq!((status.regRECounter, status.regTECounter), (0, 0));
+    driver.clear_can_buffer(0)?;
+    assert!(driver.receive_can(0, 10, Some(10))?.is_empty());
+
+    driver.reset_can_chl(1)?;
+    assert!(matches!(driver.get_can_num(1, ZCanFrameType::CAN), Err(ZCanError::ChannelNotOpened)));
+
+    driver.close();
+    Ok(())
+}
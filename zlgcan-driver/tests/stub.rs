@@ -0,0 +1,409 @@
+//! The tests of linux backends with the vendor library stub(`zlgcan_stub`).
+#![cfg(target_os = "linux")]
+#![allow(non_snake_case)]
+
+use std::ffi::{c_char, c_uint, c_void, CStr, CString};
+use std::mem::size_of;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use dlopen2::symbor::{Container, Symbol, SymBorApi};
+use isotp_rs::can::{frame::Frame, identifier::Id};
+use zlgcan_common::can::{CanChlCfgFactory, CanMessage, NewZCanFrame, ZCanChlMode, ZCanChlType, ZCanFdFrameV1, ZCanFdFrameV2, ZCanFrameType, ZCanFrameV1, ZCanFrameV2, ZCanFrameV3};
+use zlgcan_common::data::{DataObject, ZCanDataObj, ZCanDataObjV2};
+use zlgcan_common::device::{ZCanDeviceType, ZDeviceInfo};
+use zlgcan_common::error::ZCanError;
+use zlgcan_common::TryFrom;
+use zlgcan_driver::driver::{set_library_path, LibraryFamily, ZCanDriver, ZDevice};
+
+#[derive(SymBorApi)]
+struct StubApi<'a> {
+    STUB_Reset: Symbol<'a, unsafe extern "C" fn()>,
+    STUB_SetResult: Symbol<'a, unsafe extern "C" fn(name: *const c_char, code: c_uint)>,
+    STUB_CallCount: Symbol<'a, unsafe extern "C" fn(name: *const c_char) -> c_uint>,
+    STUB_PushReceive: Symbol<'a, unsafe extern "C" fn(name: *const c_char, channel: c_uint, data: *const c_void, elem: c_uint, count: c_uint)>,
+    STUB_RecordCount: Symbol<'a, unsafe extern "C" fn(name: *const c_char, channel: c_uint) -> c_uint>,
+    STUB_PopRecord: Symbol<'a, unsafe extern "C" fn(name: *const c_char, channel: c_uint, data: *mut c_void, size: c_uint) -> c_uint>,
+    STUB_SetDeviceInfo: Symbol<'a, unsafe extern "C" fn(info: *const ZDeviceInfo)>,
+    STUB_GetValue: Symbol<'a, unsafe extern "C" fn(path: *const c_char) -> *const c_char>,
+}
+
+/// The stub is shared by all tests, so the tests are run one by one.
+struct Stub {
+    api: Container<StubApi<'static>>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Stub {
+    fn new(families: &[LibraryFamily]) -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let path = stub_path();
+        families.iter()
+            .for_each(|family| set_library_path(*family, &path));
+        let api: Container<StubApi> = unsafe { Container::load(&path) }
+            .expect("ZLGCAN - could not load the stub library");
+        unsafe { (api.STUB_Reset)() };
+
+        Self { api, _guard }
+    }
+
+    fn set_result(&self, name: &str, code: u32) {
+        let name = CString::new(name).unwrap();
+        unsafe { (self.api.STUB_SetResult)(name.as_ptr(), code) }
+    }
+
+    fn call_count(&self, name: &str) -> u32 {
+        let name = CString::new(name).unwrap();
+        unsafe { (self.api.STUB_CallCount)(name.as_ptr()) }
+    }
+
+    fn push_receive<T>(&self, name: &str, channel: u8, frames: &[T]) {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            (self.api.STUB_PushReceive)(name.as_ptr(), channel as u32, frames.as_ptr() as *const c_void, size_of::<T>() as u32, frames.len() as u32)
+        }
+    }
+
+    fn record_count(&self, name: &str, channel: u8) -> u32 {
+        let name = CString::new(name).unwrap();
+        unsafe { (self.api.STUB_RecordCount)(name.as_ptr(), channel as u32) }
+    }
+
+    fn pop_record<T: Default>(&self, name: &str, channel: u8) -> Option<T> {
+        let name = CString::new(name).unwrap();
+        let mut value = T::default();
+        match unsafe { (self.api.STUB_PopRecord)(name.as_ptr(), channel as u32, &mut value as *mut T as *mut c_void, size_of::<T>() as u32) } {
+            0 => None,
+            _ => Some(value),
+        }
+    }
+
+    fn set_device_info(&self, info: &ZDeviceInfo) {
+        unsafe { (self.api.STUB_SetDeviceInfo)(info) }
+    }
+
+    fn value(&self, path: &str) -> Option<String> {
+        let path = CString::new(path).unwrap();
+        let ret = unsafe { (self.api.STUB_GetValue)(path.as_ptr()) };
+        if ret.is_null() {
+            None
+        }
+        else {
+            Some(unsafe { CStr::from_ptr(ret) }.to_string_lossy().into_owned())
+        }
+    }
+}
+
+/// The stub is built into the same directory of test executable as a dev-dependency.
+fn stub_path() -> PathBuf {
+    let exe = std::env::current_exe().expect("ZLGCAN - could not get current executable");
+    let dir = exe.parent().expect("ZLGCAN - could not get target directory");
+    [dir.join("libzlgcan_stub.so"), dir.join("../libzlgcan_stub.so")]
+        .into_iter()
+        .find(|p| p.exists())
+        .expect("ZLGCAN - could not find libzlgcan_stub.so")
+}
+
+fn new_message(id: u32, extended: bool, data: &[u8], canfd: bool) -> anyhow::Result<CanMessage> {
+    let mut msg = CanMessage::new(Id::from_bits(id, extended), data)
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    msg.set_can_fd(canfd)
+        .set_channel(1);
+    Ok(msg)
+}
+
+fn open_device(dev_type: ZCanDeviceType, channels: u8) -> anyhow::Result<ZCanDriver> {
+    let mut driver = ZCanDriver::new(dev_type as u32, 0, None)?;
+    driver.open()?;
+    assert_eq!(driver.device_info()?.can_channels(), 2);
+
+    let factory = CanChlCfgFactory::new()?;
+    let mut cfg = Vec::new();
+    for _ in 0..channels {
+        cfg.push(factory.new_can_chl_cfg(dev_type as u32, ZCanChlType::CANFD_ISO as u8, ZCanChlMode::Normal as u8, 500_000, Default::default())?);
+    }
+    driver.init_can_chl(cfg)?;
+
+    Ok(driver)
+}
+
+#[test]
+fn stub_usbcan() -> anyhow::Result<()> {
+    let stub = Stub::new(&[LibraryFamily::USBCAN]);
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCAN2, 2)?;
+    assert_eq!(stub.record_count("VCI_InitCAN", 1), 1);
+    assert_eq!(stub.call_count("VCI_StartCAN"), 2);
+
+    let msg = new_message(0x7DF, false, &[0x02, 0x10, 0x01], false)?;
+    assert_eq!(driver.transmit_can(0, vec![msg])?, 1);
+    let frame: ZCanFrameV1 = stub.pop_record("VCI_Transmit", 0).expect("frame is not transmitted");
+    let frame = <CanMessage as TryFrom<ZCanFrameV1, u64>>::try_from(frame, 0)?;
+    assert_eq!(frame.id(), Id::from_bits(0x7DF, false));
+    assert_eq!(frame.data(), &[0x02, 0x10, 0x01]);
+
+    let frames = [
+        <ZCanFrameV1 as TryFrom<CanMessage, u64>>::try_from(new_message(0x18DAF110, true, &[0x03, 0x50, 0x01], false)?, 0)?,
+        <ZCanFrameV1 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &[0x02, 0x51, 0x01], false)?, 0)?,
+    ];
+    stub.push_receive("VCI_Receive", 1, &frames);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::CAN)?, 2);
+    // the frames resized but not received are dropped.
+    let received = driver.receive_can(1, 10, None)?;
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].id(), Id::from_bits(0x18DAF110, true));
+    assert_eq!(received[0].channel(), 1);
+    assert_eq!(received[1].data(), &[0x02, 0x51, 0x01]);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::CAN)?, 0);
+
+    stub.set_result("VCI_ResetCAN", 0);
+    assert!(matches!(
+        driver.reset_can_chl(0),
+        Err(ZCanError::MethodExecuteFailed(name, 0)) if name == "VCI_ResetCAN"
+    ));
+    driver.close();
+
+    Ok(())
+}
+
+#[test]
+fn stub_usbcanfd() -> anyhow::Result<()> {
+    let stub = Stub::new(&[LibraryFamily::USBCANFD]);
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCANFD_200U, 2)?;
+    assert!(driver.device_info()?.canfd());
+
+    let msg = new_message(0x7E0, false, &[0x02, 0x3E, 0x00], false)?;
+    assert_eq!(driver.transmit_can(1, vec![msg])?, 1);
+    let frame: ZCanFrameV2 = stub.pop_record("VCI_Transmit", 1).expect("frame is not transmitted");
+    let frame = <CanMessage as TryFrom<ZCanFrameV2, u64>>::try_from(frame, 0)?;
+    assert_eq!(frame.id(), Id::from_bits(0x7E0, false));
+    assert_eq!(frame.data(), &[0x02, 0x3E, 0x00]);
+
+    let data = (0..12).collect::<Vec<u8>>();
+    let msg = new_message(0x7E0, false, &data, true)?;
+    assert_eq!(driver.transmit_canfd(1, vec![msg])?, 1);
+    let frame: ZCanFdFrameV1 = stub.pop_record("VCI_TransmitFD", 1).expect("frame is not transmitted");
+    let frame = <CanMessage as TryFrom<ZCanFdFrameV1, u64>>::try_from(frame, 0)?;
+    assert!(frame.is_can_fd());
+    assert_eq!(frame.data(), data.as_slice());
+
+    let frames = [<ZCanFdFrameV1 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &data, true)?, 0)?];
+    stub.push_receive("VCI_ReceiveFD", 0, &frames);
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CANFD)?, 1);
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 0);
+    let received = driver.receive_canfd(0, 1, None)?;
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].data(), data.as_slice());
+
+    // the CAN and CANFD frames are received by one call
+    let objects = [
+        ZCanDataObj::from(ZCanFrameV2::new(0x7E8, 1, [0x02, 0x7E, 0x00], Default::default(), 300)?),
+        ZCanDataObj::from(ZCanFdFrameV1::new(0x7E9, 1, data, Default::default(), 100)?),
+    ];
+    stub.push_receive("VCI_ReceiveData", 1, &objects);
+    let received = driver.receive_all(1, 10, Some(0))?;
+    assert_eq!(received.len(), 2);
+    assert!(matches!(&received[0], DataObject::Can(v) if v.is_can_fd() && v.id() == Id::from_bits(0x7E9, false)));
+    assert!(matches!(&received[1], DataObject::Can(v) if !v.is_can_fd() && v.data() == [0x02, 0x7E, 0x00]));
+
+    let msg = new_message(0x7E0, false, &[0x02, 0x3E, 0x80], false)?;
+    assert_eq!(driver.transmit_all(1, vec![DataObject::from(msg)])?, 1);
+    let object: ZCanDataObj = stub.pop_record("VCI_TransmitData", 1).expect("object is not transmitted");
+    let object = <DataObject as TryFrom<ZCanDataObj, u64>>::try_from(object, 0)?;
+    assert!(matches!(&object, DataObject::Can(v) if v.data() == [0x02, 0x3E, 0x80]));
+
+    // the frames of all channels are received by one call, the channels are reopened to share the clock samples
+    driver.close();
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCANFD_200U, 2)?;
+    assert!(matches!(driver.receive_merged(10, Some(0)), Err(ZCanError::ConfigurationError(_))));
+    while stub.pop_record::<u32>("VCI_SetReference", 0).is_some() {}
+    driver.set_receive_merge(true)?;
+    assert!(driver.receive_merge());
+    assert_eq!(stub.pop_record::<u32>("VCI_SetReference", 0), Some(0x32));
+    let objects = [
+        ZCanDataObj::from(ZCanFrameV2::new(0x7E8, 1, [0x01], Default::default(), 100)?),
+        ZCanDataObj::from(ZCanFdFrameV1::new(0x7E9, 0, [0x02], Default::default(), 200)?),
+        ZCanDataObj::from(ZCanFrameV2::new(0x7EA, 1, [0x03], Default::default(), 300)?),
+    ];
+    stub.push_receive("VCI_ReceiveData", 0, &objects);
+    let received = driver.receive_merged(10, Some(0))?;
+    let received = received.iter()
+        .map(|v| (v.channel(), matches!(v, DataObject::Can(v) if v.is_can_fd())))
+        .collect::<Vec<_>>();
+    assert_eq!(received, vec![(1, false), (0, true), (1, false)]);
+    driver.close();
+
+    // the frames are sorted by hardware timestamp even if the device returns them out of order
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCANFD_200U, 2)?;
+    driver.set_receive_merge(true)?;
+    let objects = [
+        ZCanDataObj::from(ZCanFrameV2::new(0x7E8, 1, [0x02], Default::default(), 2000)?),
+        ZCanDataObj::from(ZCanFrameV2::new(0x7E9, 0, [0x01], Default::default(), 1000)?),
+        ZCanDataObj::from(ZCanFrameV2::new(0x7EA, 0, [0x04], Default::default(), 4000)?),
+        ZCanDataObj::from(ZCanFdFrameV1::new(0x7EB, 1, [0x03], Default::default(), 3000)?),
+    ];
+    stub.push_receive("VCI_ReceiveData", 0, &objects);
+    let received = driver.receive_merged(10, Some(0))?;
+    let received = received.iter()
+        .map(|v| match v {
+            DataObject::Can(v) => (v.channel(), v.data()[0], v.timestamp()),
+            v => panic!("unexpected object: {:?}", v),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(received.iter().map(|(c, d, _)| (*c, *d)).collect::<Vec<_>>(), vec![(0, 1), (1, 2), (1, 3), (0, 4)]);
+    // the channels are synchronized with the same clock
+    assert_eq!(received[1].2 - received[0].2, 1000);
+    assert_eq!(received[3].2 - received[2].2, 1000);
+    driver.set_receive_merge(false)?;
+
+    // transmit partially
+    stub.set_result("VCI_Transmit", 1);
+    let frames = vec![
+        new_message(0x7E0, false, &[0x01], false)?,
+        new_message(0x7E0, false, &[0x02], false)?,
+    ];
+    assert_eq!(driver.transmit_can(0, frames)?, 1);
+    assert_eq!(stub.record_count("VCI_Transmit", 0), 1);
+
+    stub.set_result("VCI_ReadCANStatus", 0);
+    assert!(matches!(
+        driver.read_can_chl_status(0),
+        Err(ZCanError::MethodExecuteFailed(name, 0)) if name == "VCI_ReadCANStatus"
+    ));
+    driver.close();
+    assert_eq!(stub.call_count("VCI_CloseDevice"), 3);
+
+    stub.set_result("VCI_OpenDevice", 0);
+    let mut driver = ZCanDriver::new(ZCanDeviceType::ZCAN_USBCANFD_200U as u32, 0, None)?;
+    assert!(matches!(
+        driver.open(),
+        Err(ZCanError::MethodExecuteFailed(name, 0)) if name == "VCI_OpenDevice"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn stub_partial_receive() -> anyhow::Result<()> {
+    let stub = Stub::new(&[LibraryFamily::USBCAN, LibraryFamily::USBCANFD, LibraryFamily::USBCANFD_800U]);
+    let data = (0..12).collect::<Vec<u8>>();
+    // the library returns less frames than requested, only the received frames are returned.
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCAN2, 2)?;
+    let frames = [<ZCanFrameV1 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &[0x01], false)?, 0)?];
+    stub.push_receive("VCI_Receive", 0, &frames);
+    assert_eq!(driver.receive_can(0, 5, None)?.len(), 1);
+    driver.close();
+
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCANFD_200U, 2)?;
+    let frames = [<ZCanFrameV2 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &[0x01], false)?, 0)?];
+    stub.push_receive("VCI_Receive", 0, &frames);
+    assert_eq!(driver.receive_can(0, 5, None)?.len(), 1);
+    let frames = [<ZCanFdFrameV1 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &data, true)?, 0)?];
+    stub.push_receive("VCI_ReceiveFD", 0, &frames);
+    let received = driver.receive_canfd(0, 5, None)?;
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].data(), data.as_slice());
+    driver.close();
+
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCANFD_800U, 2)?;
+    let frames = [<ZCanFrameV3 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &[0x01], false)?, 0)?];
+    stub.push_receive("ZCAN_Receive", 0, &frames);
+    assert_eq!(driver.receive_can(0, 5, None)?.len(), 1);
+    let frames = [<ZCanFdFrameV2 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &data, true)?, 0)?];
+    stub.push_receive("ZCAN_ReceiveFD", 0, &frames);
+    assert_eq!(driver.receive_canfd(0, 5, None)?.len(), 1);
+    driver.close();
+
+    Ok(())
+}
+
+#[test]
+fn stub_usbcan_4e() -> anyhow::Result<()> {
+    let stub = Stub::new(&[LibraryFamily::USBCAN_4E]);
+    let info = ZDeviceInfo::try_from(&zlgcan_common::device::DeriveInfo::new(false, 2))?;
+    stub.set_device_info(&info);
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCAN_4E_U, 2)?;
+    assert!(!driver.device_info()?.canfd());
+    assert_eq!(stub.value("info/channel/channel_0/baud_rate").as_deref(), Some("500000"));
+    assert_eq!(stub.value("info/channel/channel_1/baud_rate").as_deref(), Some("500000"));
+    assert_eq!(stub.call_count("ReleaseIProperty"), 1);
+
+    let msg = new_message(0x123, false, &[0x01, 0x02], false)?;
+    assert_eq!(driver.transmit_can(1, vec![msg])?, 1);
+    let frame: ZCanFrameV3 = stub.pop_record("ZCAN_Transmit", 1).expect("frame is not transmitted");
+    let frame = <CanMessage as TryFrom<ZCanFrameV3, u64>>::try_from(frame, 0)?;
+    assert_eq!(frame.id(), Id::from_bits(0x123, false));
+
+    let frames = [<ZCanFrameV3 as TryFrom<CanMessage, u64>>::try_from(new_message(0x456, false, &[0x03], false)?, 0)?];
+    stub.push_receive("ZCAN_Receive", 0, &frames);
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 1);
+    driver.clear_can_buffer(0)?;
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 0);
+
+    stub.set_result("SetValue", 1);
+    let factory = CanChlCfgFactory::new()?;
+    let cfg = factory.new_can_chl_cfg(ZCanDeviceType::ZCAN_USBCAN_4E_U as u32, ZCanChlType::CAN as u8, ZCanChlMode::Normal as u8, 500_000, Default::default())?;
+    assert!(matches!(driver.init_can_chl(vec![cfg]), Err(ZCanError::MethodExecuteFailed(_, 1))));
+    driver.close();
+
+    Ok(())
+}
+
+#[test]
+fn stub_usbcanfd_800u() -> anyhow::Result<()> {
+    let stub = Stub::new(&[LibraryFamily::USBCANFD_800U]);
+    let mut driver = open_device(ZCanDeviceType::ZCAN_USBCANFD_800U, 2)?;
+    // the resistance and controller type are set before initialization.
+    assert_eq!(stub.pop_record::<u32>("ZCAN_SetReference", 0), Some(11));
+    assert_eq!(stub.pop_record::<u32>("ZCAN_SetReference", 0), Some(1));
+    assert_eq!(stub.record_count("ZCAN_InitCAN", 1), 1);
+
+    let data = (0..20).collect::<Vec<u8>>();
+    let msg = new_message(0x18DA10F1, true, &data, true)?;
+    assert_eq!(driver.transmit_canfd(0, vec![msg])?, 1);
+    let frame: ZCanFdFrameV2 = stub.pop_record("ZCAN_TransmitFD", 0).expect("frame is not transmitted");
+    let frame = <CanMessage as TryFrom<ZCanFdFrameV2, u64>>::try_from(frame, 0)?;
+    assert_eq!(frame.id(), Id::from_bits(0x18DA10F1, true));
+    assert_eq!(frame.data(), data.as_slice());
+
+    let frames = [
+        <ZCanFrameV3 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &[0x01], false)?, 0)?,
+    ];
+    stub.push_receive("ZCAN_Receive", 1, &frames);
+    let frames = [
+        <ZCanFdFrameV2 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &data, true)?, 0)?,
+    ];
+    stub.push_receive("ZCAN_ReceiveFD", 1, &frames);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::ALL)?, 2);
+    assert_eq!(driver.receive_can(1, 2, None)?.len(), 1);
+    assert_eq!(driver.receive_canfd(1, 2, None)?[0].data(), data.as_slice());
+
+    // the frames of all channels are received by the device handler
+    while stub.pop_record::<u32>("ZCAN_SetReference", 0).is_some() {}
+    driver.set_receive_merge(true)?;
+    assert_eq!(stub.pop_record::<u32>("ZCAN_SetReference", 0), Some(17));
+    let mut frame = <ZCanFdFrameV2 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E9, false, &data, true)?, 0)?;
+    frame.update_channel(0);
+    let objects = [
+        ZCanDataObjV2::from(<ZCanFrameV3 as TryFrom<CanMessage, u64>>::try_from(new_message(0x7E8, false, &[0x01], false)?, 0)?),
+        ZCanDataObjV2::from(frame),
+    ];
+    stub.push_receive("ZCAN_ReceiveData", 0, &objects);
+    let received = driver.receive_merged(10, Some(0))?;
+    let received = received.iter()
+        .map(|v| (v.channel(), matches!(v, DataObject::Can(v) if v.is_can_fd())))
+        .collect::<Vec<_>>();
+    assert_eq!(received, vec![(1, false), (0, true)]);
+
+    driver.reset_can_chl(0)?;
+    stub.set_result("ZCAN_InitCAN", 0);
+    let factory = CanChlCfgFactory::new()?;
+    let cfg = factory.new_can_chl_cfg(ZCanDeviceType::ZCAN_USBCANFD_800U as u32, ZCanChlType::CANFD_ISO as u8, ZCanChlMode::Normal as u8, 500_000, Default::default())?;
+    assert!(matches!(
+        driver.init_can_chl(vec![cfg]),
+        Err(ZCanError::MethodExecuteFailed(name, 0)) if name == "ZCAN_InitCAN"
+    ));
+    driver.close();
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+mod utils;
+
+use std::thread::sleep;
+use std::time::Duration;
+use isotp_rs::can::{frame::{Direct, Frame}, identifier::Id};
+use isotp_rs::device::SyncDevice;
+use zlgcan_common::can::{CanChlCfgExt, CanChlCfgFactory, CanMessage, ZCanChlMode, ZCanChlType, ZCanFrameType, ZCanTxMode};
+use zlgcan_common::data::DataObject;
+use zlgcan_common::device::{DeriveInfo, ZCanDeviceType};
+use zlgcan_common::error::ZCanError;
+use zlgcan_driver::driver::{ZCanDriver, ZDevice};
+use zlgcan_driver::extends::{AscReader, AscWriter, BlfReader, BlfWriter, ZCanSync};
+use self::utils::canfd_device2;
+
+#[test]
+fn virtual_device() -> anyhow::Result<()> {
+    let dev_type = ZCanDeviceType::ZCAN_VIRTUAL_DEVICE;
+    canfd_device2(dev_type, 2, 2, 0, 1)?;
+    Ok(())
+}
+
+#[test]
+fn virtual_device_derive() -> anyhow::Result<()> {
+    let dev_type = ZCanDeviceType::ZCAN_VIRTUAL_DEVICE;
+    let dev_idx = 1;
+    let channels = 4;
+
+    let mut driver = ZCanDriver::new(dev_type as u32, dev_idx, Some(DeriveInfo::new(false, channels)))?;
+    driver.open()?;
+    assert!(driver.is_online()?);
+    let dev_info = driver.device_info()?;
+    assert_eq!(dev_info.can_channels(), channels);
+    assert!(!dev_info.canfd());
+
+    let factory = CanChlCfgFactory::new()?;
+    let mut cfg = Vec::new();
+    for _ in 0..channels {
+        cfg.push(factory.new_can_chl_cfg(dev_type as u32, ZCanChlType::CAN as u8, ZCanChlMode::Normal as u8, 500_000, CanChlCfgExt::default())?);
+    }
+    driver.init_can_chl(cfg)?;
+    let status = driver.read_can_chl_status(0)?;
+    assert_eq!((status.regRECounter, status.regTECounter), (0, 0));
+    driver.read_can_chl_error(0)?;
+    assert!(driver.read_channel_error(0)?.is_empty());
+
+    let mut msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_can(2, vec![msg.clone()])?, 1);
+    for channel in 0..channels {
+        let expect = if channel == 2 { 0 } else { 1 };
+        assert_eq!(driver.get_can_num(channel, ZCanFrameType::CAN)?, expect);
+    }
+
+    let frames = driver.receive_can(0, 10, Some(0))?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].channel(), 0);
+    assert_eq!(frames[0].direct(), Direct::Receive);
+    assert_eq!(frames[0].data(), [0x02, 0x10, 0x01].as_slice());
+
+    msg.set_tx_mode(ZCanTxMode::SelfReception as u8);
+    assert_eq!(driver.transmit_can(2, vec![msg.clone()])?, 1);
+    let frames = driver.receive_can(2, 10, Some(0))?;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].direct(), Direct::Transmit);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::CAN)?, 2);
+
+    driver.clear_can_buffer(1)?;
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::CAN)?, 0);
+    assert!(driver.receive_can(1, 10, Some(10))?.is_empty());
+
+    assert!(matches!(driver.transmit_canfd(0, vec![msg]), Err(ZCanError::MethodNotSupported)));
+
+    driver.reset_can_chl(3)?;
+    assert!(matches!(driver.get_can_num(3, ZCanFrameType::CAN), Err(ZCanError::ChannelNotOpened)));
+
+    driver.close();
+    Ok(())
+}
+
+#[test]
+fn virtual_device_channel() -> anyhow::Result<()> {
+    let dev_type = ZCanDeviceType::ZCAN_VIRTUAL_DEVICE;
+    let dev_idx = 4;
+
+    let mut driver = ZCanDriver::new(dev_type as u32, dev_idx, Some(DeriveInfo::new(false, 3)))?;
+    driver.open()?;
+
+    let factory = CanChlCfgFactory::new()?;
+    driver.init_can_channels([
+        (0, factory.builder(dev_type, 500_000).build()?),
+        (2, factory.builder(dev_type, 500_000).build()?),
+    ])?;
+    assert!(matches!(driver.get_can_num(1, ZCanFrameType::CAN), Err(ZCanError::ChannelNotOpened)));
+
+    let msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    assert_eq!(driver.transmit_can(0, vec![msg.clone()])?, 1);
+    assert_eq!(driver.get_can_num(2, ZCanFrameType::CAN)?, 1);
+
+    // the other channels keep running
+    driver.reconfigure(0, factory.builder(dev_type, 250_000).build()?)?;
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 0);
+    assert_eq!(driver.get_can_num(2, ZCanFrameType::CAN)?, 1);
+    assert!(matches!(driver.reconfigure(1, factory.builder(dev_type, 250_000).build()?), Err(ZCanError::ChannelNotOpened)));
+    assert!(matches!(driver.init_can_channel(3, factory.builder(dev_type, 250_000).build()?), Err(ZCanError::ConfigurationError(_))));
+
+    driver.init_can_channel(1, factory.builder(dev_type, 500_000).build()?)?;
+    assert_eq!(driver.transmit_can(2, vec![msg])?, 1);
+    assert_eq!(driver.get_can_num(0, ZCanFrameType::CAN)?, 1);
+    assert_eq!(driver.get_can_num(1, ZCanFrameType::CAN)?, 1);
+
+    // the frames are received separately when the data object is not supported
+    let objects = driver.receive_all(0, 10, Some(0))?;
+    assert_eq!(objects.len(), 1);
+    assert!(matches!(&objects[0], DataObject::Can(v) if v.channel() == 0));
+    assert!(matches!(driver.transmit_all(0, objects), Err(ZCanError::MethodNotSupported)));
+    assert!(matches!(driver.set_receive_merge(true), Err(ZCanError::MethodNotSupported)));
+    assert!(!driver.receive_merge());
+
+    driver.close();
+    Ok(())
+}
+
+#[test]
+fn virtual_device_asc() -> anyhow::Result<()> {
+    let dev_type = ZCanDeviceType::ZCAN_VIRTUAL_DEVICE;
+    let path = std::env::temp_dir().join(format!("zlgcan-virtual-{}.asc", std::process::id()));
+
+    let mut driver = ZCanDriver::new(dev_type as u32, 5, Some(DeriveInfo::new(false, 2)))?;
+    driver.open()?;
+    let factory = CanChlCfgFactory::new()?;
+    driver.init_can_channels([
+        (0, factory.builder(dev_type, 500_000).build()?),
+        (1, factory.builder(dev_type, 500_000).build()?),
+    ])?;
+
+    let mut device = ZCanSync::new(driver);
+    assert!(device.register_listener("asc".into(), Box::new(AscWriter::create(&path)?)));
+    device.sync_start(100);
+    let mut msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    msg.set_channel(0);
+    device.sender().send(msg)?;
+    sleep(Duration::from_millis(100));
+    // the trigger block is ended when the writer is dropped
+    assert!(device.unregister_listener("asc".into()));
+    device.close();
+
+    let messages = AscReader::open(&path)?.collect::<Result<Vec<_>, _>>()?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(messages.len(), 2);
+    assert_eq!((messages[0].channel(), messages[0].direct()), (0, Direct::Transmit));
+    assert_eq!((messages[1].channel(), messages[1].direct()), (1, Direct::Receive));
+    assert!(messages.iter().all(|v| v.data() == [0x02, 0x10, 0x01]));
+    assert!(messages[0].timestamp() <= messages[1].timestamp());
+
+    Ok(())
+}
+
+#[test]
+fn virtual_device_blf() -> anyhow::Result<()> {
+    let dev_type = ZCanDeviceType::ZCAN_VIRTUAL_DEVICE;
+    let path = std::env::temp_dir().join(format!("zlgcan-virtual-{}.blf", std::process::id()));
+
+    let mut driver = ZCanDriver::new(dev_type as u32, 6, Some(DeriveInfo::new(false, 2)))?;
+    driver.open()?;
+    let factory = CanChlCfgFactory::new()?;
+    driver.init_can_channels([
+        (0, factory.builder(dev_type, 500_000).build()?),
+        (1, factory.builder(dev_type, 500_000).build()?),
+    ])?;
+
+    let mut device = ZCanSync::new(driver);
+    assert!(device.register_listener("blf".into(), Box::new(BlfWriter::create(&path)?)));
+    device.sync_start(100);
+    let mut msg = CanMessage::new(Id::from_bits(0x7DF, false), [0x02, 0x10, 0x01].as_slice())
+        .ok_or(ZCanError::Other("invalid data length".to_string()))?;
+    msg.set_channel(0);
+    device.sender().send(msg)?;
+    sleep(Duration::from_millis(100));
+    // the objects are compressed and the header is updated when the writer is dropped
+    assert!(device.unregister_listener("blf".into()));
+    device.close();
+
+    let objects = BlfReader::open(&path)?.collect::<Result<Vec<_>, _>>()?;
+    std::fs::remove_file(&path)?;
+    let messages = objects.into_iter()
+        .filter_map(|v| match v {
+            DataObject::Can(v) => Some(v),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(messages.len(), 2);
+    assert_eq!((messages[0].channel(), messages[0].direct()), (0, Direct::Transmit));
+    assert_eq!((messages[1].channel(), messages[1].direct()), (1, Direct::Receive));
+    assert!(messages.iter().all(|v| v.data() == [0x02, 0x10, 0x01]));
+    assert!(messages[0].timestamp() <= messages[1].timestamp());
+
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! A stub of the ZLGCAN vendor libraries for testing without hardware.
+//!
+//! The library exports the `VCI_*` and `ZCAN_*` symbols of `libusbcan.so`, `libusbcan-4e.so`,
+//! `libusbcan-8e.so`, `libusbcanfd.so` and `libusbcanfd800u.so`, so it can be loaded in place of any of them.
+//! The frame layout and status code are selected by the device type that passed in(or encoded in handler).
+//!
+//! The behaviour is scripted by the `STUB_*` symbols:
+//! * `STUB_PushReceive` queues the frames returned by a receive method, or the status returned by a read method;
+//! * `STUB_PopRecord` takes the frames passed to a transmit method, or the configuration passed to an init method;
+//! * `STUB_SetResult` injects the return code of a method, a handler or the received frame count;
+//! * `STUB_SetValue` and `STUB_GetValue` access the value tree of `IProperty`;
+//! * `STUB_SetDeviceInfo` sets the device information.
+//!
+//! The states are keyed by method name and channel, the device index is ignored.
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+
+mod vci;
+mod zcan;
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_char, c_uint, c_void, CStr, CString};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use zlgcan_common::device::{DeriveInfo, ZDeviceInfo};
+
+#[derive(Default)]
+struct Stub {
+    results: HashMap<String, c_uint>,
+    calls: HashMap<String, c_uint>,
+    receives: HashMap<(String, c_uint), VecDeque<Vec<u8>>>,
+    records: HashMap<(String, c_uint), VecDeque<Vec<u8>>>,
+    values: HashMap<String, CString>,
+    device_info: Option<ZDeviceInfo>,
+    property_ok: c_uint,
+}
+
+fn stub() -> MutexGuard<'static, Stub> {
+    static STUB: OnceLock<Mutex<Stub>> = OnceLock::new();
+    STUB.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+#[inline]
+unsafe fn c_str(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return Default::default();
+    }
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+/// Count the calling of method and return the injected result or `default`.
+fn invoke(name: &str, default: c_uint) -> c_uint {
+    let mut stub = stub();
+    *stub.calls.entry(name.to_owned()).or_default() += 1;
+    stub.results.get(name).copied().unwrap_or(default)
+}
+
+/// Record the `count` elements with size `elem` into the records of method.
+unsafe fn record(name: &str, channel: c_uint, data: *const c_void, elem: usize, count: c_uint) {
+    if data.is_null() {
+        return;
+    }
+    let data = std::slice::from_raw_parts(data as *const u8, elem * count as usize);
+    let mut stub = stub();
+    let records = stub.records.entry((name.to_owned(), channel)).or_default();
+    data.chunks(elem)
+        .for_each(|v| records.push_back(v.to_vec()));
+}
+
+/// Take at most `size` elements with size `elem` from the receive queue of method.
+unsafe fn receive(name: &str, channel: c_uint, data: *mut c_void, elem: usize, size: c_uint) -> c_uint {
+    let size = invoke(name, size).min(size);
+    if data.is_null() {
+        return 0;
+    }
+    let mut stub = stub();
+    let Some(queue) = stub.receives.get_mut(&(name.to_owned(), channel)) else {
+        return 0;
+    };
+    let mut count = 0;
+    while count < size {
+        match queue.pop_front() {
+            Some(v) => {
+                let ptr = (data as *mut u8).add(count as usize * elem);
+                std::ptr::copy_nonoverlapping(v.as_ptr(), ptr, v.len().min(elem));
+                count += 1;
+            },
+            None => break,
+        }
+    }
+    count
+}
+
+/// Take the first element with size `elem` from the receive queue of method, if it is existed.
+unsafe fn read(name: &str, channel: c_uint, data: *mut c_void, elem: usize) {
+    receive(name, channel, data, elem, 1);
+}
+
+fn receive_num(name: &str, channel: c_uint) -> c_uint {
+    stub().receives.get(&(name.to_owned(), channel))
+        .map(|v| v.len() as c_uint)
+        .unwrap_or_default()
+}
+
+fn clear_receive(name: &str, channel: c_uint) {
+    stub().receives.remove(&(name.to_owned(), channel));
+}
+
+fn device_info() -> ZDeviceInfo {
+    let stub = stub();
+    match stub.device_info {
+        Some(v) => v,
+        None => ZDeviceInfo::try_from(&DeriveInfo::new(true, 2))
+            .unwrap_or_default(),
+    }
+}
+
+unsafe extern "C" fn set_value(path: *const c_char, value: *const c_char) -> i32 {
+    let ok = stub().property_ok;
+    let ret = invoke("SetValue", ok);
+    if ret == ok {
+        STUB_SetValue(path, value);
+    }
+    ret as i32
+}
+
+unsafe extern "C" fn get_value(path: *const c_char) -> *const c_char {
+    invoke("GetValue", 1);
+    STUB_GetValue(path)
+}
+
+/// Reset all states of the stub.
+#[no_mangle]
+pub extern "C" fn STUB_Reset() {
+    *stub() = Default::default();
+}
+
+/// Inject the return code of method, it is returned until `STUB_ClearResult` or `STUB_Reset` is called.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_SetResult(name: *const c_char, code: c_uint) {
+    stub().results.insert(c_str(name), code);
+}
+
+/// Clear the injected return code of method.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_ClearResult(name: *const c_char) {
+    stub().results.remove(&c_str(name));
+}
+
+/// Get the calling count of method.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_CallCount(name: *const c_char) -> c_uint {
+    stub().calls.get(&c_str(name))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Queue `count` elements with size `elem` that returned by the receive or read method of channel.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_PushReceive(name: *const c_char, channel: c_uint, data: *const c_void, elem: c_uint, count: c_uint) {
+    if data.is_null() {
+        return;
+    }
+    let data = std::slice::from_raw_parts(data as *const u8, (elem * count) as usize);
+    let mut stub = stub();
+    let queue = stub.receives.entry((c_str(name), channel)).or_default();
+    data.chunks(elem as usize)
+        .for_each(|v| queue.push_back(v.to_vec()));
+}
+
+/// Get the count of elements recorded by the method of channel.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_RecordCount(name: *const c_char, channel: c_uint) -> c_uint {
+    stub().records.get(&(c_str(name), channel))
+        .map(|v| v.len() as c_uint)
+        .unwrap_or_default()
+}
+
+/// Take the first element recorded by the method of channel, return the copied size.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_PopRecord(name: *const c_char, channel: c_uint, data: *mut c_void, size: c_uint) -> c_uint {
+    let mut stub = stub();
+    match stub.records.get_mut(&(c_str(name), channel)).and_then(|v| v.pop_front()) {
+        Some(v) => {
+            let len = v.len().min(size as usize);
+            if !data.is_null() {
+                std::ptr::copy_nonoverlapping(v.as_ptr(), data as *mut u8, len);
+            }
+            len as c_uint
+        },
+        None => 0,
+    }
+}
+
+/// Set the device information returned by `VCI_ReadBoardInfo` and `ZCAN_GetDeviceInf`.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_SetDeviceInfo(info: *const ZDeviceInfo) {
+    stub().device_info = info.as_ref().copied();
+}
+
+/// Set the value of path in the value tree of `IProperty`, the path is removed if `value` is null.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_SetValue(path: *const c_char, value: *const c_char) {
+    let path = c_str(path);
+    if value.is_null() {
+        stub().values.remove(&path);
+        return;
+    }
+    let value = CString::new(c_str(value)).unwrap_or_default();
+    stub().values.insert(path, value);
+}
+
+/// Get the value of path in the value tree of `IProperty`, return null if it is not existed.
+/// The pointer is valid until the value is set again.
+#[no_mangle]
+pub unsafe extern "C" fn STUB_GetValue(path: *const c_char) -> *const c_char {
+    stub().values.get(&c_str(path))
+        .map(|v| v.as_ptr())
+        .unwrap_or(std::ptr::null())
+}
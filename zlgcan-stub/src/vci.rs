@@ -0,0 +1,212 @@
+//! The `VCI_*` symbols of `libusbcan.so` and `libusbcanfd.so`.
+use std::ffi::{c_uint, c_void};
+use std::mem::size_of;
+use zlgcan_common::can::{ZCanChlCfgV2, ZCanChlError, ZCanChlStatus, ZCanFdFrameV1, ZCanFrameV1, ZCanFrameV2};
+use zlgcan_common::data::ZCanDataObj;
+use zlgcan_common::device::{ZCanDeviceType, ZDeviceInfo};
+use zlgcan_common::lin::{ZLinChlCfg, ZLinFrame, ZLinPublish, ZLinSubscribe};
+use crate::{clear_receive, device_info, invoke, read, receive, receive_num, record};
+
+const STATUS_OK: c_uint = 1;
+const CANFD_FLAG: c_uint = 0x8000_0000;
+
+/// The USBCAN1 and USBCAN2 use `ZCanFrameV1`, the others use `ZCanFrameV2`.
+#[inline]
+fn frame_size(dev_type: c_uint) -> usize {
+    if dev_type == ZCanDeviceType::ZCAN_USBCAN1 as c_uint
+        || dev_type == ZCanDeviceType::ZCAN_USBCAN2 as c_uint {
+        size_of::<ZCanFrameV1>()
+    }
+    else {
+        size_of::<ZCanFrameV2>()
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_OpenDevice(_dev_type: c_uint, _dev_idx: c_uint, _reserved: c_uint) -> c_uint {
+    invoke("VCI_OpenDevice", STATUS_OK)
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_CloseDevice(_dev_type: c_uint, _dev_idx: c_uint) -> c_uint {
+    invoke("VCI_CloseDevice", STATUS_OK)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_InitCAN(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, cfg: *const ZCanChlCfgV2) -> c_uint {
+    let ret = invoke("VCI_InitCAN", STATUS_OK);
+    if ret == STATUS_OK {
+        record("VCI_InitCAN", channel, cfg as *const c_void, size_of::<ZCanChlCfgV2>(), 1);
+    }
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_ReadBoardInfo(_dev_type: c_uint, _dev_idx: c_uint, info: *mut ZDeviceInfo) -> c_uint {
+    let ret = invoke("VCI_ReadBoardInfo", STATUS_OK);
+    if ret == STATUS_OK && !info.is_null() {
+        *info = device_info();
+    }
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_ReadErrInfo(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, err: *mut ZCanChlError) -> c_uint {
+    read("VCI_ReadErrInfo", channel, err as *mut c_void, size_of::<ZCanChlError>());
+    invoke("VCI_ReadErrInfo", STATUS_OK)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_ReadCANStatus(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, status: *mut ZCanChlStatus) -> c_uint {
+    read("VCI_ReadCANStatus", channel, status as *mut c_void, size_of::<ZCanChlStatus>());
+    invoke("VCI_ReadCANStatus", STATUS_OK)
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_GetReference(_dev_type: c_uint, _dev_idx: c_uint, _channel: c_uint, _cmd: c_uint, _value: *mut c_void) -> c_uint {
+    invoke("VCI_GetReference", STATUS_OK)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_SetReference(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, cmd: c_uint, _value: *const c_void) -> c_uint {
+    let ret = invoke("VCI_SetReference", STATUS_OK);
+    if ret == STATUS_OK {
+        record("VCI_SetReference", channel, &cmd as *const c_uint as *const c_void, size_of::<c_uint>(), 1);
+    }
+    ret
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_GetReceiveNum(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint) -> c_uint {
+    let num = if channel & CANFD_FLAG > 0 {
+        receive_num("VCI_ReceiveFD", channel & !CANFD_FLAG)
+    }
+    else {
+        receive_num("VCI_Receive", channel)
+    };
+    invoke("VCI_GetReceiveNum", num)
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_ClearBuffer(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint) -> c_uint {
+    let ret = invoke("VCI_ClearBuffer", STATUS_OK);
+    if ret == STATUS_OK {
+        clear_receive("VCI_Receive", channel);
+        clear_receive("VCI_ReceiveFD", channel);
+    }
+    ret
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_StartCAN(_dev_type: c_uint, _dev_idx: c_uint, _channel: c_uint) -> c_uint {
+    invoke("VCI_StartCAN", STATUS_OK)
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_ResetCAN(_dev_type: c_uint, _dev_idx: c_uint, _channel: c_uint) -> c_uint {
+    invoke("VCI_ResetCAN", STATUS_OK)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_Transmit(dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, frames: *const c_void, len: c_uint) -> c_uint {
+    let ret = invoke("VCI_Transmit", len).min(len);
+    record("VCI_Transmit", channel, frames, frame_size(dev_type), ret);
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_TransmitFD(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, frames: *const ZCanFdFrameV1, len: c_uint) -> c_uint {
+    let ret = invoke("VCI_TransmitFD", len).min(len);
+    record("VCI_TransmitFD", channel, frames as *const c_void, size_of::<ZCanFdFrameV1>(), ret);
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_Receive(dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, frames: *mut c_void, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("VCI_Receive", channel, frames, frame_size(dev_type), size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_ReceiveFD(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, frames: *mut ZCanFdFrameV1, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("VCI_ReceiveFD", channel, frames as *mut c_void, size_of::<ZCanFdFrameV1>(), size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_TransmitData(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, data: *const ZCanDataObj, len: c_uint) -> c_uint {
+    let ret = invoke("VCI_TransmitData", len).min(len);
+    record("VCI_TransmitData", channel, data as *const c_void, size_of::<ZCanDataObj>(), ret);
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_ReceiveData(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, data: *mut ZCanDataObj, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("VCI_ReceiveData", channel, data as *mut c_void, size_of::<ZCanDataObj>(), size)
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_Debug(_debug: c_uint) -> c_uint {
+    invoke("VCI_Debug", STATUS_OK)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_InitLIN(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, cfg: *const ZLinChlCfg) -> c_uint {
+    let ret = invoke("VCI_InitLIN", STATUS_OK);
+    if ret == STATUS_OK {
+        record("VCI_InitLIN", channel, cfg as *const c_void, size_of::<ZLinChlCfg>(), 1);
+    }
+    ret
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_StartLIN(_dev_type: c_uint, _dev_idx: c_uint, _channel: c_uint) -> c_uint {
+    invoke("VCI_StartLIN", STATUS_OK)
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_ResetLIN(_dev_type: c_uint, _dev_idx: c_uint, _channel: c_uint) -> c_uint {
+    invoke("VCI_ResetLIN", STATUS_OK)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_TransmitLIN(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, frames: *const ZLinFrame, len: c_uint) -> c_uint {
+    let ret = invoke("VCI_TransmitLIN", len).min(len);
+    record("VCI_TransmitLIN", channel, frames as *const c_void, size_of::<ZLinFrame>(), ret);
+    ret
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_GetLINReceiveNum(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint) -> c_uint {
+    invoke("VCI_GetLINReceiveNum", receive_num("VCI_ReceiveLIN", channel))
+}
+
+#[no_mangle]
+pub extern "C" fn VCI_ClearLINBuffer(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint) -> c_uint {
+    let ret = invoke("VCI_ClearLINBuffer", STATUS_OK);
+    if ret == STATUS_OK {
+        clear_receive("VCI_ReceiveLIN", channel);
+    }
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_ReceiveLIN(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, frames: *mut ZLinFrame, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("VCI_ReceiveLIN", channel, frames as *mut c_void, size_of::<ZLinFrame>(), size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_SetLINSubscribe(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, cfg: *const ZLinSubscribe, len: c_uint) -> c_uint {
+    let ret = invoke("VCI_SetLINSubscribe", STATUS_OK);
+    if ret == STATUS_OK {
+        record("VCI_SetLINSubscribe", channel, cfg as *const c_void, size_of::<ZLinSubscribe>(), len);
+    }
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn VCI_SetLINPublish(_dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, cfg: *const ZLinPublish, len: c_uint) -> c_uint {
+    let ret = invoke("VCI_SetLINPublish", STATUS_OK);
+    if ret == STATUS_OK {
+        record("VCI_SetLINPublish", channel, cfg as *const c_void, size_of::<ZLinPublish>(), len);
+    }
+    ret
+}
@@ -0,0 +1,170 @@
+//! The `ZCAN_*` symbols of `libusbcan-4e.so`, `libusbcan-8e.so` and `libusbcanfd800u.so`.
+//!
+//! The device handler is `device type << 8 | device index`,
+//! and the channel handler is `device handler << 8 | channel`.
+use std::ffi::{c_uchar, c_uint, c_void};
+use std::mem::size_of;
+use zlgcan_common::can::{ZCanChlCfgV1, ZCanChlError, ZCanChlStatus, ZCanFdFrameV2, ZCanFrameType, ZCanFrameV3};
+use zlgcan_common::data::ZCanDataObjV2;
+use zlgcan_common::device::{IProperty, ZCanDeviceType, ZDeviceInfo};
+use crate::{clear_receive, device_info, get_value, invoke, read, receive, receive_num, record, set_value, stub};
+
+static PROPERTY: IProperty = IProperty {
+    SetValue: Some(set_value),
+    GetValue: Some(get_value),
+    GetProperties: None,
+};
+
+/// The USBCANFD-800U returns 1 when succeed, the USBCAN-4E-U and USBCAN-8E-U return 0.
+#[inline]
+fn status_ok(dev_type: c_uint) -> c_uint {
+    if dev_type == ZCanDeviceType::ZCAN_USBCANFD_800U as c_uint { 1 } else { 0 }
+}
+
+#[inline]
+fn handler_status_ok(hdl: c_uint) -> c_uint {
+    let dev_hdl = if hdl > 0xFFFF { hdl >> 8 } else { hdl };
+    status_ok(dev_hdl >> 8)
+}
+
+#[inline]
+fn channel(chl_hdl: c_uint) -> c_uint {
+    chl_hdl & 0xFF
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_OpenDevice(dev_type: c_uint, dev_idx: c_uint, _reserved: c_uint) -> c_uint {
+    invoke("ZCAN_OpenDevice", (dev_type << 8) | (dev_idx & 0xFF))
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_CloseDevice(dev_hdl: c_uint) -> c_uint {
+    invoke("ZCAN_CloseDevice", handler_status_ok(dev_hdl))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_GetDeviceInf(dev_hdl: c_uint, info: *mut ZDeviceInfo) -> c_uint {
+    let ok = handler_status_ok(dev_hdl);
+    let ret = invoke("ZCAN_GetDeviceInf", ok);
+    if ret == ok && !info.is_null() {
+        *info = device_info();
+    }
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_InitCAN(dev_hdl: c_uint, channel: c_uint, cfg: *const ZCanChlCfgV1) -> c_uint {
+    let ret = invoke("ZCAN_InitCAN", (dev_hdl << 8) | (channel & 0xFF));
+    if ret != 0 {
+        record("ZCAN_InitCAN", channel, cfg as *const c_void, size_of::<ZCanChlCfgV1>(), 1);
+    }
+    ret
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_StartCAN(chl_hdl: c_uint) -> c_uint {
+    invoke("ZCAN_StartCAN", handler_status_ok(chl_hdl))
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_ResetCAN(chl_hdl: c_uint) -> c_uint {
+    invoke("ZCAN_ResetCAN", handler_status_ok(chl_hdl))
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_ClearBuffer(chl_hdl: c_uint) -> c_uint {
+    let ok = handler_status_ok(chl_hdl);
+    let ret = invoke("ZCAN_ClearBuffer", ok);
+    if ret == ok {
+        clear_receive("ZCAN_Receive", channel(chl_hdl));
+        clear_receive("ZCAN_ReceiveFD", channel(chl_hdl));
+    }
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_ReadChannelErrInfo(chl_hdl: c_uint, err: *mut ZCanChlError) -> c_uint {
+    read("ZCAN_ReadChannelErrInfo", channel(chl_hdl), err as *mut c_void, size_of::<ZCanChlError>());
+    invoke("ZCAN_ReadChannelErrInfo", handler_status_ok(chl_hdl))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_ReadChannelStatus(chl_hdl: c_uint, status: *mut ZCanChlStatus) -> c_uint {
+    read("ZCAN_ReadChannelStatus", channel(chl_hdl), status as *mut c_void, size_of::<ZCanChlStatus>());
+    invoke("ZCAN_ReadChannelStatus", handler_status_ok(chl_hdl))
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_GetReceiveNum(chl_hdl: c_uint, can_type: c_uchar) -> c_uint {
+    let channel = channel(chl_hdl);
+    let num = match ZCanFrameType::try_from(can_type) {
+        Ok(ZCanFrameType::CAN) => receive_num("ZCAN_Receive", channel),
+        Ok(ZCanFrameType::CANFD) => receive_num("ZCAN_ReceiveFD", channel),
+        Ok(ZCanFrameType::ALL) => receive_num("ZCAN_Receive", channel) + receive_num("ZCAN_ReceiveFD", channel),
+        Err(_) => 0,
+    };
+    invoke("ZCAN_GetReceiveNum", num)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_Transmit(chl_hdl: c_uint, frames: *const ZCanFrameV3, len: c_uint) -> c_uint {
+    let ret = invoke("ZCAN_Transmit", len).min(len);
+    record("ZCAN_Transmit", channel(chl_hdl), frames as *const c_void, size_of::<ZCanFrameV3>(), ret);
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_Receive(chl_hdl: c_uint, frames: *mut ZCanFrameV3, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("ZCAN_Receive", channel(chl_hdl), frames as *mut c_void, size_of::<ZCanFrameV3>(), size)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_TransmitFD(chl_hdl: c_uint, frames: *const ZCanFdFrameV2, len: c_uint) -> c_uint {
+    let ret = invoke("ZCAN_TransmitFD", len).min(len);
+    record("ZCAN_TransmitFD", channel(chl_hdl), frames as *const c_void, size_of::<ZCanFdFrameV2>(), ret);
+    ret
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_ReceiveFD(chl_hdl: c_uint, frames: *mut ZCanFdFrameV2, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("ZCAN_ReceiveFD", channel(chl_hdl), frames as *mut c_void, size_of::<ZCanFdFrameV2>(), size)
+}
+
+/// The objects of all channels are queued on channel 0.
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_ReceiveData(_dev_hdl: c_uint, objects: *mut ZCanDataObjV2, size: c_uint, _timeout: c_uint) -> c_uint {
+    receive("ZCAN_ReceiveData", 0, objects as *mut c_void, size_of::<ZCanDataObjV2>(), size)
+}
+
+/// Return null when the injected result is 0.
+#[no_mangle]
+pub extern "C" fn GetIProperty(hdl: c_uint) -> *const IProperty {
+    match invoke("GetIProperty", 1) {
+        0 => std::ptr::null(),
+        _ => {
+            stub().property_ok = handler_status_ok(hdl);
+            &PROPERTY
+        },
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ReleaseIProperty(_p: *const IProperty) -> c_uint {
+    let ok = stub().property_ok;
+    invoke("ReleaseIProperty", ok)
+}
+
+#[no_mangle]
+pub extern "C" fn ZCAN_GetReference(dev_type: c_uint, _dev_idx: c_uint, _channel: c_uint, _cmd: c_uint, _value: *mut c_void) -> c_uint {
+    invoke("ZCAN_GetReference", status_ok(dev_type))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ZCAN_SetReference(dev_type: c_uint, _dev_idx: c_uint, channel: c_uint, cmd: c_uint, _value: *const c_void) -> c_uint {
+    let ok = status_ok(dev_type);
+    let ret = invoke("ZCAN_SetReference", ok);
+    if ret == ok {
+        record("ZCAN_SetReference", channel, &cmd as *const c_uint as *const c_void, size_of::<c_uint>(), 1);
+    }
+    ret
+}